@@ -42,6 +42,17 @@ impl FieldSerde for M31Ext3 {
         }
     }
 
+    #[inline(always)]
+    fn try_deserialize_from<R: Read>(mut reader: R) -> Option<Self> {
+        Some(M31Ext3 {
+            v: [
+                M31::try_deserialize_from(&mut reader)?,
+                M31::try_deserialize_from(&mut reader)?,
+                M31::try_deserialize_from(&mut reader)?,
+            ],
+        })
+    }
+
     #[inline(always)]
     fn deserialize_from_ecc_format<R: Read>(mut reader: R) -> Self {
         let mut buf = [0u8; 32];
@@ -44,6 +44,17 @@ impl FieldSerde for SimdM31Ext3 {
         }
     }
 
+    #[inline(always)]
+    fn try_deserialize_from<R: Read>(mut reader: R) -> Option<Self> {
+        Some(Self {
+            v: [
+                SimdM31::try_deserialize_from(&mut reader)?,
+                SimdM31::try_deserialize_from(&mut reader)?,
+                SimdM31::try_deserialize_from(&mut reader)?,
+            ],
+        })
+    }
+
     #[inline(always)]
     fn deserialize_from_ecc_format<R: Read>(mut reader: R) -> Self {
         Self {
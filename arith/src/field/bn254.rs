@@ -125,6 +125,13 @@ impl FieldSerde for Fr {
         Fr::from_bytes(&buffer).unwrap()
     }
 
+    #[inline(always)]
+    fn try_deserialize_from<R: Read>(mut reader: R) -> Option<Self> {
+        let mut buffer = [0u8; 32];
+        reader.read_exact(&mut buffer).ok()?;
+        Option::from(Fr::from_bytes(&buffer))
+    }
+
     #[inline(always)]
     fn deserialize_from_ecc_format<R: Read>(reader: R) -> Self {
         Fr::deserialize_from(reader) // same as deserialize_from
@@ -25,6 +25,16 @@ pub(crate) fn mod_reduce_u32(x: u32) -> u32 {
     (x & M31_MOD) + (x >> 31)
 }
 
+/// The canonical representative of a value below 2^31, in which M31_MOD stands for zero
+#[inline]
+pub(crate) fn to_canonical_u32(x: u32) -> u32 {
+    if x >= M31_MOD {
+        x - M31_MOD
+    } else {
+        x
+    }
+}
+
 #[inline]
 fn mod_reduce_i64(x: i64) -> i64 {
     (x & M31_MOD as i64) + (x >> 31)
@@ -38,7 +48,10 @@ pub struct M31 {
 impl FieldSerde for M31 {
     #[inline(always)]
     fn serialize_into<W: Write>(&self, mut writer: W) {
-        writer.write_all(self.v.to_le_bytes().as_ref()).unwrap(); // todo: error propagation
+        // the arithmetic may leave M31_MOD for zero, the bytes are always canonical
+        writer
+            .write_all(to_canonical_u32(self.v).to_le_bytes().as_ref())
+            .unwrap(); // todo: error propagation
     }
 
     #[inline(always)]
@@ -52,9 +65,18 @@ impl FieldSerde for M31 {
     fn deserialize_from<R: Read>(mut reader: R) -> Self {
         let mut u = [0u8; 4];
         reader.read_exact(&mut u).unwrap(); // todo: error propagation
-        let mut v = u32::from_le_bytes(u);
-        v = mod_reduce_u32(v);
-        M31 { v }
+        let v = u32::from_le_bytes(u);
+        M31 {
+            v: to_canonical_u32(mod_reduce_u32(v)),
+        }
+    }
+
+    #[inline(always)]
+    fn try_deserialize_from<R: Read>(mut reader: R) -> Option<Self> {
+        let mut u = [0u8; 4];
+        reader.read_exact(&mut u).ok()?;
+        let v = u32::from_le_bytes(u);
+        (v < M31_MOD).then_some(M31 { v })
     }
 
     #[inline(always)]
@@ -141,9 +163,10 @@ impl Field for M31 {
 
     #[inline(always)]
     fn from_uniform_bytes(bytes: &[u8; 32]) -> Self {
-        let mut v = u32::from_le_bytes(bytes[..4].try_into().unwrap());
-        v = mod_reduce_u32(v);
-        M31 { v }
+        let v = u32::from_le_bytes(bytes[..4].try_into().unwrap());
+        M31 {
+            v: to_canonical_u32(mod_reduce_u32(v)),
+        }
     }
 }
 
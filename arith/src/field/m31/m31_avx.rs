@@ -9,7 +9,7 @@ use std::{
 
 use rand::{Rng, RngCore};
 
-use crate::{Field, FieldSerde, SimdField, M31, M31_MOD};
+use crate::{field::m31::to_canonical_u32, Field, FieldSerde, SimdField, M31, M31_MOD};
 
 const M31_PACK_SIZE: usize = 16;
 const PACKED_MOD: __m512i = unsafe { transmute([M31_MOD; M31_PACK_SIZE]) };
@@ -52,8 +52,13 @@ impl FieldSerde for AVXM31 {
     #[inline(always)]
     /// serialize self into bytes
     fn serialize_into<W: Write>(&self, mut writer: W) {
-        let data = unsafe { transmute::<__m512i, [u8; 64]>(self.v) };
-        writer.write_all(&data).unwrap();
+        // the arithmetic may leave M31_MOD for zero, the bytes are always canonical
+        let lanes = unsafe { transmute::<__m512i, [u32; M31_PACK_SIZE]>(self.v) };
+        lanes.iter().for_each(|&x| {
+            writer
+                .write_all(&to_canonical_u32(x).to_le_bytes())
+                .unwrap()
+        });
     }
 
     #[inline(always)]
@@ -73,6 +78,22 @@ impl FieldSerde for AVXM31 {
         }
     }
 
+    /// every lane must be reduced, the arithmetic expects values below the modulus
+    #[inline(always)]
+    fn try_deserialize_from<R: Read>(mut reader: R) -> Option<Self> {
+        let mut data = [0; 64];
+        reader.read_exact(&mut data).ok()?;
+        let lanes = unsafe { transmute::<[u8; 64], [u32; M31_PACK_SIZE]>(data) };
+        if lanes.iter().any(|&x| x >= M31_MOD) {
+            return None;
+        }
+        unsafe {
+            Some(AVXM31 {
+                v: transmute::<[u8; 64], __m512i>(data),
+            })
+        }
+    }
+
     #[inline(always)]
     fn deserialize_from_ecc_format<R: Read>(mut reader: R) -> Self {
         let mut buf = [0u8; 32];
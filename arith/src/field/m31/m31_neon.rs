@@ -9,7 +9,7 @@ use std::{
 
 use rand::{Rng, RngCore};
 
-use crate::{Field, FieldSerde, SimdField, M31, M31_MOD};
+use crate::{field::m31::to_canonical_u32, Field, FieldSerde, SimdField, M31, M31_MOD};
 
 const PACKED_MOD: uint32x4_t = unsafe { transmute([M31_MOD; 4]) };
 const PACKED_0: uint32x4_t = unsafe { transmute([0; 4]) };
@@ -78,8 +78,13 @@ impl FieldSerde for NeonM31 {
     #[inline(always)]
     /// serialize self into bytes
     fn serialize_into<W: Write>(&self, mut writer: W) {
-        let data = unsafe { transmute::<[uint32x4_t; 2], [u8; 32]>(self.v) };
-        writer.write_all(&data).unwrap();
+        // the arithmetic may leave M31_MOD for zero, the bytes are always canonical
+        let lanes = unsafe { transmute::<[uint32x4_t; 2], [u32; 8]>(self.v) };
+        lanes.iter().for_each(|&x| {
+            writer
+                .write_all(&to_canonical_u32(x).to_le_bytes())
+                .unwrap()
+        });
     }
 
     #[inline(always)]
@@ -99,6 +104,22 @@ impl FieldSerde for NeonM31 {
         }
     }
 
+    /// every lane must be reduced, the arithmetic expects values below the modulus
+    #[inline(always)]
+    fn try_deserialize_from<R: Read>(mut reader: R) -> Option<Self> {
+        let mut data = [0; 32];
+        reader.read_exact(&mut data).ok()?;
+        let lanes = unsafe { transmute::<[u8; 32], [u32; 8]>(data) };
+        if lanes.iter().any(|&x| x >= M31_MOD) {
+            return None;
+        }
+        unsafe {
+            Some(NeonM31 {
+                v: transmute::<[u8; 32], [uint32x4_t; 2]>(data),
+            })
+        }
+    }
+
     #[inline(always)]
     fn deserialize_from_ecc_format<R: Read>(mut reader: R) -> Self {
         let mut buf = [0u8; 32];
@@ -11,6 +11,12 @@ pub trait FieldSerde {
     /// deserialize bytes into field
     fn deserialize_from<R: Read>(reader: R) -> Self;
 
+    /// deserialize bytes into field, returns None if the reader ends early or the bytes are
+    /// not the canonical encoding of an element, e.g., for untrusted proofs
+    fn try_deserialize_from<R: Read>(reader: R) -> Option<Self>
+    where
+        Self: Sized;
+
     /// deserialize bytes into field following ecc format
     fn deserialize_from_ecc_format<R: Read>(_reader: R) -> Self;
 }
@@ -33,6 +39,12 @@ impl FieldSerde for u64 {
         u64::from_le_bytes(buffer)
     }
 
+    fn try_deserialize_from<R: Read>(mut reader: R) -> Option<Self> {
+        let mut buffer = [0u8; 8];
+        reader.read_exact(&mut buffer).ok()?;
+        Some(u64::from_le_bytes(buffer))
+    }
+
     fn deserialize_from_ecc_format<R: Read>(_reader: R) -> Self {
         unimplemented!("not implemented for u64")
     }
@@ -1,5 +1,7 @@
 use ark_std::test_rng;
-use halo2curves::bn256::Fr;
+use halo2curves::{bn256::Fr, ff::PrimeField};
+
+use crate::FieldSerde;
 
 use super::field::{random_field_tests, random_inversion_tests};
 
@@ -10,3 +12,15 @@ fn test_field() {
     let mut rng = test_rng();
     random_inversion_tests::<Fr, _>(&mut rng, "bn254::Fr".to_string());
 }
+
+#[test]
+fn test_try_deserialize_rejects_non_canonical_fr() {
+    // the modulus itself, little endian
+    let modulus = Fr::MODULUS.trim_start_matches("0x");
+    let mut bytes = (0..32)
+        .map(|i| u8::from_str_radix(&modulus[2 * i..2 * i + 2], 16).unwrap())
+        .collect::<Vec<_>>();
+    bytes.reverse();
+    assert_eq!(Fr::try_deserialize_from(&bytes[..]), None);
+    assert_eq!(Fr::try_deserialize_from(&[0xff; 32][..]), None);
+}
@@ -231,6 +231,11 @@ fn random_serde_tests<F: Field + FieldSerde, R: RngCore>(mut rng: R, type_name:
         let mut cursor = Cursor::new(buffer);
         let b = F::deserialize_from(&mut cursor);
         assert_eq!(a, b);
+
+        let mut buffer = vec![];
+        a.serialize_into(&mut buffer);
+        assert_eq!(F::try_deserialize_from(&buffer[..]), Some(a));
+        assert_eq!(F::try_deserialize_from(&buffer[..buffer.len() - 1]), None);
     }
     end_timer!(start);
 }
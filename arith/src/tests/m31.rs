@@ -2,7 +2,7 @@ use std::io::Cursor;
 
 use ark_std::test_rng;
 
-use crate::{FieldSerde, SimdM31, M31};
+use crate::{Field, FieldSerde, SimdM31, M31, M31_MOD};
 
 use super::{
     field::{random_field_tests, random_inversion_tests},
@@ -30,3 +30,40 @@ fn test_custom_serde_vectorize_m31() {
     let b = SimdM31::deserialize_from(&mut cursor);
     assert_eq!(a, b);
 }
+
+#[test]
+fn test_try_deserialize_rejects_non_canonical_m31() {
+    let modulus = M31_MOD.to_le_bytes();
+    assert_eq!(M31::try_deserialize_from(&modulus[..]), None);
+    // deserialize_from silently reduces the same bytes
+    assert_eq!(M31::deserialize_from(&modulus[..]), M31::from(0));
+
+    let mut buffer = vec![];
+    SimdM31::from(5).serialize_into(&mut buffer);
+    buffer[..4].copy_from_slice(&modulus);
+    assert_eq!(SimdM31::try_deserialize_from(&buffer[..]), None);
+}
+
+#[test]
+fn test_serialize_canonical_m31() {
+    let modulus = M31_MOD.to_le_bytes();
+    let zero = [0u8; 4];
+
+    // the arithmetic may leave the modulus for zero, the bytes are canonical nonetheless
+    let mut buffer = vec![];
+    M31 { v: M31_MOD }.serialize_into(&mut buffer);
+    assert_eq!(buffer, zero);
+
+    let mut bytes = vec![];
+    (0..SimdM31::serialized_size() / 4).for_each(|_| bytes.extend_from_slice(&modulus));
+    let mut buffer = vec![];
+    SimdM31::deserialize_from(&bytes[..]).serialize_into(&mut buffer);
+    assert!(buffer.iter().all(|&x| x == 0));
+
+    for v in [M31_MOD, u32::MAX] {
+        let mut bytes = [0u8; 32];
+        bytes[..4].copy_from_slice(&v.to_le_bytes());
+        assert!(M31::from_uniform_bytes(&bytes).v < M31_MOD);
+        assert!(M31::deserialize_from(&bytes[..4]).v < M31_MOD);
+    }
+}
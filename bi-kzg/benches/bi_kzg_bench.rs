@@ -1,4 +1,4 @@
-use bi_kzg::{BiKZGVerifierParam, BivariatePolynomial, CoeffFormBiKZG, PolynomialCommitmentScheme};
+use bi_kzg::{BivariatePolynomial, CoeffFormBiKZG, PolynomialCommitmentScheme};
 use halo2curves::bn256::{self, Bn256, Fr};
 use halo2curves::ff::Field;
 use halo2curves::msm::best_multiexp;
@@ -32,18 +32,18 @@ fn bench_commit(c: &mut Criterion) {
     for &degree_0 in degree_set.iter() {
         for &degree_1 in degree_set.iter() {
             let srs = CoeffFormBiKZG::<Bn256>::gen_srs_for_testing(&mut rng, degree_0, degree_1);
-            let vk = BiKZGVerifierParam::<Bn256>::from(&srs);
+            let (pp, vk) = CoeffFormBiKZG::<Bn256>::trim(&srs, degree_0, degree_1);
             let poly = BivariatePolynomial::<Fr>::random(&mut rng, degree_0, degree_1);
             c.bench_function(
                 &format!("bi-kzg commit with degrees {} {}", degree_0, degree_1),
                 |b| {
                     b.iter(|| {
-                        let _ = black_box(CoeffFormBiKZG::<Bn256>::commit(&srs, &poly));
+                        let _ = black_box(CoeffFormBiKZG::<Bn256>::commit(&pp, &poly));
                     });
                 },
             );
 
-            let com = CoeffFormBiKZG::<Bn256>::commit(&srs, &poly);
+            let com = CoeffFormBiKZG::<Bn256>::commit(&pp, &poly);
 
             let points = (0..10)
                 .map(|_| (Fr::random(&mut rng), Fr::random(&mut rng)))
@@ -59,14 +59,14 @@ fn bench_commit(c: &mut Criterion) {
                     b.iter(|| {
                         let _ = points
                             .iter()
-                            .map(|p| CoeffFormBiKZG::<Bn256>::open(&srs, &poly, p))
+                            .map(|p| CoeffFormBiKZG::<Bn256>::open(&pp, &poly, p))
                             .collect::<Vec<_>>();
                     });
                 },
             );
             let proofs = points
                 .iter()
-                .map(|p| CoeffFormBiKZG::<Bn256>::open(&srs, &poly, p))
+                .map(|p| CoeffFormBiKZG::<Bn256>::open(&pp, &poly, p))
                 .collect::<Vec<_>>();
 
             c.bench_function(
@@ -0,0 +1,154 @@
+//! Helpers shared by the multi-opening of both forms of Bi-KZG.
+//!
+//! The polynomials opened at the same point are combined with powers of a challenge gamma,
+//! and a single proof is computed for the combination. The proofs for the distinct points
+//! are then checked with one pairing equation, combined with powers of a challenge delta.
+
+use halo2curves::ff::{Field, PrimeField};
+use halo2curves::group::{Curve, Group, GroupEncoding};
+use halo2curves::msm::best_multiexp;
+use halo2curves::pairing::{MillerLoopResult, MultiMillerLoop};
+use halo2curves::CurveAffine;
+
+use crate::util::hash_to_field;
+use crate::{BiKZGBatchProof, BiKZGCommitment, BiKZGVerifierParam};
+
+/// The challenge gamma used to combine the polynomials opened at the same point,
+/// bound to the commitments, the points and the claimed evaluations.
+pub(crate) fn combination_challenge<E: MultiMillerLoop>(
+    commitments: &[BiKZGCommitment<E>],
+    points: &[(E::Fr, E::Fr)],
+    evals: &[E::Fr],
+) -> E::Fr {
+    let mut data = vec![];
+    commitments
+        .iter()
+        .for_each(|c| data.extend_from_slice(c.com.to_bytes().as_ref()));
+    points.iter().for_each(|(a, b)| {
+        data.extend_from_slice(a.to_repr().as_ref());
+        data.extend_from_slice(b.to_repr().as_ref());
+    });
+    evals
+        .iter()
+        .for_each(|e| data.extend_from_slice(e.to_repr().as_ref()));
+    hash_to_field(&data)
+}
+
+/// The distinct points in order of first appearance, and for each input point the
+/// index of its distinct point.
+pub(crate) fn distinct_points<F: PartialEq + Copy>(points: &[(F, F)]) -> (Vec<(F, F)>, Vec<usize>) {
+    let mut distinct: Vec<(F, F)> = vec![];
+    let indices = points
+        .iter()
+        .map(|p| match distinct.iter().position(|q| q == p) {
+            Some(idx) => idx,
+            None => {
+                distinct.push(*p);
+                distinct.len() - 1
+            }
+        })
+        .collect();
+    (distinct, indices)
+}
+
+/// For each distinct point, the combination with powers of gamma of the coefficients of
+/// the polynomials opened at that point. Both forms are linear in their coefficients.
+pub(crate) fn combine_coefficients<F: Field>(
+    coefficients: &[&[F]],
+    indices: &[usize],
+    num_distinct: usize,
+    gamma: &F,
+) -> Vec<Vec<F>> {
+    let len = coefficients[0].len();
+    let mut combined = vec![vec![F::ZERO; len]; num_distinct];
+    let mut gamma_power = F::ONE;
+    for (coeffs, idx) in coefficients.iter().zip(indices.iter()) {
+        assert_eq!(coeffs.len(), len);
+        combined[*idx]
+            .iter_mut()
+            .zip(coeffs.iter())
+            .for_each(|(c, f)| *c += gamma_power * f);
+        gamma_power *= gamma;
+    }
+    combined
+}
+
+/// Check all the openings with a single pairing equation:
+/// e(sum_j delta^j (C_j - u_j g + a_j pi0_j + b_j pi1_j), h)
+///     = e(sum_j delta^j pi0_j, tau_0 h) * e(sum_j delta^j pi1_j, tau_1 h)
+/// where C_j and u_j are the combinations with powers of gamma of the commitments and
+/// evaluations at the j-th distinct point (a_j, b_j).
+pub(crate) fn batch_verify_internal<E: MultiMillerLoop>(
+    verifier_param: &BiKZGVerifierParam<E>,
+    commitments: &[BiKZGCommitment<E>],
+    points: &[(E::Fr, E::Fr)],
+    batch_proof: &BiKZGBatchProof<E>,
+) -> bool
+where
+    E::G1Affine: CurveAffine<ScalarExt = E::Fr, CurveExt = E::G1>,
+{
+    let (distinct, indices) = distinct_points(points);
+    if commitments.len() != points.len()
+        || batch_proof.evals.len() != points.len()
+        || batch_proof.proofs.len() != distinct.len()
+    {
+        return false;
+    }
+
+    let gamma = combination_challenge(commitments, points, &batch_proof.evals);
+    let delta = {
+        let mut data = gamma.to_repr().as_ref().to_vec();
+        batch_proof.proofs.iter().for_each(|p| {
+            data.extend_from_slice(p.pi0.to_bytes().as_ref());
+            data.extend_from_slice(p.pi1.to_bytes().as_ref());
+        });
+        hash_to_field::<E::Fr>(&data)
+    };
+    let mut delta_powers = vec![E::Fr::ONE; distinct.len()];
+    for j in 1..distinct.len() {
+        delta_powers[j] = delta_powers[j - 1] * delta;
+    }
+
+    // scalars and bases of sum_j delta^j (C_j - u_j g + a_j pi0_j + b_j pi1_j)
+    let mut scalars = Vec::with_capacity(commitments.len() + 2 * distinct.len() + 1);
+    let mut bases = Vec::with_capacity(commitments.len() + 2 * distinct.len() + 1);
+    let mut combined_eval = E::Fr::ZERO;
+    let mut gamma_power = E::Fr::ONE;
+    for ((com, eval), idx) in commitments
+        .iter()
+        .zip(batch_proof.evals.iter())
+        .zip(indices.iter())
+    {
+        let coef = delta_powers[*idx] * gamma_power;
+        scalars.push(coef);
+        bases.push(com.com);
+        combined_eval += coef * eval;
+        gamma_power *= gamma;
+    }
+    scalars.push(-combined_eval);
+    bases.push(verifier_param.g);
+    for ((proof, (a, b)), delta_power) in batch_proof
+        .proofs
+        .iter()
+        .zip(distinct.iter())
+        .zip(delta_powers.iter())
+    {
+        scalars.push(*delta_power * a);
+        bases.push(proof.pi0);
+        scalars.push(*delta_power * b);
+        bases.push(proof.pi1);
+    }
+    let lhs = (-best_multiexp(&scalars, &bases)).to_affine();
+
+    let pi0s = batch_proof.proofs.iter().map(|p| p.pi0).collect::<Vec<_>>();
+    let pi1s = batch_proof.proofs.iter().map(|p| p.pi1).collect::<Vec<_>>();
+    let pi0 = best_multiexp(&delta_powers, &pi0s).to_affine();
+    let pi1 = best_multiexp(&delta_powers, &pi1s).to_affine();
+
+    let res = E::multi_miller_loop(&[
+        (&pi0, &verifier_param.tau_0_h.into()),
+        (&pi1, &verifier_param.tau_1_h.into()),
+        (&lhs, &verifier_param.h.into()),
+    ]);
+    res.final_exponentiation().is_identity().into()
+}
@@ -4,6 +4,7 @@ use ark_std::log2;
 use halo2curves::{
     ff::{Field, PrimeField},
     fft::best_fft,
+    group::Group,
 };
 
 #[inline]
@@ -211,3 +212,48 @@ pub(crate) fn bi_fft_in_place<F: PrimeField>(coeffs: &mut [F], degree_n: usize,
     // outer layer of FFT over variable y
     best_fft_vec_in_place(coeffs, omega_1, log2(degree_n), log2(degree_m));
 }
+
+/// Inverse FFT over the n-th roots of unity on a vector of group elements, i.e., maps
+/// (g^{a_k})_k to (g^{1/n sum_k a_k omega^{-ik}})_i.
+/// Used to derive the lagrange bases from the powers of tau in the exponent.
+pub(crate) fn group_ifft_in_place<G: Group>(a: &mut [G])
+where
+    G::Scalar: PrimeField,
+{
+    let n = a.len();
+    assert!(n.is_power_of_two());
+    if n == 1 {
+        return;
+    }
+    let log_n = n.trailing_zeros() as usize;
+    let omega_inv = G::Scalar::ROOT_OF_UNITY
+        .pow_vartime([(1 << G::Scalar::S) / n as u64])
+        .invert()
+        .unwrap();
+
+    for k in 0..n {
+        let rk = bitreverse(k, log_n);
+        if k < rk {
+            a.swap(rk, k);
+        }
+    }
+
+    let mut m = 1;
+    while m < n {
+        let w_m = omega_inv.pow_vartime([(n / (2 * m)) as u64]);
+        for chunk in a.chunks_exact_mut(2 * m) {
+            let (left, right) = chunk.split_at_mut(m);
+            let mut w = G::Scalar::ONE;
+            for (x, y) in left.iter_mut().zip(right.iter_mut()) {
+                let t = *y * w;
+                *y = *x - t;
+                *x += t;
+                w *= w_m;
+            }
+        }
+        m *= 2;
+    }
+
+    let n_inv = G::Scalar::from(n as u64).invert().unwrap();
+    a.iter_mut().for_each(|x| *x *= n_inv);
+}
@@ -0,0 +1,365 @@
+//! An updatable powers-of-tau ceremony for the Bi-KZG structured reference string.
+//!
+//! The ceremony starts from tau_0 = tau_1 = 1. Each participant samples secrets s_0 and s_1,
+//! and rescales the SRS to tau_0 s_0 and tau_1 s_1: the powers of g by s_0^i s_1^j, tau_0 h
+//! and tau_1 h by s_0 and s_1, and the lagrange bases are recomputed from the new powers.
+//! The participant publishes g^{s_0}, g^{s_1} with Schnorr proofs of knowledge, together with
+//! the new g^{tau_0}, g^{tau_1}, tau_0 h and tau_1 h. The SRS is safe as long as one of the
+//! participants discarded their secrets.
+//!
+//! The verifier checks each update against the previous one with pairings, and the
+//! structure of the final SRS with random linear combinations of the powers of g.
+
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Error, ErrorKind, Read, Result, Write},
+    path::Path,
+};
+
+use halo2curves::ff::{Field, PrimeField};
+use halo2curves::group::prime::PrimeCurveAffine;
+use halo2curves::group::{Curve, Group, GroupEncoding};
+use halo2curves::msm::best_multiexp;
+use halo2curves::pairing::{MillerLoopResult, MultiMillerLoop};
+use halo2curves::CurveAffine;
+use rand::RngCore;
+
+use crate::srs::{lagrange_bases_from_powers, read_point, read_usize, write_point, write_usize};
+use crate::util::{hash_to_field, parallelize, powers_of_field_elements, tensor_product_parallel};
+use crate::BiKZGSRS;
+
+/// The update of the SRS by one participant of the ceremony.
+#[derive(Clone, Debug)]
+pub struct BiKZGContribution<E: MultiMillerLoop> {
+    /// g^{s_0} for the secret s_0 of the participant
+    pub s_0_g: E::G1Affine,
+    /// g^{s_1} for the secret s_1 of the participant
+    pub s_1_g: E::G1Affine,
+    /// Schnorr proof of knowledge of s_0: the commitment g^r and the response r + c s_0
+    pub s_0_pok: (E::G1Affine, E::Fr),
+    /// Schnorr proof of knowledge of s_1
+    pub s_1_pok: (E::G1Affine, E::Fr),
+    /// g^{tau_0} after the update
+    pub tau_0_g: E::G1Affine,
+    /// g^{tau_1} after the update
+    pub tau_1_g: E::G1Affine,
+    /// tau_0 h after the update
+    pub tau_0_h: E::G2Affine,
+    /// tau_1 h after the update
+    pub tau_1_h: E::G2Affine,
+}
+
+/// The transcript of the ceremony: the current SRS and all the contributions so far.
+#[derive(Clone, Debug)]
+pub struct BiKZGCeremony<E: MultiMillerLoop> {
+    pub srs: BiKZGSRS<E>,
+    pub contributions: Vec<BiKZGContribution<E>>,
+}
+
+/// The challenge of the Schnorr proof of knowledge of the secret behind s_g,
+/// bound to the previous tau element it updates.
+fn pok_challenge<E: MultiMillerLoop>(
+    s_g: &E::G1Affine,
+    r_g: &E::G1Affine,
+    prev_tau_h: &E::G2Affine,
+) -> E::Fr {
+    let mut data = vec![];
+    data.extend_from_slice(E::G1Affine::generator().to_bytes().as_ref());
+    data.extend_from_slice(s_g.to_bytes().as_ref());
+    data.extend_from_slice(r_g.to_bytes().as_ref());
+    data.extend_from_slice(prev_tau_h.to_bytes().as_ref());
+    hash_to_field(&data)
+}
+
+/// e(a, b) == e(c, d)
+fn pairing_eq<E: MultiMillerLoop>(
+    a: &E::G1Affine,
+    b: &E::G2Affine,
+    c: &E::G1Affine,
+    d: &E::G2Affine,
+) -> bool {
+    let neg_c = (-c.to_curve()).to_affine();
+    let res = E::multi_miller_loop(&[(a, &(*b).into()), (&neg_c, &(*d).into())]);
+    res.final_exponentiation().is_identity().into()
+}
+
+#[inline]
+fn write_scalar<W: Write, F: PrimeField>(x: &F, writer: &mut W) -> Result<()> {
+    writer.write_all(x.to_repr().as_ref())
+}
+
+#[inline]
+fn read_scalar<R: Read, F: PrimeField>(reader: &mut R) -> Result<F> {
+    let mut repr = F::Repr::default();
+    reader.read_exact(repr.as_mut())?;
+    Option::from(F::from_repr(repr))
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid scalar encoding"))
+}
+
+impl<E: MultiMillerLoop> BiKZGContribution<E>
+where
+    E::G1Affine: CurveAffine<ScalarExt = E::Fr, CurveExt = E::G1>,
+{
+    fn prove_knowledge(
+        mut rng: impl RngCore,
+        secret: &E::Fr,
+        s_g: &E::G1Affine,
+        prev_tau_h: &E::G2Affine,
+    ) -> (E::G1Affine, E::Fr) {
+        let r = E::Fr::random(&mut rng);
+        let r_g = (E::G1Affine::generator() * r).to_affine();
+        let c = pok_challenge::<E>(s_g, &r_g, prev_tau_h);
+        (r_g, r + c * secret)
+    }
+
+    fn verify_knowledge(
+        s_g: &E::G1Affine,
+        pok: &(E::G1Affine, E::Fr),
+        prev_tau_h: &E::G2Affine,
+    ) -> bool {
+        let c = pok_challenge::<E>(s_g, &pok.0, prev_tau_h);
+        E::G1Affine::generator() * pok.1 == pok.0.to_curve() + *s_g * c
+    }
+
+    /// Check the update from the previous tau elements, g^{tau} and tau h of the previous
+    /// contribution, or the generators for the first one.
+    fn verify_update(&self, prev_tau_0_h: &E::G2Affine, prev_tau_1_h: &E::G2Affine) -> bool {
+        let g = E::G1Affine::generator();
+        let h = E::G2Affine::generator();
+        for (s_g, pok, tau_g, tau_h, prev_tau_h) in [
+            (
+                &self.s_0_g,
+                &self.s_0_pok,
+                &self.tau_0_g,
+                &self.tau_0_h,
+                prev_tau_0_h,
+            ),
+            (
+                &self.s_1_g,
+                &self.s_1_pok,
+                &self.tau_1_g,
+                &self.tau_1_h,
+                prev_tau_1_h,
+            ),
+        ] {
+            // the secret is known to the participant and is not zero
+            if bool::from(s_g.is_identity()) || !Self::verify_knowledge(s_g, pok, prev_tau_h) {
+                return false;
+            }
+            // g^{tau s} is the previous tau rescaled by s, and tau h matches it
+            if !pairing_eq::<E>(tau_g, &h, s_g, prev_tau_h)
+                || !pairing_eq::<E>(tau_g, &h, &g, tau_h)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn serialize_into<W: Write>(&self, writer: &mut W) -> Result<()> {
+        write_point(&self.s_0_g, writer)?;
+        write_point(&self.s_1_g, writer)?;
+        write_point(&self.s_0_pok.0, writer)?;
+        write_scalar(&self.s_0_pok.1, writer)?;
+        write_point(&self.s_1_pok.0, writer)?;
+        write_scalar(&self.s_1_pok.1, writer)?;
+        write_point(&self.tau_0_g, writer)?;
+        write_point(&self.tau_1_g, writer)?;
+        write_point(&self.tau_0_h, writer)?;
+        write_point(&self.tau_1_h, writer)
+    }
+
+    fn deserialize_from<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            s_0_g: read_point(reader)?,
+            s_1_g: read_point(reader)?,
+            s_0_pok: (read_point(reader)?, read_scalar(reader)?),
+            s_1_pok: (read_point(reader)?, read_scalar(reader)?),
+            tau_0_g: read_point(reader)?,
+            tau_1_g: read_point(reader)?,
+            tau_0_h: read_point(reader)?,
+            tau_1_h: read_point(reader)?,
+        })
+    }
+}
+
+impl<E: MultiMillerLoop> BiKZGCeremony<E>
+where
+    E::G1Affine: CurveAffine<ScalarExt = E::Fr, CurveExt = E::G1>,
+{
+    /// Start a ceremony for polynomials of degrees (supported_n, supported_m),
+    /// from the SRS with tau_0 = tau_1 = 1.
+    pub fn new(supported_n: usize, supported_m: usize) -> Self {
+        assert!(supported_n.is_power_of_two());
+        assert!(supported_m.is_power_of_two());
+
+        let powers_of_g = vec![E::G1Affine::generator(); supported_n * supported_m];
+        let srs = BiKZGSRS {
+            supported_n,
+            supported_m,
+            powers_of_g_lagrange_over_both_roots: lagrange_bases_from_powers::<E>(
+                &powers_of_g,
+                supported_n,
+                supported_m,
+            ),
+            powers_of_g,
+            h: E::G2Affine::generator(),
+            tau_0_h: E::G2Affine::generator(),
+            tau_1_h: E::G2Affine::generator(),
+        };
+        Self {
+            srs,
+            contributions: vec![],
+        }
+    }
+
+    /// Rerandomize the SRS with fresh secrets, which are dropped once the update is done.
+    pub fn contribute(&mut self, mut rng: impl RngCore) -> &BiKZGContribution<E> {
+        let supported_n = self.srs.supported_n;
+        let supported_m = self.srs.supported_m;
+        let s_0 = E::Fr::random(&mut rng);
+        let s_1 = E::Fr::random(&mut rng);
+        let g = E::G1Affine::generator();
+
+        // g^{tau_0^i tau_1^j} -> g^{tau_0^i tau_1^j s_0^i s_1^j}
+        let scalars = tensor_product_parallel(
+            &powers_of_field_elements(&s_0, supported_n),
+            &powers_of_field_elements(&s_1, supported_m),
+        );
+        let mut proj_bases = vec![E::G1::identity(); supported_n * supported_m];
+        let powers_of_g = &self.srs.powers_of_g;
+        parallelize(&mut proj_bases, |g, start| {
+            for (idx, g) in g.iter_mut().enumerate() {
+                let offset = start + idx;
+                *g = powers_of_g[offset] * scalars[offset];
+            }
+        });
+        let mut powers_of_g = vec![E::G1Affine::identity(); supported_n * supported_m];
+        parallelize(&mut powers_of_g, |g, starts| {
+            E::G1::batch_normalize(&proj_bases[starts..(starts + g.len())], g);
+        });
+
+        let (prev_tau_0_h, prev_tau_1_h) = (self.srs.tau_0_h, self.srs.tau_1_h);
+        let (prev_tau_0_g, prev_tau_1_g) = self
+            .contributions
+            .last()
+            .map_or((g, g), |c| (c.tau_0_g, c.tau_1_g));
+        let s_0_g = (g * s_0).to_affine();
+        let s_1_g = (g * s_1).to_affine();
+        let contribution = BiKZGContribution {
+            s_0_g,
+            s_1_g,
+            s_0_pok: BiKZGContribution::<E>::prove_knowledge(&mut rng, &s_0, &s_0_g, &prev_tau_0_h),
+            s_1_pok: BiKZGContribution::<E>::prove_knowledge(&mut rng, &s_1, &s_1_g, &prev_tau_1_h),
+            tau_0_g: (prev_tau_0_g * s_0).to_affine(),
+            tau_1_g: (prev_tau_1_g * s_1).to_affine(),
+            tau_0_h: (prev_tau_0_h * s_0).to_affine(),
+            tau_1_h: (prev_tau_1_h * s_1).to_affine(),
+        };
+
+        self.srs.powers_of_g_lagrange_over_both_roots =
+            lagrange_bases_from_powers::<E>(&powers_of_g, supported_n, supported_m);
+        self.srs.powers_of_g = powers_of_g;
+        self.srs.tau_0_h = contribution.tau_0_h;
+        self.srs.tau_1_h = contribution.tau_1_h;
+        self.contributions.push(contribution);
+        self.contributions.last().unwrap()
+    }
+
+    /// Verify the whole transcript: each contribution updates the previous one, and the
+    /// final SRS is made of the powers of the final tau_0 and tau_1.
+    /// The randomness is only used to combine the checks on the powers of g.
+    pub fn verify(&self, mut rng: impl RngCore) -> bool {
+        let srs = &self.srs;
+        let (n, m) = (srs.supported_n, srs.supported_m);
+        let g = E::G1Affine::generator();
+        let h = E::G2Affine::generator();
+        if self.contributions.is_empty()
+            || !n.is_power_of_two()
+            || !m.is_power_of_two()
+            || srs.powers_of_g.len() != n * m
+            || srs.powers_of_g_lagrange_over_both_roots.len() != n * m
+            || srs.powers_of_g[0] != g
+            || srs.h != h
+        {
+            return false;
+        }
+
+        // the chain of updates
+        let (mut prev_tau_0_h, mut prev_tau_1_h) = (h, h);
+        for contribution in self.contributions.iter() {
+            if !contribution.verify_update(&prev_tau_0_h, &prev_tau_1_h) {
+                return false;
+            }
+            prev_tau_0_h = contribution.tau_0_h;
+            prev_tau_1_h = contribution.tau_1_h;
+        }
+        let last = self.contributions.last().unwrap();
+        if srs.tau_0_h != last.tau_0_h
+            || srs.tau_1_h != last.tau_1_h
+            || (n > 1 && srs.powers_of_g[1] != last.tau_0_g)
+            || (m > 1 && srs.powers_of_g[n] != last.tau_1_g)
+        {
+            return false;
+        }
+
+        // g^{tau_0^{i+1} tau_1^j} = (g^{tau_0^i tau_1^j})^{tau_0}, and
+        // g^{tau_0^i tau_1^{j+1}} = (g^{tau_0^i tau_1^j})^{tau_1},
+        // each checked for a random combination of the powers
+        let row_pairs = (0..n * m)
+            .filter(|k| k % n != n - 1)
+            .map(|k| (k, k + 1))
+            .collect::<Vec<_>>();
+        let column_pairs = (0..n * (m - 1)).map(|k| (k, k + n)).collect::<Vec<_>>();
+        for (pairs, tau_h) in [(row_pairs, &srs.tau_0_h), (column_pairs, &srs.tau_1_h)] {
+            if pairs.is_empty() {
+                continue;
+            }
+            let coeffs = (0..pairs.len())
+                .map(|_| E::Fr::random(&mut rng))
+                .collect::<Vec<_>>();
+            let (lower, upper): (Vec<_>, Vec<_>) = pairs
+                .iter()
+                .map(|(k, l)| (srs.powers_of_g[*k], srs.powers_of_g[*l]))
+                .unzip();
+            let lower = best_multiexp(&coeffs, &lower).to_affine();
+            let upper = best_multiexp(&coeffs, &upper).to_affine();
+            if !pairing_eq::<E>(&lower, tau_h, &upper, &h) {
+                return false;
+            }
+        }
+
+        srs.powers_of_g_lagrange_over_both_roots
+            == lagrange_bases_from_powers::<E>(&srs.powers_of_g, n, m)
+    }
+
+    /// The ceremony is stored as the SRS followed by the number of contributions
+    /// and the contributions.
+    pub fn serialize_into<W: Write>(&self, mut writer: W) -> Result<()> {
+        self.srs.serialize_into(&mut writer)?;
+        write_usize(self.contributions.len(), &mut writer)?;
+        for contribution in self.contributions.iter() {
+            contribution.serialize_into(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    pub fn deserialize_from<R: Read>(mut reader: R) -> Result<Self> {
+        let srs = BiKZGSRS::deserialize_from(&mut reader)?;
+        let num_contributions = read_usize(&mut reader)?;
+        let contributions = (0..num_contributions)
+            .map(|_| BiKZGContribution::deserialize_from(&mut reader))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { srs, contributions })
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.serialize_into(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::deserialize_from(BufReader::new(File::open(path)?))
+    }
+}
@@ -12,14 +12,18 @@ use halo2curves::CurveAffine;
 use itertools::Itertools;
 use rand::RngCore;
 
+use crate::batch::{
+    batch_verify_internal, combination_challenge, combine_coefficients, distinct_points,
+};
+use crate::distributed::{aggregate_partial_proofs, gather_row_evals, party_rows, sum_commitments};
 use crate::poly::{lagrange_coefficients, univariate_quotient};
-use crate::structs::BivariateLagrangePolynomial;
 use crate::structs::BivariatePolynomial;
 use crate::util::parallelize;
 use crate::{
-    pcs::PolynomialCommitmentScheme,
+    pcs::{DistributedPolynomialCommitmentScheme, PolynomialCommitmentScheme},
     util::{powers_of_field_elements, tensor_product_parallel},
-    BiKZGCommitment, BiKZGProof, BiKZGSRS, BiKZGVerifierParam,
+    BiKZGBatchProof, BiKZGCommitment, BiKZGPartialProof, BiKZGProof, BiKZGProverParam, BiKZGSRS,
+    BiKZGVerifierParam,
 };
 
 /// Commit to the bi-variate polynomial in its coefficient form.
@@ -33,14 +37,14 @@ where
     E::G1Affine: CurveAffine<ScalarExt = E::Fr, CurveExt = E::G1>,
 {
     type SRS = BiKZGSRS<E>;
-    type ProverParam = BiKZGSRS<E>;
+    type ProverParam = BiKZGProverParam<E>;
     type VerifierParam = BiKZGVerifierParam<E>;
     type Polynomial = BivariatePolynomial<E::Fr>;
     type Commitment = BiKZGCommitment<E>;
     type Proof = BiKZGProof<E>;
     type Evaluation = E::Fr;
     type Point = (E::Fr, E::Fr);
-    type BatchProof = Vec<Self::Proof>;
+    type BatchProof = BiKZGBatchProof<E>;
 
     fn gen_srs_for_testing(
         mut rng: impl RngCore,
@@ -124,6 +128,8 @@ where
         };
 
         BiKZGSRS {
+            supported_n,
+            supported_m,
             powers_of_g: coeff_bases,
             powers_of_g_lagrange_over_both_roots: lagrange_bases,
             h: E::G2Affine::generator(),
@@ -132,13 +138,15 @@ where
         }
     }
 
-    // fn trim(
-    //     srs: impl Borrow<Self::SRS>,
-    //     supported_degree: Option<usize>,
-    //     supported_num_vars: Option<usize>,
-    // ) -> (Self::ProverParam, Self::VerifierParam) {
-    //     unimplemented!()
-    // }
+    fn trim(
+        srs: impl Borrow<Self::SRS>,
+        supported_n: usize,
+        supported_m: usize,
+    ) -> (Self::ProverParam, Self::VerifierParam) {
+        let srs = srs.borrow();
+        let prover_param = srs.coeff_form_prover_param(supported_n, supported_m);
+        (prover_param, BiKZGVerifierParam::from(srs))
+    }
 
     fn commit(
         prover_param: impl Borrow<Self::ProverParam>,
@@ -150,10 +158,7 @@ where
         ));
         let timer2 =
             start_timer!(|| format!("Computing the msm for size {}", poly.coefficients.len()));
-        let com = best_multiexp(
-            &poly.coefficients,
-            prover_param.borrow().powers_of_g.as_slice(),
-        );
+        let com = best_multiexp(&poly.coefficients, prover_param.borrow().bases.as_slice());
         end_timer!(timer2);
         end_timer!(timer);
 
@@ -187,7 +192,7 @@ where
             let timer2 = start_timer!(|| format!("Computing the msm for size {}", q_0_x_b.len()));
             let pi_0 = best_multiexp(
                 &q_0_x_b,
-                prover_param.borrow().powers_of_g[..polynomial.degree_0].as_ref(),
+                prover_param.borrow().powers_of_tau_0[..polynomial.degree_0].as_ref(),
             )
             .to_affine();
             end_timer!(timer2);
@@ -204,42 +209,19 @@ where
                 .take(polynomial.degree_0)
                 .zip_eq(f_x_b.iter())
                 .for_each(|(c, f)| *c -= f);
-            let t_lag = t.interpolate();
 
-            // divisor = (y - b)
-            let divisor_lag = BivariateLagrangePolynomial::from_y_monomial(
-                &point.1,
-                polynomial.degree_0,
-                polynomial.degree_1,
-            );
-            let y_minus_a_inv_lag = divisor_lag
-                .coefficients
-                .iter()
-                .map(|o| {
-                    if o.is_zero_vartime() {
-                        panic!("not invertible")
-                    } else {
-                        o.invert().unwrap()
-                    }
-                })
-                .collect::<Vec<_>>();
-
-            // q_1(x, y) = t(x, y) * (y - b)^{-1}
-            let q_1_x_y = t_lag
-                .iter()
-                .zip_eq(y_minus_a_inv_lag.iter())
-                .map(|(c, y)| (*c) * *y)
-                .collect::<Vec<_>>();
+            // q_1(x, y) = t(x, y) / (y - b), by synthetic division in y from the highest
+            // power, for all the powers of x at once
+            let n = polynomial.degree_0;
+            let mut q_1_x_y = vec![E::Fr::ZERO; t.coefficients.len()];
+            for j in (1..polynomial.degree_1).rev() {
+                for i in 0..n {
+                    q_1_x_y[(j - 1) * n + i] = t.coefficients[j * n + i] + b * q_1_x_y[j * n + i];
+                }
+            }
 
             let timer2 = start_timer!(|| format!("Computing the msm for size {}", q_1_x_y.len()));
-            let res = best_multiexp(
-                &q_1_x_y,
-                prover_param
-                    .borrow()
-                    .powers_of_g_lagrange_over_both_roots
-                    .as_ref(),
-            )
-            .to_affine();
+            let res = best_multiexp(&q_1_x_y, prover_param.borrow().bases.as_ref()).to_affine();
             end_timer!(timer2);
             res
         };
@@ -279,5 +261,135 @@ where
         res
     }
 
-    // TODO: implement multi-opening and batch verification
+    /// The polynomials opened at the same point are combined with powers of a challenge,
+    /// and a single proof is computed for each distinct point.
+    fn multi_open(
+        prover_param: impl Borrow<Self::ProverParam>,
+        polynomials: &[Self::Polynomial],
+        points: &[Self::Point],
+        evals: &[Self::Evaluation],
+    ) -> Self::BatchProof {
+        assert_eq!(polynomials.len(), points.len());
+        assert_eq!(polynomials.len(), evals.len());
+        let timer = start_timer!(|| format!("Multi-opening {} polynomials", polynomials.len()));
+
+        let prover_param = prover_param.borrow();
+        let commitments = polynomials
+            .iter()
+            .map(|p| Self::commit(prover_param, p))
+            .collect::<Vec<_>>();
+        let gamma = combination_challenge(&commitments, points, evals);
+
+        let (distinct, indices) = distinct_points(points);
+        let coefficients = polynomials
+            .iter()
+            .map(|p| p.coefficients.as_slice())
+            .collect::<Vec<_>>();
+        let proofs = combine_coefficients(&coefficients, &indices, distinct.len(), &gamma)
+            .into_iter()
+            .zip(distinct.iter())
+            .map(|(coeffs, point)| {
+                let poly = BivariatePolynomial::new(
+                    coeffs,
+                    polynomials[0].degree_0,
+                    polynomials[0].degree_1,
+                );
+                Self::open(prover_param, &poly, point).0
+            })
+            .collect();
+
+        end_timer!(timer);
+        BiKZGBatchProof {
+            evals: evals.to_vec(),
+            proofs,
+        }
+    }
+
+    fn batch_verify(
+        verifier_param: &Self::VerifierParam,
+        commitments: &[Self::Commitment],
+        points: &[Self::Point],
+        batch_proof: &Self::BatchProof,
+    ) -> bool {
+        let timer = start_timer!(|| format!("Batch verifying {} openings", points.len()));
+        let res = batch_verify_internal(verifier_param, commitments, points, batch_proof);
+        end_timer!(timer);
+        res
+    }
+}
+
+impl<E: MultiMillerLoop> DistributedPolynomialCommitmentScheme for CoeffFormBiKZG<E>
+where
+    E::G1Affine: CurveAffine<ScalarExt = E::Fr, CurveExt = E::G1>,
+{
+    type PartialProof = BiKZGPartialProof<E>;
+
+    /// The rows are the coefficients of f in x for the powers of y held by the party.
+    fn partial_commit(
+        prover_param: impl Borrow<Self::ProverParam>,
+        party_id: usize,
+        rows: &[Self::Evaluation],
+    ) -> Self::Commitment {
+        let prover_param = prover_param.borrow();
+        let (start, num_rows) = party_rows(prover_param, party_id, rows);
+        let n = prover_param.supported_n;
+        let timer = start_timer!(|| format!("Party {} committing to {} rows", party_id, num_rows));
+        let com = best_multiexp(rows, &prover_param.bases[start * n..(start + num_rows) * n]);
+        end_timer!(timer);
+
+        Self::Commitment { com: com.into() }
+    }
+
+    fn aggregate_commitments(partial_commitments: &[Self::Commitment]) -> Self::Commitment {
+        sum_commitments(partial_commitments)
+    }
+
+    fn partial_open(
+        prover_param: impl Borrow<Self::ProverParam>,
+        party_id: usize,
+        rows: &[Self::Evaluation],
+        point: &Self::Point,
+    ) -> Self::PartialProof {
+        let prover_param = prover_param.borrow();
+        let (start, num_rows) = party_rows(prover_param, party_id, rows);
+        let n = prover_param.supported_n;
+        let timer = start_timer!(|| format!("Party {} opening {} rows", party_id, num_rows));
+
+        // for each row, f_j(a) and q_0_j(x) = (f_j(x) - f_j(a)) / (x - a)
+        let mut evals = Vec::with_capacity(num_rows);
+        let mut quotients = Vec::with_capacity(rows.len());
+        for row in rows.chunks_exact(n) {
+            let u = row
+                .iter()
+                .rev()
+                .fold(E::Fr::ZERO, |acc, c| acc * point.0 + c);
+            let mut t = row.to_vec();
+            t[0] -= u;
+            quotients.extend(univariate_quotient(&t, &point.0));
+            evals.push(u);
+        }
+        let pi0 = best_multiexp(
+            &quotients,
+            &prover_param.bases[start * n..(start + num_rows) * n],
+        )
+        .to_affine();
+        end_timer!(timer);
+
+        BiKZGPartialProof {
+            party_id,
+            rows: start..start + num_rows,
+            pi0,
+            evals,
+        }
+    }
+
+    /// The evaluations of the rows are the coefficients of f(a, y).
+    fn aggregate_proofs(
+        prover_param: impl Borrow<Self::ProverParam>,
+        partial_proofs: &[Self::PartialProof],
+        point: &Self::Point,
+    ) -> (Self::Proof, Self::Evaluation) {
+        let f_a_y = gather_row_evals(prover_param.borrow(), partial_proofs);
+        aggregate_partial_proofs(prover_param.borrow(), partial_proofs, &f_a_y, &point.1)
+    }
 }
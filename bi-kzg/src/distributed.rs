@@ -0,0 +1,105 @@
+//! Helpers shared by the distributed commitment and opening of both forms of Bi-KZG.
+//!
+//! The polynomial is split by rows, f(X, Y) = sum_j f_j(X) B_j(Y) where B_j is Y^j for the
+//! coefficient form and L_j(Y) for the lagrange form. The commitment is the sum of the
+//! commitments to the rows. For the opening at (a, b), the proof uses the decomposition
+//! f(X, Y) - f(a, b) = q_0(X, Y) (X - a) + q_1(Y) (Y - b), with
+//! q_0(X, Y) = sum_j B_j(Y) (f_j(X) - f_j(a)) / (X - a), which each party commits to for its
+//! rows, and q_1(Y) = (f(a, Y) - f(a, b)) / (Y - b), which the coordinator commits to from
+//! the evaluations f_j(a) of all the rows. The standard verifier checks the same equation.
+
+use halo2curves::ff::Field;
+use halo2curves::group::{Curve, Group};
+use halo2curves::msm::best_multiexp;
+use halo2curves::pairing::Engine;
+use halo2curves::CurveAffine;
+
+use crate::poly::univariate_quotient;
+use crate::{BiKZGCommitment, BiKZGPartialProof, BiKZGProof, BiKZGProverParam};
+
+/// The commitment to the whole polynomial is the sum of the commitments to the rows.
+pub(crate) fn sum_commitments<E: Engine>(
+    partial_commitments: &[BiKZGCommitment<E>],
+) -> BiKZGCommitment<E> {
+    let com = partial_commitments
+        .iter()
+        .fold(E::G1::identity(), |acc, c| acc + c.com);
+    BiKZGCommitment {
+        com: com.to_affine(),
+    }
+}
+
+/// The rows of the party `party_id` within the polynomial of supported_n x supported_m
+/// coefficients, as the index of the first row and the number of rows.
+pub(crate) fn party_rows<E: Engine>(
+    prover_param: &BiKZGProverParam<E>,
+    party_id: usize,
+    rows: &[E::Fr],
+) -> (usize, usize) {
+    let n = prover_param.supported_n;
+    assert_eq!(rows.len() % n, 0);
+    let num_rows = rows.len() / n;
+    assert!((party_id + 1) * num_rows <= prover_param.supported_m);
+    (party_id * num_rows, num_rows)
+}
+
+/// The evaluations f_j(a) of all the rows, from the partial proofs of the parties in any
+/// order. Panics unless the rows of the parties are contiguous and cover the supported_m rows
+/// of the polynomial.
+pub(crate) fn gather_row_evals<E: Engine>(
+    prover_param: &BiKZGProverParam<E>,
+    partial_proofs: &[BiKZGPartialProof<E>],
+) -> Vec<E::Fr> {
+    let mut partial_proofs = partial_proofs.iter().collect::<Vec<_>>();
+    partial_proofs.sort_by_key(|p| p.rows.start);
+
+    let mut evals = Vec::with_capacity(prover_param.supported_m);
+    for p in partial_proofs {
+        assert_eq!(
+            p.rows.start,
+            evals.len(),
+            "the rows of party {} do not follow the previous ones",
+            p.party_id
+        );
+        assert_eq!(
+            p.rows.start,
+            p.party_id * p.rows.len(),
+            "party {} opened the rows of another party",
+            p.party_id
+        );
+        assert_eq!(p.rows.len(), p.evals.len());
+        evals.extend_from_slice(&p.evals);
+    }
+    assert_eq!(
+        evals.len(),
+        prover_param.supported_m,
+        "the rows of the parties do not cover the polynomial"
+    );
+    evals
+}
+
+/// Aggregate the partial proofs, given the coefficients of f(a, Y) in Y gathered from the
+/// evaluations of the parties: pi0 is the sum of the partial pi0, and pi1 commits to
+/// q_1(Y) = (f(a, Y) - f(a, b)) / (Y - b) with the bases g^{tau_1^j}.
+pub(crate) fn aggregate_partial_proofs<E: Engine>(
+    prover_param: &BiKZGProverParam<E>,
+    partial_proofs: &[BiKZGPartialProof<E>],
+    f_a_y: &[E::Fr],
+    b: &E::Fr,
+) -> (BiKZGProof<E>, E::Fr)
+where
+    E::G1Affine: CurveAffine<ScalarExt = E::Fr, CurveExt = E::G1>,
+{
+    let pi0 = partial_proofs
+        .iter()
+        .fold(E::G1::identity(), |acc, p| acc + p.pi0)
+        .to_affine();
+
+    let u = f_a_y.iter().rev().fold(E::Fr::ZERO, |acc, c| acc * b + c);
+    let mut t = f_a_y.to_vec();
+    t[0] -= u;
+    let q_1 = univariate_quotient(&t, b);
+    let pi1 = best_multiexp(&q_1, &prover_param.powers_of_tau_1[..q_1.len()]).to_affine();
+
+    (BiKZGProof { pi0, pi1 }, u)
+}
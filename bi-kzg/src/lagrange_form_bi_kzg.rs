@@ -1,26 +1,27 @@
-//! We don't need this file for now. We will use the `CoeffFormBiKZG`.
-
 use std::{borrow::Borrow, marker::PhantomData};
 
 use ark_std::{end_timer, start_timer};
 use halo2curves::ff::Field;
-use halo2curves::ff::PrimeField;
-use halo2curves::group::prime::PrimeCurveAffine;
 use halo2curves::group::Curve;
-use halo2curves::group::Group;
 use halo2curves::msm::best_multiexp;
-use halo2curves::pairing::{MillerLoopResult, MultiMillerLoop};
+use halo2curves::pairing::MultiMillerLoop;
 use halo2curves::CurveAffine;
 use itertools::Itertools;
 use rand::RngCore;
 
-use crate::poly::{lagrange_coefficients, univariate_quotient};
+use crate::batch::{
+    batch_verify_internal, combination_challenge, combine_coefficients, distinct_points,
+};
+use crate::distributed::{aggregate_partial_proofs, gather_row_evals, party_rows, sum_commitments};
+use crate::poly::{
+    lagrange_coefficients_over_roots, quotient_at_root, quotient_factors_over_roots,
+    univariate_ifft, univariate_quotient,
+};
 use crate::structs::BivariateLagrangePolynomial;
-use crate::util::parallelize;
 use crate::{
-    pcs::PolynomialCommitmentScheme,
-    util::{powers_of_field_elements, tensor_product_parallel},
-    BiKZGCommitment, BiKZGProof, BiKZGSRS, BiKZGVerifierParam,
+    pcs::{DistributedPolynomialCommitmentScheme, PolynomialCommitmentScheme},
+    BiKZGBatchProof, BiKZGCommitment, BiKZGPartialProof, BiKZGProof, BiKZGProverParam, BiKZGSRS,
+    BiKZGVerifierParam, CoeffFormBiKZG,
 };
 
 /// Commit to the bi-variate polynomial in its lagrange form.
@@ -34,113 +35,30 @@ where
     E::G1Affine: CurveAffine<ScalarExt = E::Fr, CurveExt = E::G1>,
 {
     type SRS = BiKZGSRS<E>;
-    type ProverParam = BiKZGSRS<E>;
+    type ProverParam = BiKZGProverParam<E>;
     type VerifierParam = BiKZGVerifierParam<E>;
     type Polynomial = BivariateLagrangePolynomial<E::Fr>;
     type Commitment = BiKZGCommitment<E>;
     type Proof = BiKZGProof<E>;
     type Evaluation = E::Fr;
     type Point = (E::Fr, E::Fr);
-    type BatchProof = Vec<Self::Proof>;
+    type BatchProof = BiKZGBatchProof<E>;
+
+    /// The SRS is the same as for the coefficient form, it contains the bases for both forms.
+    fn gen_srs_for_testing(rng: impl RngCore, supported_n: usize, supported_m: usize) -> Self::SRS {
+        CoeffFormBiKZG::<E>::gen_srs_for_testing(rng, supported_n, supported_m)
+    }
 
-    fn gen_srs_for_testing(
-        mut rng: impl RngCore,
+    fn trim(
+        srs: impl Borrow<Self::SRS>,
         supported_n: usize,
         supported_m: usize,
-    ) -> Self::SRS {
-        assert!(supported_n.is_power_of_two());
-        assert!(supported_m.is_power_of_two());
-
-        let tau_0 = E::Fr::random(&mut rng);
-        let tau_1 = E::Fr::random(&mut rng);
-        let g1 = E::G1Affine::generator();
-
-        // roots of unity for supported_n and supported_m
-        let (omega_0, omega_1) = {
-            let omega = E::Fr::ROOT_OF_UNITY;
-            let omega_0 = omega.pow_vartime(&[(1 << E::Fr::S) / supported_n as u64]);
-            let omega_1 = omega.pow_vartime(&[(1 << E::Fr::S) / supported_m as u64]);
-
-            assert!(
-                omega_0.pow_vartime(&[supported_n as u64]) == E::Fr::ONE,
-                "omega_0 is not root of unity for supported_n"
-            );
-            assert!(
-                omega_1.pow_vartime(&[supported_m as u64]) == E::Fr::ONE,
-                "omega_1 is not root of unity for supported_m"
-            );
-            (omega_0, omega_1)
-        };
-
-        // computes the vector of L_i^N(tau_0) * L_j^M(tau_1) for i in 0..supported_n and j in 0..supported_m
-        let (scalars, lagrange_scalars) = {
-            let powers_of_omega_0 = powers_of_field_elements(&omega_0, supported_n);
-            let powers_of_tau_0 = powers_of_field_elements(&tau_0, supported_n);
-            let lagrange_tau_0 = lagrange_coefficients(&powers_of_omega_0, &tau_0);
-            let powers_of_omega_1 = powers_of_field_elements(&omega_1, supported_m);
-            let powers_of_tau_1 = powers_of_field_elements(&tau_1, supported_m);
-            let lagrange_tau_1 = lagrange_coefficients(&powers_of_omega_1, &tau_1);
-            let scalars = tensor_product_parallel(&powers_of_tau_0, &powers_of_tau_1);
-            let lagrange_scalars = tensor_product_parallel(&lagrange_tau_0, &lagrange_tau_1);
-
-            (scalars, lagrange_scalars)
-        };
-
-        let g1_prog = g1.to_curve();
-        let coeff_bases = {
-            let mut proj_bases = vec![E::G1::identity(); supported_n * supported_m];
-            parallelize(&mut proj_bases, |g, start| {
-                for (idx, g) in g.iter_mut().enumerate() {
-                    let offset = start + idx;
-                    *g = g1_prog * scalars[offset];
-                }
-            });
-
-            let mut g_bases = vec![E::G1Affine::identity(); supported_n * supported_m];
-            parallelize(&mut g_bases, |g, starts| {
-                E::G1::batch_normalize(&proj_bases[starts..(starts + g.len())], g);
-            });
-            drop(proj_bases);
-            g_bases
-        };
-
-        let lagrange_bases = {
-            let mut proj_bases = vec![E::G1::identity(); supported_n * supported_m];
-            parallelize(&mut proj_bases, |g, start| {
-                for (idx, g) in g.iter_mut().enumerate() {
-                    let offset = start + idx;
-                    *g = g1 * lagrange_scalars[offset];
-                }
-            });
-
-            let mut affine_bases = vec![E::G1Affine::identity(); supported_n * supported_m];
-            parallelize(&mut affine_bases, |affine_bases, starts| {
-                E::G1::batch_normalize(
-                    &proj_bases[starts..(starts + affine_bases.len())],
-                    affine_bases,
-                );
-            });
-            drop(proj_bases);
-            affine_bases
-        };
-
-        BiKZGSRS {
-            powers_of_g: coeff_bases,
-            powers_of_g_lagrange_over_both_roots: lagrange_bases,
-            h: E::G2Affine::generator(),
-            tau_0_h: (E::G2Affine::generator() * tau_0).into(),
-            tau_1_h: (E::G2Affine::generator() * tau_1).into(),
-        }
+    ) -> (Self::ProverParam, Self::VerifierParam) {
+        let srs = srs.borrow();
+        let prover_param = srs.lagrange_form_prover_param(supported_n, supported_m);
+        (prover_param, BiKZGVerifierParam::from(srs))
     }
 
-    // fn trim(
-    //     srs: impl Borrow<Self::SRS>,
-    //     supported_degree: Option<usize>,
-    //     supported_num_vars: Option<usize>,
-    // ) -> (Self::ProverParam, Self::VerifierParam) {
-    //     unimplemented!()
-    // }
-
     fn commit(
         prover_param: impl Borrow<Self::ProverParam>,
         poly: &Self::Polynomial,
@@ -150,13 +68,7 @@ where
             poly.degree_0, poly.degree_1
         ));
 
-        let com = best_multiexp(
-            &poly.coefficients,
-            prover_param
-                .borrow()
-                .powers_of_g_lagrange_over_both_roots
-                .as_slice(),
-        );
+        let com = best_multiexp(&poly.coefficients, prover_param.borrow().bases.as_slice());
 
         end_timer!(timer);
 
@@ -169,74 +81,58 @@ where
         point: &Self::Point,
     ) -> (Self::Proof, Self::Evaluation) {
         let timer = start_timer!(|| format!(
-            "Opening polynomial of degree {} {}",
+            "Opening lagrange polynomial of degree {} {}",
             polynomial.degree_0, polynomial.degree_1
         ));
 
         let a = point.0;
         let b = point.1;
-        let u = polynomial.evaluate(&a, &b);
 
         let timer2 = start_timer!(|| "Computing the proof pi0");
-        let (pi_0, f_x_b) = {
-            let f_x_b = polynomial.evaluate_y(&point.1);
-            let mut q_0_x_b = f_x_b.clone();
-            q_0_x_b[0] -= u;
-            let q_0_x_b = univariate_quotient(&q_0_x_b, &point.0);
+        let (pi_0, f_x_b, u) = {
+            // f(x, b) in lagrange form, and u = f(a, b)
+            let f_x_b = polynomial.evaluate_at_y(&b);
+            let mut t = univariate_ifft(&f_x_b);
+            let u = t.iter().rev().fold(E::Fr::ZERO, |acc, c| acc * a + c);
+
+            // q_0(x, b) = (f(x, b) - u) / (x - a)
+            t[0] -= u;
+            let q_0_x_b = univariate_quotient(&t, &a);
 
             let pi_0 = best_multiexp(
                 &q_0_x_b,
-                prover_param.borrow().powers_of_g[..polynomial.degree_0].as_ref(),
+                prover_param.borrow().powers_of_tau_0[..polynomial.degree_0].as_ref(),
             )
             .to_affine();
-            (pi_0, f_x_b)
+            (pi_0, f_x_b, u)
         };
         end_timer!(timer2);
 
         let timer2 = start_timer!(|| "Computing the proof pi1");
         let pi_1 = {
+            // t = f(x, y) - f(x, b), over the roots
             let mut t = polynomial.clone();
             t.coefficients
-                .iter_mut()
-                .take(polynomial.degree_0)
-                .zip_eq(f_x_b.iter())
-                .for_each(|(c, f)| *c -= f);
-            let coeffs = t.lagrange_coeffs();
-
-            let mut divisor = vec![E::Fr::from(0); polynomial.degree_0 * polynomial.degree_1];
-            divisor[0] = -point.1;
-            divisor[polynomial.degree_0] = E::Fr::ONE;
-            let divisor =
-                BivariatePolynomial::new(divisor, polynomial.degree_0, polynomial.degree_1);
-
-            let divisor = divisor.lagrange_coeffs();
-
-            // todo: batch invert
-            let y_minus_a_inv_lag = divisor
-                .iter()
-                .map(|o| {
-                    if o.is_zero_vartime() {
-                        panic!("not invertible")
-                    } else {
-                        o.invert().unwrap()
-                    }
-                })
-                .collect::<Vec<_>>();
-
-            let q_1_x_y = coeffs
-                .iter()
-                .zip_eq(y_minus_a_inv_lag.iter())
-                .map(|(c, y)| (*c) * *y)
-                .collect::<Vec<_>>();
-
-            best_multiexp(
-                &q_1_x_y,
-                prover_param
-                    .borrow()
-                    .powers_of_g_lagrange_over_both_roots
-                    .as_ref(),
-            )
-            .to_affine()
+                .chunks_exact_mut(polynomial.degree_0)
+                .for_each(|chunk| {
+                    chunk
+                        .iter_mut()
+                        .zip_eq(f_x_b.iter())
+                        .for_each(|(c, f)| *c -= f)
+                });
+
+            // q_1(x, y) = t(x, y) * (y - b)^{-1}, the derivative in y where b is a root
+            let (y_minus_b_inv, root) = quotient_factors_over_roots(polynomial.degree_1, &b);
+            let mut q_1_x_y = t.coefficients;
+            q_1_x_y
+                .chunks_exact_mut(polynomial.degree_0)
+                .zip_eq(y_minus_b_inv.iter())
+                .for_each(|(chunk, d)| chunk.iter_mut().for_each(|c| *c *= d));
+            if let Some(j) = root {
+                quotient_at_root(&mut q_1_x_y, polynomial.degree_0, j);
+            }
+
+            best_multiexp(&q_1_x_y, prover_param.borrow().bases.as_ref()).to_affine()
         };
         end_timer!(timer2);
         let proof = BiKZGProof::<E> {
@@ -248,6 +144,7 @@ where
         (proof, u)
     }
 
+    /// The proofs have the same form as for the coefficient form.
     fn verify(
         verifier_param: &Self::VerifierParam,
         commitment: &Self::Commitment,
@@ -258,26 +155,156 @@ where
     where
         E: MultiMillerLoop,
     {
-        let pi0_a_pi1_b_g1_cmu = best_multiexp(
-            &[point.0, point.1, E::Fr::ONE, -*value],
-            &[
-                proof.pi0,
-                proof.pi1,
-                commitment.com.into(),
-                verifier_param.g.into(),
-            ],
-        );
-        let pi0_a_pi1_b_g1_cmu = (-pi0_a_pi1_b_g1_cmu).to_affine();
-        let res = E::multi_miller_loop(&[
-            (&proof.pi0, &verifier_param.tau_0_h.into()),
-            (&proof.pi1, &verifier_param.tau_1_h.into()),
-            (&pi0_a_pi1_b_g1_cmu, &verifier_param.h.into()),
-        ]);
-        let res = res.final_exponentiation().is_identity().into();
+        CoeffFormBiKZG::<E>::verify(verifier_param, commitment, point, value, proof)
+    }
+
+    /// The polynomials opened at the same point are combined with powers of a challenge,
+    /// and a single proof is computed for each distinct point.
+    fn multi_open(
+        prover_param: impl Borrow<Self::ProverParam>,
+        polynomials: &[Self::Polynomial],
+        points: &[Self::Point],
+        evals: &[Self::Evaluation],
+    ) -> Self::BatchProof {
+        assert_eq!(polynomials.len(), points.len());
+        assert_eq!(polynomials.len(), evals.len());
+        let timer = start_timer!(|| format!("Multi-opening {} polynomials", polynomials.len()));
+
+        let prover_param = prover_param.borrow();
+        let commitments = polynomials
+            .iter()
+            .map(|p| Self::commit(prover_param, p))
+            .collect::<Vec<_>>();
+        let gamma = combination_challenge(&commitments, points, evals);
+
+        let (distinct, indices) = distinct_points(points);
+        let coefficients = polynomials
+            .iter()
+            .map(|p| p.coefficients.as_slice())
+            .collect::<Vec<_>>();
+        let proofs = combine_coefficients(&coefficients, &indices, distinct.len(), &gamma)
+            .into_iter()
+            .zip(distinct.iter())
+            .map(|(coeffs, point)| {
+                let poly = BivariateLagrangePolynomial::new(
+                    coeffs,
+                    polynomials[0].degree_0,
+                    polynomials[0].degree_1,
+                );
+                Self::open(prover_param, &poly, point).0
+            })
+            .collect();
+
+        end_timer!(timer);
+        BiKZGBatchProof {
+            evals: evals.to_vec(),
+            proofs,
+        }
+    }
 
+    fn batch_verify(
+        verifier_param: &Self::VerifierParam,
+        commitments: &[Self::Commitment],
+        points: &[Self::Point],
+        batch_proof: &Self::BatchProof,
+    ) -> bool {
+        let timer = start_timer!(|| format!("Batch verifying {} openings", points.len()));
+        let res = batch_verify_internal(verifier_param, commitments, points, batch_proof);
+        end_timer!(timer);
         res
     }
+}
+
+impl<E: MultiMillerLoop> DistributedPolynomialCommitmentScheme for LagrangeFormBiKZG<E>
+where
+    E::G1Affine: CurveAffine<ScalarExt = E::Fr, CurveExt = E::G1>,
+{
+    type PartialProof = BiKZGPartialProof<E>;
+
+    /// The rows are the evaluations of f over the roots of unity in x, for the roots of
+    /// unity in y held by the party.
+    fn partial_commit(
+        prover_param: impl Borrow<Self::ProverParam>,
+        party_id: usize,
+        rows: &[Self::Evaluation],
+    ) -> Self::Commitment {
+        let prover_param = prover_param.borrow();
+        let (start, num_rows) = party_rows(prover_param, party_id, rows);
+        let n = prover_param.supported_n;
+        let timer = start_timer!(|| format!("Party {} committing to {} rows", party_id, num_rows));
+        let com = best_multiexp(rows, &prover_param.bases[start * n..(start + num_rows) * n]);
+        end_timer!(timer);
+
+        Self::Commitment { com: com.into() }
+    }
+
+    fn aggregate_commitments(partial_commitments: &[Self::Commitment]) -> Self::Commitment {
+        sum_commitments(partial_commitments)
+    }
 
-    
-    // TODO: implement multi-opening and batch verification
+    /// The quotients by (x - a) are computed pointwise over the roots of unity,
+    /// and from the derivative of the rows where a is one of them.
+    fn partial_open(
+        prover_param: impl Borrow<Self::ProverParam>,
+        party_id: usize,
+        rows: &[Self::Evaluation],
+        point: &Self::Point,
+    ) -> Self::PartialProof {
+        let prover_param = prover_param.borrow();
+        let (start, num_rows) = party_rows(prover_param, party_id, rows);
+        let n = prover_param.supported_n;
+        let timer = start_timer!(|| format!("Party {} opening {} rows", party_id, num_rows));
+
+        let lagrange_a = lagrange_coefficients_over_roots(n, &point.0);
+        let (x_minus_a_inv, root) = quotient_factors_over_roots(n, &point.0);
+
+        // for each row, f_j(a) and q_0_j(x) = (f_j(x) - f_j(a)) / (x - a) over the roots
+        let mut evals = Vec::with_capacity(num_rows);
+        let mut quotients = Vec::with_capacity(rows.len());
+        for row in rows.chunks_exact(n) {
+            let u = row
+                .iter()
+                .zip_eq(lagrange_a.iter())
+                .map(|(f, l)| *f * l)
+                .sum::<E::Fr>();
+            let mut quotient = row
+                .iter()
+                .zip_eq(x_minus_a_inv.iter())
+                .map(|(f, d)| (*f - u) * d)
+                .collect::<Vec<_>>();
+            if let Some(i) = root {
+                quotient_at_root(&mut quotient, 1, i);
+            }
+            quotients.extend(quotient);
+            evals.push(u);
+        }
+        let pi0 = best_multiexp(
+            &quotients,
+            &prover_param.bases[start * n..(start + num_rows) * n],
+        )
+        .to_affine();
+        end_timer!(timer);
+
+        BiKZGPartialProof {
+            party_id,
+            rows: start..start + num_rows,
+            pi0,
+            evals,
+        }
+    }
+
+    /// The evaluations of the rows are the evaluations of f(a, y) over the roots of unity.
+    fn aggregate_proofs(
+        prover_param: impl Borrow<Self::ProverParam>,
+        partial_proofs: &[Self::PartialProof],
+        point: &Self::Point,
+    ) -> (Self::Proof, Self::Evaluation) {
+        let f_a_y = gather_row_evals(prover_param.borrow(), partial_proofs);
+        aggregate_partial_proofs(
+            prover_param.borrow(),
+            partial_proofs,
+            &univariate_ifft(&f_a_y),
+            &point.1,
+        )
+    }
 }
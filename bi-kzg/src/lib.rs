@@ -1,18 +1,25 @@
+mod batch;
 mod bi_fft;
+mod ceremony;
 mod coeff_form_bi_kzg;
+mod distributed;
+mod lagrange_form_bi_kzg;
 mod pcs;
 mod poly;
+mod srs;
 mod structs;
 mod util;
 
-// mod lagrange_form_bi_kzg;
-
 #[cfg(test)]
 mod tests;
 
+pub use ceremony::{BiKZGCeremony, BiKZGContribution};
 pub use coeff_form_bi_kzg::CoeffFormBiKZG;
-pub use pcs::PolynomialCommitmentScheme;
-pub use structs::BivariatePolynomial;
-pub use structs::{BiKZGCommitment, BiKZGProof, BiKZGSRS, BiKZGVerifierParam};
-
-// pub use lagrange_form_bi_kzg::LagrangeFormBiKZG;
+pub use lagrange_form_bi_kzg::LagrangeFormBiKZG;
+pub use pcs::{DistributedPolynomialCommitmentScheme, PolynomialCommitmentScheme};
+pub use srs::MAX_SRS_SIZE;
+pub use structs::{
+    BiKZGBatchProof, BiKZGCommitment, BiKZGPartialProof, BiKZGProof, BiKZGProverParam, BiKZGSRS,
+    BiKZGVerifierParam,
+};
+pub use structs::{BivariateLagrangePolynomial, BivariatePolynomial};
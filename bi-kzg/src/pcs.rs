@@ -34,6 +34,13 @@ pub trait PolynomialCommitmentScheme {
     /// THE OUTPUT SRS SHOULD NOT BE USED IN PRODUCTION.
     fn gen_srs_for_testing(rng: impl RngCore, supported_n: usize, supported_m: usize) -> Self::SRS;
 
+    /// Trim the SRS to the parameters for polynomials of degrees (supported_n, supported_m).
+    fn trim(
+        srs: impl Borrow<Self::SRS>,
+        supported_n: usize,
+        supported_m: usize,
+    ) -> (Self::ProverParam, Self::VerifierParam);
+
     /// Generate a commitment for a polynomial
     /// ## Note on function signature
     /// Usually, data structure like SRS and ProverParam are huge and users
@@ -91,3 +98,41 @@ pub trait PolynomialCommitmentScheme {
         unimplemented!()
     }
 }
+
+/// This trait defines APIs for committing to and opening a polynomial distributed over
+/// several parties. Each party holds a contiguous block of rows of the polynomial, the rows
+/// being the coefficients (or evaluations) in the first variable for a given index of the
+/// second variable. A coordinator aggregates the partial results into a commitment and a
+/// proof that are checked with the usual `verify`.
+pub trait DistributedPolynomialCommitmentScheme: PolynomialCommitmentScheme {
+    /// The partial opening computed by a party
+    type PartialProof: Clone + Debug;
+
+    /// Commit to the rows held by the party `party_id`. All parties hold the same number of
+    /// rows, so that the rows of the party start at `party_id * rows.len()`.
+    fn partial_commit(
+        prover_param: impl Borrow<Self::ProverParam>,
+        party_id: usize,
+        rows: &[Self::Evaluation],
+    ) -> Self::Commitment;
+
+    /// Combine the partial commitments of all the parties.
+    fn aggregate_commitments(partial_commitments: &[Self::Commitment]) -> Self::Commitment;
+
+    /// Open the rows held by the party `party_id` at `point`.
+    fn partial_open(
+        prover_param: impl Borrow<Self::ProverParam>,
+        party_id: usize,
+        rows: &[Self::Evaluation],
+        point: &Self::Point,
+    ) -> Self::PartialProof;
+
+    /// Combine the partial openings of all the parties, in any order, into a proof and the
+    /// evaluation of the whole polynomial at `point`. The rows of the parties must be
+    /// contiguous and cover the polynomial.
+    fn aggregate_proofs(
+        prover_param: impl Borrow<Self::ProverParam>,
+        partial_proofs: &[Self::PartialProof],
+        point: &Self::Point,
+    ) -> (Self::Proof, Self::Evaluation);
+}
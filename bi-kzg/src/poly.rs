@@ -1,5 +1,6 @@
 use ark_std::{end_timer, start_timer};
 use halo2curves::ff::{Field, PrimeField};
+use halo2curves::fft::best_fft;
 use itertools::Itertools;
 use rand::RngCore;
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
@@ -164,8 +165,101 @@ pub(crate) fn univariate_quotient<F: PrimeField>(poly: &[F], point: &F) -> Vec<F
     coefficients
 }
 
+/// For a point x, compute the coefficients of Lagrange polynomial L_{i}(x) at x over the
+/// n-th roots of unity, with the barycentric formula
+/// `L_{i}(x) = \frac{\omega^i (x^n - 1)}{n (x - \omega^i)}`
+pub(crate) fn lagrange_coefficients_over_roots<F: PrimeField>(n: usize, x: &F) -> Vec<F> {
+    let omega = F::ROOT_OF_UNITY.pow_vartime([(1 << F::S) / n as u64]);
+    let powers_of_omega = powers_of_field_elements(&omega, n);
+
+    let x_n_minus_one = x.pow_vartime([n as u64]) - F::ONE;
+    if x_n_minus_one.is_zero_vartime() {
+        // x is one of the roots
+        return powers_of_omega
+            .iter()
+            .map(|w| if w == x { F::ONE } else { F::ZERO })
+            .collect();
+    }
+
+    let factor = x_n_minus_one * F::from(n as u64).invert().unwrap();
+    powers_of_omega
+        .iter()
+        .map(|w| *w * factor * (*x - w).invert().unwrap())
+        .collect()
+}
+
+/// The factors `1 / (\omega^i - a)` to divide by `(x - a)` pointwise over the n-th roots of
+/// unity. If a is the root `\omega^j`, its factor is zero and j is returned: the quotient
+/// at that root is given by `quotient_at_root`.
+pub(crate) fn quotient_factors_over_roots<F: PrimeField>(
+    n: usize,
+    a: &F,
+) -> (Vec<F>, Option<usize>) {
+    let omega = F::ROOT_OF_UNITY.pow_vartime([(1 << F::S) / n as u64]);
+    let mut root = None;
+    let factors = powers_of_field_elements(&omega, n)
+        .iter()
+        .enumerate()
+        .map(|(i, w)| {
+            let d = *w - a;
+            if d.is_zero_vartime() {
+                root = Some(i);
+                F::ZERO
+            } else {
+                d.invert().unwrap()
+            }
+        })
+        .collect();
+    (factors, root)
+}
+
+/// Set the values at the root `\omega^j` of the quotients `q(x) = (g(x) - g(\omega^j)) / (x - \omega^j)`.
+/// `quotients` holds n blocks of `width` values, the block i for `x = \omega^i`, one value
+/// per quotient; the block j is overwritten. The quotient at `\omega^j` is the derivative
+/// `g'(\omega^j)`, and since the derivatives of the Lagrange polynomials sum to zero,
+/// `q(\omega^j) = -\omega^{-j} \sum_{i \neq j} \omega^i q(\omega^i)`.
+pub(crate) fn quotient_at_root<F: PrimeField>(quotients: &mut [F], width: usize, j: usize) {
+    let n = quotients.len() / width;
+    let omega = F::ROOT_OF_UNITY.pow_vartime([(1 << F::S) / n as u64]);
+    let powers_of_omega = powers_of_field_elements(&omega, n);
+
+    let mut derivative = vec![F::ZERO; width];
+    quotients
+        .chunks_exact(width)
+        .zip_eq(powers_of_omega.iter())
+        .enumerate()
+        .filter(|(i, _)| *i != j)
+        .for_each(|(_, (block, w))| {
+            derivative
+                .iter_mut()
+                .zip_eq(block.iter())
+                .for_each(|(d, q)| *d += *q * w)
+        });
+
+    let factor = -powers_of_omega[j].invert().unwrap();
+    quotients[j * width..(j + 1) * width]
+        .iter_mut()
+        .zip_eq(derivative.iter())
+        .for_each(|(q, d)| *q = *d * factor);
+}
+
+/// Convert the evaluations of a univariate polynomial over the n-th roots of unity
+/// to its coefficients via inverse FFT
+pub(crate) fn univariate_ifft<F: PrimeField>(evals: &[F]) -> Vec<F> {
+    let n = evals.len();
+    assert!(n.is_power_of_two());
+    let omega = F::ROOT_OF_UNITY.pow_vartime([(1 << F::S) / n as u64]);
+
+    let mut coeffs = evals.to_vec();
+    best_fft(&mut coeffs, omega.invert().unwrap(), n.trailing_zeros());
+    let n_inv = F::from(n as u64).invert().unwrap();
+    coeffs.iter_mut().for_each(|c| *c *= n_inv);
+    coeffs
+}
+
 impl<F: Field> BivariateLagrangePolynomial<F> {
-    fn new(coeffs: Vec<F>, degree_0: usize, degree_1: usize) -> Self {
+    #[inline]
+    pub fn new(coeffs: Vec<F>, degree_0: usize, degree_1: usize) -> Self {
         assert_eq!(coeffs.len(), degree_0 * degree_1);
         Self {
             coefficients: coeffs,
@@ -173,6 +267,13 @@ impl<F: Field> BivariateLagrangePolynomial<F> {
             degree_1,
         }
     }
+
+    pub fn random(mut rng: impl RngCore, degree_0: usize, degree_1: usize) -> Self {
+        let coefficients = (0..degree_0 * degree_1)
+            .map(|_| F::random(&mut rng))
+            .collect();
+        Self::new(coefficients, degree_0, degree_1)
+    }
 }
 
 impl<F: PrimeField> From<BivariatePolynomial<F>> for BivariateLagrangePolynomial<F> {
@@ -189,7 +290,33 @@ impl<F: PrimeField> From<&BivariatePolynomial<F>> for BivariateLagrangePolynomia
 }
 
 impl<F: PrimeField> BivariateLagrangePolynomial<F> {
+    /// evaluate the polynomial at (x, y)
+    pub fn evaluate(&self, x: &F, y: &F) -> F {
+        self.evaluate_at_y(y)
+            .iter()
+            .zip(lagrange_coefficients_over_roots(self.degree_0, x).iter())
+            .fold(F::ZERO, |acc, (f, l)| acc + *f * l)
+    }
+
+    /// evaluate the polynomial at y, return a univariate polynomial in x,
+    /// in its lagrange form over the roots of unity
+    pub fn evaluate_at_y(&self, y: &F) -> Vec<F> {
+        let lagrange_y = lagrange_coefficients_over_roots(self.degree_1, y);
+        let mut f_x_b = vec![F::ZERO; self.degree_0];
+        lagrange_y
+            .iter()
+            .zip_eq(self.coefficients.chunks_exact(self.degree_0))
+            .for_each(|(l_j, chunk_j)| {
+                f_x_b
+                    .iter_mut()
+                    .zip(chunk_j.iter())
+                    .for_each(|(f, c)| *f += *c * *l_j)
+            });
+        f_x_b
+    }
+
     /// construct a bivariate lagrange polynomial from a monomial f(y) = y - b
+    #[cfg(test)]
     pub(crate) fn from_y_monomial(b: &F, n: usize, m: usize) -> Self {
         // roots of unity for supported_n and supported_m
         let omega_1 = {
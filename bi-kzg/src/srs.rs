@@ -0,0 +1,234 @@
+//! Trimming and storage of the structured reference string.
+//!
+//! The group elements are stored in their compressed encoding, in the order of the fields
+//! of the structs, after the supported degrees as little endian u64.
+//!
+//! The SRS holds the bases of both forms of the polynomials; the prover parameters trimmed
+//! from it keep only those of the form of the scheme.
+
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Error, ErrorKind, Read, Result, Write},
+    path::Path,
+};
+
+use halo2curves::{
+    group::{prime::PrimeCurveAffine, Curve, GroupEncoding},
+    pairing::Engine,
+};
+
+use crate::{bi_fft::group_ifft_in_place, BiKZGProverParam, BiKZGSRS, BiKZGVerifierParam};
+
+/// Largest number of G1 points of each basis of an SRS that is read, 2^28 points take 8 GiB
+/// compressed
+pub const MAX_SRS_SIZE: usize = 1 << 28;
+
+#[inline]
+pub(crate) fn write_point<W: Write, G: GroupEncoding>(p: &G, writer: &mut W) -> Result<()> {
+    writer.write_all(p.to_bytes().as_ref())
+}
+
+#[inline]
+pub(crate) fn read_point<R: Read, G: GroupEncoding>(reader: &mut R) -> Result<G> {
+    let mut repr = G::Repr::default();
+    reader.read_exact(repr.as_mut())?;
+    Option::from(G::from_bytes(&repr))
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid point encoding"))
+}
+
+#[inline]
+pub(crate) fn write_usize<W: Write>(x: usize, writer: &mut W) -> Result<()> {
+    writer.write_all(&(x as u64).to_le_bytes())
+}
+
+#[inline]
+pub(crate) fn read_usize<R: Read>(reader: &mut R) -> Result<usize> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes) as usize)
+}
+
+/// The lagrange bases over both roots of unity from the powers of g,
+/// L_i(tau) = 1/n sum_k omega^{-ik} tau^k computed in the exponent along x then along y
+pub(crate) fn lagrange_bases_from_powers<E: Engine>(
+    powers_of_g: &[E::G1Affine],
+    supported_n: usize,
+    supported_m: usize,
+) -> Vec<E::G1Affine> {
+    let mut proj_bases = powers_of_g.iter().map(|p| p.to_curve()).collect::<Vec<_>>();
+    proj_bases
+        .chunks_exact_mut(supported_n)
+        .for_each(group_ifft_in_place);
+    for i in 0..supported_n {
+        let mut column = (0..supported_m)
+            .map(|j| proj_bases[j * supported_n + i])
+            .collect::<Vec<_>>();
+        group_ifft_in_place(&mut column);
+        for (j, p) in column.into_iter().enumerate() {
+            proj_bases[j * supported_n + i] = p;
+        }
+    }
+    let mut lagrange_bases = vec![E::G1Affine::identity(); supported_n * supported_m];
+    E::G1::batch_normalize(&proj_bases, &mut lagrange_bases);
+    lagrange_bases
+}
+
+impl<E: Engine> BiKZGSRS<E> {
+    /// Trim the SRS to polynomials of degrees (supported_n, supported_m), which must be
+    /// powers of 2 no larger than the degrees of the SRS.
+    /// The lagrange bases over the smaller roots of unity are recomputed from the powers
+    /// of g with an inverse FFT in the exponent.
+    pub fn trim(&self, supported_n: usize, supported_m: usize) -> Self {
+        let powers_of_g = self.trimmed_powers_of_g(supported_n, supported_m);
+        Self {
+            supported_n,
+            supported_m,
+            powers_of_g_lagrange_over_both_roots: lagrange_bases_from_powers::<E>(
+                &powers_of_g,
+                supported_n,
+                supported_m,
+            ),
+            powers_of_g,
+            h: self.h,
+            tau_0_h: self.tau_0_h,
+            tau_1_h: self.tau_1_h,
+        }
+    }
+
+    /// The powers of g for the degrees (supported_n, supported_m), which must be powers of 2
+    /// no larger than the degrees of the SRS
+    fn trimmed_powers_of_g(&self, supported_n: usize, supported_m: usize) -> Vec<E::G1Affine> {
+        assert!(supported_n.is_power_of_two() && supported_n <= self.supported_n);
+        assert!(supported_m.is_power_of_two() && supported_m <= self.supported_m);
+
+        self.powers_of_g
+            .chunks_exact(self.supported_n)
+            .take(supported_m)
+            .flat_map(|row| row[..supported_n].iter().cloned())
+            .collect()
+    }
+
+    fn prover_param(
+        powers_of_g: &[E::G1Affine],
+        bases: Vec<E::G1Affine>,
+        supported_n: usize,
+        supported_m: usize,
+    ) -> BiKZGProverParam<E> {
+        BiKZGProverParam {
+            supported_n,
+            supported_m,
+            bases,
+            powers_of_tau_0: powers_of_g[..supported_n].to_vec(),
+            powers_of_tau_1: powers_of_g.iter().step_by(supported_n).cloned().collect(),
+        }
+    }
+
+    /// The prover parameters for polynomials in coefficient form of degrees
+    /// (supported_n, supported_m), with the powers of g as bases
+    pub fn coeff_form_prover_param(
+        &self,
+        supported_n: usize,
+        supported_m: usize,
+    ) -> BiKZGProverParam<E> {
+        let powers_of_g = self.trimmed_powers_of_g(supported_n, supported_m);
+        Self::prover_param(&powers_of_g, powers_of_g.clone(), supported_n, supported_m)
+    }
+
+    /// The prover parameters for polynomials in lagrange form of degrees
+    /// (supported_n, supported_m), with the lagrange bases as bases. They are recomputed
+    /// over the smaller roots of unity if the degrees are lower than the ones of the SRS.
+    pub fn lagrange_form_prover_param(
+        &self,
+        supported_n: usize,
+        supported_m: usize,
+    ) -> BiKZGProverParam<E> {
+        let powers_of_g = self.trimmed_powers_of_g(supported_n, supported_m);
+        let bases = if (supported_n, supported_m) == (self.supported_n, self.supported_m) {
+            self.powers_of_g_lagrange_over_both_roots.clone()
+        } else {
+            lagrange_bases_from_powers::<E>(&powers_of_g, supported_n, supported_m)
+        };
+        Self::prover_param(&powers_of_g, bases, supported_n, supported_m)
+    }
+
+    pub fn serialize_into<W: Write>(&self, mut writer: W) -> Result<()> {
+        write_usize(self.supported_n, &mut writer)?;
+        write_usize(self.supported_m, &mut writer)?;
+        for p in self
+            .powers_of_g
+            .iter()
+            .chain(self.powers_of_g_lagrange_over_both_roots.iter())
+        {
+            write_point(p, &mut writer)?;
+        }
+        write_point(&self.h, &mut writer)?;
+        write_point(&self.tau_0_h, &mut writer)?;
+        write_point(&self.tau_1_h, &mut writer)
+    }
+
+    pub fn deserialize_from<R: Read>(mut reader: R) -> Result<Self> {
+        let supported_n = read_usize(&mut reader)?;
+        let supported_m = read_usize(&mut reader)?;
+        if !supported_n.is_power_of_two() || !supported_m.is_power_of_two() {
+            return Err(Error::new(ErrorKind::InvalidData, "invalid SRS degrees"));
+        }
+        // the degrees are checked before anything is allocated for them
+        let size = supported_n
+            .checked_mul(supported_m)
+            .filter(|size| *size <= MAX_SRS_SIZE)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "SRS too large"))?;
+        let powers_of_g = (0..size)
+            .map(|_| read_point(&mut reader))
+            .collect::<Result<Vec<_>>>()?;
+        let powers_of_g_lagrange_over_both_roots = (0..size)
+            .map(|_| read_point(&mut reader))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            supported_n,
+            supported_m,
+            powers_of_g,
+            powers_of_g_lagrange_over_both_roots,
+            h: read_point(&mut reader)?,
+            tau_0_h: read_point(&mut reader)?,
+            tau_1_h: read_point(&mut reader)?,
+        })
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.serialize_into(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::deserialize_from(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<E: Engine> BiKZGVerifierParam<E> {
+    pub fn serialize_into<W: Write>(&self, mut writer: W) -> Result<()> {
+        write_point(&self.g, &mut writer)?;
+        write_point(&self.h, &mut writer)?;
+        write_point(&self.tau_0_h, &mut writer)?;
+        write_point(&self.tau_1_h, &mut writer)
+    }
+
+    pub fn deserialize_from<R: Read>(mut reader: R) -> Result<Self> {
+        Ok(Self {
+            g: read_point(&mut reader)?,
+            h: read_point(&mut reader)?,
+            tau_0_h: read_point(&mut reader)?,
+            tau_1_h: read_point(&mut reader)?,
+        })
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.serialize_into(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::deserialize_from(BufReader::new(File::open(path)?))
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use halo2curves::pairing::Engine;
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -17,6 +19,10 @@ pub struct BivariateLagrangePolynomial<F> {
 /// Structured reference string for Bi-KZG polynomial commitment scheme.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct BiKZGSRS<E: Engine> {
+    /// the supported degree in the first variable
+    pub supported_n: usize,
+    /// the supported degree in the second variable
+    pub supported_m: usize,
     /// (g_1^{\tau_0^i\tau_1^j})_{i\in [0,N], j\in [0, M]} = \\
     /// (
     ///  g_1, g_1^{\tau_0}, g_1^{\tau_0^2}, ..., g_1^{\tau_0^N},
@@ -34,6 +40,24 @@ pub struct BiKZGSRS<E: Engine> {
     pub tau_1_h: E::G2Affine,
 }
 
+/// Prover parameters of Bi-KZG for polynomials of degrees (supported_n, supported_m), with
+/// the bases of the form of the polynomials only.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BiKZGProverParam<E: Engine> {
+    /// the supported degree in the first variable
+    pub supported_n: usize,
+    /// the supported degree in the second variable
+    pub supported_m: usize,
+    /// the bases of the commitments: g_1^{\tau_0^i\tau_1^j} for the coefficient form, g in
+    /// lagrange form over omega_0 and omega_1 for the lagrange form
+    pub bases: Vec<E::G1Affine>,
+    /// (g_1^{\tau_0^i})_{i\in [0,N]}, for the quotients by (x - a)
+    pub powers_of_tau_0: Vec<E::G1Affine>,
+    /// (g_1^{\tau_1^j})_{j\in [0,M]}, for the quotients by (y - b) of the distributed
+    /// openings
+    pub powers_of_tau_1: Vec<E::G1Affine>,
+}
+
 /// `UnivariateVerifierParam` is used to check evaluation proofs for a given
 /// commitment.
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
@@ -63,6 +87,28 @@ pub struct BiKZGProof<E: Engine> {
     pub pi1: E::G1Affine,
 }
 
+/// Multi-opening proof for Bi-KZG polynomial commitment scheme.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BiKZGBatchProof<E: Engine> {
+    /// the evaluations of the polynomials at their points
+    pub evals: Vec<E::Fr>,
+    /// one proof per distinct point, for the combination of the polynomials opened there
+    pub proofs: Vec<BiKZGProof<E>>,
+}
+
+/// Partial opening of the rows held by one party, for the distributed Bi-KZG.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BiKZGPartialProof<E: Engine> {
+    /// the id of the party
+    pub party_id: usize,
+    /// the range of the rows of the party within the polynomial
+    pub rows: Range<usize>,
+    /// the commitment to the quotients by (x - a) of the rows of the party
+    pub pi0: E::G1Affine,
+    /// the evaluations f_j(a) of the rows of the party
+    pub evals: Vec<E::Fr>,
+}
+
 impl<E: Engine> From<&BiKZGSRS<E>> for BiKZGVerifierParam<E> {
     fn from(srs: &BiKZGSRS<E>) -> Self {
         Self {
@@ -1,17 +1,18 @@
 use ark_std::test_rng;
 use halo2curves::{
     bn256::{Bn256, Fr},
-    ff::Field,
+    ff::{Field, PrimeField},
 };
 
 use crate::{
     bi_fft::bi_fft_in_place,
     coeff_form_bi_kzg::CoeffFormBiKZG,
-    pcs::PolynomialCommitmentScheme,
+    lagrange_form_bi_kzg::LagrangeFormBiKZG,
+    pcs::{DistributedPolynomialCommitmentScheme, PolynomialCommitmentScheme},
     poly::{lagrange_coefficients, univariate_quotient},
     structs::BivariateLagrangePolynomial,
     util::tensor_product_parallel,
-    BiKZGVerifierParam, BivariatePolynomial,
+    BiKZGCeremony, BiKZGSRS, BiKZGVerifierParam, BivariatePolynomial, MAX_SRS_SIZE,
 };
 
 #[test]
@@ -21,15 +22,15 @@ fn test_bi_kzg_single_pass() {
     let m = 32;
 
     let srs = CoeffFormBiKZG::<Bn256>::gen_srs_for_testing(&mut rng, n, m);
-    let vk = BiKZGVerifierParam::<Bn256>::from(&srs);
+    let (pp, vk) = CoeffFormBiKZG::<Bn256>::trim(&srs, n, m);
 
     let poly = BivariatePolynomial::<Fr>::random(&mut rng, n, m);
 
     let x = Fr::random(&mut rng);
     let y = Fr::random(&mut rng);
 
-    let commit = CoeffFormBiKZG::<Bn256>::commit(&srs, &poly);
-    let (proof, eval) = CoeffFormBiKZG::<Bn256>::open(&srs, &poly, &(x, y));
+    let commit = CoeffFormBiKZG::<Bn256>::commit(&pp, &poly);
+    let (proof, eval) = CoeffFormBiKZG::<Bn256>::open(&pp, &poly, &(x, y));
     assert!(CoeffFormBiKZG::<Bn256>::verify(
         &vk,
         &commit,
@@ -45,7 +46,7 @@ fn test_bi_kzg_e2e() {
     let n = 2;
     let m = 4;
     let srs = CoeffFormBiKZG::<Bn256>::gen_srs_for_testing(&mut rng, n, m);
-    let vk = BiKZGVerifierParam::<Bn256>::from(&srs);
+    let (pp, vk) = CoeffFormBiKZG::<Bn256>::trim(&srs, n, m);
     let poly = BivariatePolynomial::new(
         vec![
             Fr::from(1u64),
@@ -67,8 +68,8 @@ fn test_bi_kzg_e2e() {
 
     assert_eq!(poly.evaluate(&x, &y), Fr::from(85309u64));
 
-    let commit = CoeffFormBiKZG::<Bn256>::commit(&srs, &poly);
-    let (proof, eval) = CoeffFormBiKZG::<Bn256>::open(&srs, &poly, &(x, y));
+    let commit = CoeffFormBiKZG::<Bn256>::commit(&pp, &poly);
+    let (proof, eval) = CoeffFormBiKZG::<Bn256>::open(&pp, &poly, &(x, y));
 
     assert!(CoeffFormBiKZG::<Bn256>::verify(
         &vk,
@@ -81,15 +82,15 @@ fn test_bi_kzg_e2e() {
     for n in [2, 4, 8, 16] {
         for m in [2, 4, 8, 16] {
             let srs = CoeffFormBiKZG::<Bn256>::gen_srs_for_testing(&mut rng, n, m);
-            let vk = BiKZGVerifierParam::<Bn256>::from(&srs);
+            let (pp, vk) = CoeffFormBiKZG::<Bn256>::trim(&srs, n, m);
             for _ in 0..10 {
                 let poly = BivariatePolynomial::<Fr>::random(&mut rng, n, m);
 
                 let x = Fr::random(&mut rng);
                 let y = Fr::random(&mut rng);
 
-                let commit = CoeffFormBiKZG::<Bn256>::commit(&srs, &poly);
-                let (proof, eval) = CoeffFormBiKZG::<Bn256>::open(&srs, &poly, &(x, y));
+                let commit = CoeffFormBiKZG::<Bn256>::commit(&pp, &poly);
+                let (proof, eval) = CoeffFormBiKZG::<Bn256>::open(&pp, &poly, &(x, y));
                 assert!(CoeffFormBiKZG::<Bn256>::verify(
                     &vk,
                     &commit,
@@ -102,6 +103,559 @@ fn test_bi_kzg_e2e() {
     }
 }
 
+#[test]
+fn test_lagrange_bi_kzg_single_pass() {
+    let mut rng = test_rng();
+    let n = 16;
+    let m = 32;
+
+    let srs = LagrangeFormBiKZG::<Bn256>::gen_srs_for_testing(&mut rng, n, m);
+    let (pp, vk) = LagrangeFormBiKZG::<Bn256>::trim(&srs, n, m);
+
+    let poly = BivariateLagrangePolynomial::<Fr>::random(&mut rng, n, m);
+
+    let x = Fr::random(&mut rng);
+    let y = Fr::random(&mut rng);
+
+    let commit = LagrangeFormBiKZG::<Bn256>::commit(&pp, &poly);
+    let (proof, eval) = LagrangeFormBiKZG::<Bn256>::open(&pp, &poly, &(x, y));
+    assert!(LagrangeFormBiKZG::<Bn256>::verify(
+        &vk,
+        &commit,
+        &(x, y),
+        &eval,
+        &proof
+    ));
+    assert!(!LagrangeFormBiKZG::<Bn256>::verify(
+        &vk,
+        &commit,
+        &(x, y),
+        &(eval + Fr::ONE),
+        &proof
+    ));
+}
+
+#[test]
+fn test_bi_kzg_open_at_roots_of_unity() {
+    let mut rng = test_rng();
+    let (n, m) = (16, 32);
+    let srs = CoeffFormBiKZG::<Bn256>::gen_srs_for_testing(&mut rng, n, m);
+    let (pp, vk) = CoeffFormBiKZG::<Bn256>::trim(&srs, n, m);
+    let (lag_pp, _) = LagrangeFormBiKZG::<Bn256>::trim(&srs, n, m);
+    let poly = BivariatePolynomial::<Fr>::random(&mut rng, n, m);
+    let lag_poly = BivariateLagrangePolynomial::from(&poly);
+
+    let omega_0 = Field::pow_vartime(&Fr::ROOT_OF_UNITY, [(1 << Fr::S) / n as u64]);
+    let omega_1 = Field::pow_vartime(&Fr::ROOT_OF_UNITY, [(1 << Fr::S) / m as u64]);
+    let a = Field::pow_vartime(&omega_0, [3]);
+    let b = Field::pow_vartime(&omega_1, [5]);
+    let commit = CoeffFormBiKZG::<Bn256>::commit(&pp, &poly);
+
+    // either coordinate, or both, on the roots
+    for (x, y) in [(a, Fr::random(&mut rng)), (Fr::random(&mut rng), b), (a, b)] {
+        let (proof, eval) = CoeffFormBiKZG::<Bn256>::open(&pp, &poly, &(x, y));
+        assert_eq!(eval, poly.evaluate(&x, &y));
+        assert!(CoeffFormBiKZG::<Bn256>::verify(
+            &vk,
+            &commit,
+            &(x, y),
+            &eval,
+            &proof
+        ));
+
+        let (proof, eval) = LagrangeFormBiKZG::<Bn256>::open(&lag_pp, &lag_poly, &(x, y));
+        assert_eq!(eval, poly.evaluate(&x, &y));
+        assert!(LagrangeFormBiKZG::<Bn256>::verify(
+            &vk,
+            &commit,
+            &(x, y),
+            &eval,
+            &proof
+        ));
+        assert!(!LagrangeFormBiKZG::<Bn256>::verify(
+            &vk,
+            &commit,
+            &(x, y),
+            &(eval + Fr::ONE),
+            &proof
+        ));
+
+        let proofs = lag_poly
+            .coefficients
+            .chunks(n * m / 2)
+            .enumerate()
+            .map(|(party_id, rows)| {
+                LagrangeFormBiKZG::<Bn256>::partial_open(&lag_pp, party_id, rows, &(x, y))
+            })
+            .collect::<Vec<_>>();
+        let (proof, eval) = LagrangeFormBiKZG::<Bn256>::aggregate_proofs(&lag_pp, &proofs, &(x, y));
+        assert_eq!(eval, poly.evaluate(&x, &y));
+        assert!(LagrangeFormBiKZG::<Bn256>::verify(
+            &vk,
+            &commit,
+            &(x, y),
+            &eval,
+            &proof
+        ));
+    }
+}
+
+#[test]
+fn test_lagrange_bi_kzg_e2e() {
+    let mut rng = test_rng();
+    let n = 2;
+    let m = 4;
+    let srs = LagrangeFormBiKZG::<Bn256>::gen_srs_for_testing(&mut rng, n, m);
+    let (pp, vk) = LagrangeFormBiKZG::<Bn256>::trim(&srs, n, m);
+    let poly = BivariatePolynomial::new(
+        vec![
+            Fr::from(1u64),
+            Fr::from(2u64),
+            Fr::from(3u64),
+            Fr::from(4u64),
+            Fr::from(5u64),
+            Fr::from(6u64),
+            Fr::from(7u64),
+            Fr::from(8u64),
+        ],
+        n,
+        m,
+    );
+    let lag_poly = BivariateLagrangePolynomial::from(&poly);
+
+    let x = Fr::from(9u64);
+    let y = Fr::from(10u64);
+
+    assert_eq!(lag_poly.evaluate(&x, &y), Fr::from(85309u64));
+
+    // both forms commit to the same polynomial
+    let commit = LagrangeFormBiKZG::<Bn256>::commit(&pp, &lag_poly);
+    let (coeff_pp, _) = CoeffFormBiKZG::<Bn256>::trim(&srs, n, m);
+    assert_eq!(
+        commit.com,
+        CoeffFormBiKZG::<Bn256>::commit(&coeff_pp, &poly).com
+    );
+
+    let (proof, eval) = LagrangeFormBiKZG::<Bn256>::open(&pp, &lag_poly, &(x, y));
+    assert_eq!(eval, Fr::from(85309u64));
+    assert!(LagrangeFormBiKZG::<Bn256>::verify(
+        &vk,
+        &commit,
+        &(x, y),
+        &eval,
+        &proof
+    ));
+
+    for n in [2, 4, 8, 16] {
+        for m in [2, 4, 8, 16] {
+            let srs = LagrangeFormBiKZG::<Bn256>::gen_srs_for_testing(&mut rng, n, m);
+            let (pp, vk) = LagrangeFormBiKZG::<Bn256>::trim(&srs, n, m);
+            for _ in 0..10 {
+                let poly = BivariateLagrangePolynomial::<Fr>::random(&mut rng, n, m);
+
+                let x = Fr::random(&mut rng);
+                let y = Fr::random(&mut rng);
+
+                let commit = LagrangeFormBiKZG::<Bn256>::commit(&pp, &poly);
+                let (proof, eval) = LagrangeFormBiKZG::<Bn256>::open(&pp, &poly, &(x, y));
+                assert!(LagrangeFormBiKZG::<Bn256>::verify(
+                    &vk,
+                    &commit,
+                    &(x, y),
+                    &eval,
+                    &proof
+                ));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_lagrange_poly_eval() {
+    let mut rng = test_rng();
+    for (n, m) in [(2, 4), (4, 2), (8, 8)] {
+        let poly = BivariatePolynomial::<Fr>::random(&mut rng, n, m);
+        let lag_poly = BivariateLagrangePolynomial::from(&poly);
+
+        let x = Fr::random(&mut rng);
+        let y = Fr::random(&mut rng);
+        assert_eq!(lag_poly.evaluate(&x, &y), poly.evaluate(&x, &y));
+
+        // at a root of unity, the evaluation is one of the coefficients
+        let omega_0 = Field::pow_vartime(&Fr::ROOT_OF_UNITY, [(1 << Fr::S) / n as u64]);
+        assert_eq!(lag_poly.evaluate(&omega_0, &y), poly.evaluate(&omega_0, &y));
+    }
+}
+
+#[test]
+fn test_bi_kzg_batch() {
+    let mut rng = test_rng();
+    let n = 8;
+    let m = 16;
+
+    let srs = CoeffFormBiKZG::<Bn256>::gen_srs_for_testing(&mut rng, n, m);
+    let (pp, vk) = CoeffFormBiKZG::<Bn256>::trim(&srs, n, m);
+    let (lag_pp, _) = LagrangeFormBiKZG::<Bn256>::trim(&srs, n, m);
+
+    // 6 polynomials opened at 3 distinct points
+    let distinct = (0..3)
+        .map(|_| (Fr::random(&mut rng), Fr::random(&mut rng)))
+        .collect::<Vec<_>>();
+    let points = (0..6).map(|i| distinct[i % 3]).collect::<Vec<_>>();
+    let polys = (0..6)
+        .map(|_| BivariatePolynomial::<Fr>::random(&mut rng, n, m))
+        .collect::<Vec<_>>();
+    let evals = polys
+        .iter()
+        .zip(points.iter())
+        .map(|(p, (x, y))| p.evaluate(x, y))
+        .collect::<Vec<_>>();
+
+    // coefficient form
+    let commits = polys
+        .iter()
+        .map(|p| CoeffFormBiKZG::<Bn256>::commit(&pp, p))
+        .collect::<Vec<_>>();
+    let batch_proof = CoeffFormBiKZG::<Bn256>::multi_open(&pp, &polys, &points, &evals);
+    assert_eq!(batch_proof.proofs.len(), 3);
+    assert!(CoeffFormBiKZG::<Bn256>::batch_verify(
+        &vk,
+        &commits,
+        &points,
+        &batch_proof
+    ));
+
+    let mut wrong_proof = batch_proof.clone();
+    wrong_proof.evals[4] += Fr::ONE;
+    assert!(!CoeffFormBiKZG::<Bn256>::batch_verify(
+        &vk,
+        &commits,
+        &points,
+        &wrong_proof
+    ));
+    let mut wrong_points = points.clone();
+    wrong_points.swap(0, 1);
+    assert!(!CoeffFormBiKZG::<Bn256>::batch_verify(
+        &vk,
+        &commits,
+        &wrong_points,
+        &batch_proof
+    ));
+    assert!(!CoeffFormBiKZG::<Bn256>::batch_verify(
+        &vk,
+        &commits[..5],
+        &points[..5],
+        &batch_proof
+    ));
+
+    // lagrange form
+    let lag_polys = polys
+        .iter()
+        .map(BivariateLagrangePolynomial::from)
+        .collect::<Vec<_>>();
+    let lag_commits = lag_polys
+        .iter()
+        .map(|p| LagrangeFormBiKZG::<Bn256>::commit(&lag_pp, p))
+        .collect::<Vec<_>>();
+    let batch_proof = LagrangeFormBiKZG::<Bn256>::multi_open(&lag_pp, &lag_polys, &points, &evals);
+    assert!(LagrangeFormBiKZG::<Bn256>::batch_verify(
+        &vk,
+        &lag_commits,
+        &points,
+        &batch_proof
+    ));
+
+    let mut wrong_proof = batch_proof.clone();
+    wrong_proof.evals[0] += Fr::ONE;
+    assert!(!LagrangeFormBiKZG::<Bn256>::batch_verify(
+        &vk,
+        &lag_commits,
+        &points,
+        &wrong_proof
+    ));
+}
+
+#[test]
+fn test_bi_kzg_trim() {
+    let mut rng = test_rng();
+    let srs = CoeffFormBiKZG::<Bn256>::gen_srs_for_testing(&mut rng, 16, 8);
+
+    for (n, m) in [(16, 8), (4, 8), (8, 2), (1, 4)] {
+        let (pp, vk) = CoeffFormBiKZG::<Bn256>::trim(&srs, n, m);
+        let (lag_pp, _) = LagrangeFormBiKZG::<Bn256>::trim(&srs, n, m);
+        // each form keeps only its own bases
+        let trimmed = srs.trim(n, m);
+        assert_eq!(pp.bases, trimmed.powers_of_g);
+        assert_eq!(lag_pp.bases, trimmed.powers_of_g_lagrange_over_both_roots);
+        assert_eq!(pp.powers_of_tau_0, lag_pp.powers_of_tau_0);
+        assert_eq!(pp.powers_of_tau_0.len(), n);
+        assert_eq!(pp.powers_of_tau_1.len(), m);
+        assert_eq!(vk.tau_0_h, srs.tau_0_h);
+        assert_eq!(vk.tau_1_h, srs.tau_1_h);
+
+        let poly = BivariatePolynomial::<Fr>::random(&mut rng, n, m);
+        let lag_poly = BivariateLagrangePolynomial::from(&poly);
+        let commit = CoeffFormBiKZG::<Bn256>::commit(&pp, &poly);
+        // the recomputed lagrange bases commit to the same polynomial
+        assert_eq!(
+            commit.com,
+            LagrangeFormBiKZG::<Bn256>::commit(&lag_pp, &lag_poly).com
+        );
+
+        let x = Fr::random(&mut rng);
+        let y = Fr::random(&mut rng);
+        let (proof, eval) = CoeffFormBiKZG::<Bn256>::open(&pp, &poly, &(x, y));
+        assert!(CoeffFormBiKZG::<Bn256>::verify(
+            &vk,
+            &commit,
+            &(x, y),
+            &eval,
+            &proof
+        ));
+        let (proof, eval) = LagrangeFormBiKZG::<Bn256>::open(&lag_pp, &lag_poly, &(x, y));
+        assert!(LagrangeFormBiKZG::<Bn256>::verify(
+            &vk,
+            &commit,
+            &(x, y),
+            &eval,
+            &proof
+        ));
+    }
+}
+
+#[test]
+fn test_bi_kzg_srs_save_load() {
+    let mut rng = test_rng();
+    let srs = CoeffFormBiKZG::<Bn256>::gen_srs_for_testing(&mut rng, 4, 8);
+    let vk = BiKZGVerifierParam::<Bn256>::from(&srs);
+
+    let dir = std::env::temp_dir();
+    let srs_path = dir.join("bi_kzg_test_srs.bin");
+    let vk_path = dir.join("bi_kzg_test_vk.bin");
+    srs.save_to_file(&srs_path).unwrap();
+    vk.save_to_file(&vk_path).unwrap();
+    let loaded_srs = BiKZGSRS::<Bn256>::load_from_file(&srs_path).unwrap();
+    let loaded_vk = BiKZGVerifierParam::<Bn256>::load_from_file(&vk_path).unwrap();
+    std::fs::remove_file(&srs_path).unwrap();
+    std::fs::remove_file(&vk_path).unwrap();
+    let mut buf = vec![];
+    srs.serialize_into(&mut buf).unwrap();
+    let mut loaded_buf = vec![];
+    loaded_srs.serialize_into(&mut loaded_buf).unwrap();
+    assert_eq!(buf, loaded_buf);
+    assert_eq!(srs.powers_of_g, loaded_srs.powers_of_g);
+    assert_eq!(vk.g, loaded_vk.g);
+    assert_eq!(vk.h, loaded_vk.h);
+    assert_eq!(vk.tau_0_h, loaded_vk.tau_0_h);
+    assert_eq!(vk.tau_1_h, loaded_vk.tau_1_h);
+
+    // truncated or corrupted data is rejected
+    assert!(BiKZGSRS::<Bn256>::deserialize_from(&buf[..buf.len() - 1]).is_err());
+    // as well as degrees too large to allocate, before reading the points
+    let mut header = vec![];
+    header.extend_from_slice(&(1u64 << 32).to_le_bytes());
+    header.extend_from_slice(&(1u64 << 32).to_le_bytes());
+    assert!(BiKZGSRS::<Bn256>::deserialize_from(&header[..]).is_err());
+    let mut header = vec![];
+    header.extend_from_slice(&(MAX_SRS_SIZE as u64).to_le_bytes());
+    header.extend_from_slice(&2u64.to_le_bytes());
+    assert!(BiKZGSRS::<Bn256>::deserialize_from(&header[..]).is_err());
+    buf[0] = 3;
+    assert!(BiKZGSRS::<Bn256>::deserialize_from(&buf[..]).is_err());
+}
+
+#[test]
+fn test_bi_kzg_distributed() {
+    let mut rng = test_rng();
+    let (n, m) = (16, 8);
+    let srs = CoeffFormBiKZG::<Bn256>::gen_srs_for_testing(&mut rng, n, m);
+    let (pp, vk) = CoeffFormBiKZG::<Bn256>::trim(&srs, n, m);
+    let (lag_pp, _) = LagrangeFormBiKZG::<Bn256>::trim(&srs, n, m);
+    let poly = BivariatePolynomial::<Fr>::random(&mut rng, n, m);
+    let lag_poly = BivariateLagrangePolynomial::from(&poly);
+    let x = Fr::random(&mut rng);
+    let y = Fr::random(&mut rng);
+
+    for num_parties in [1, 2, 4, 8] {
+        let rows_per_party = n * m / num_parties;
+
+        // each party works on its own rows in its own thread
+        let (coeff_partials, lag_partials): (Vec<_>, Vec<_>) = std::thread::scope(|s| {
+            let handles = poly
+                .coefficients
+                .chunks(rows_per_party)
+                .zip(lag_poly.coefficients.chunks(rows_per_party))
+                .enumerate()
+                .map(|(party_id, (rows, lag_rows))| {
+                    let (pp, lag_pp) = (&pp, &lag_pp);
+                    s.spawn(move || {
+                        (
+                            (
+                                CoeffFormBiKZG::<Bn256>::partial_commit(pp, party_id, rows),
+                                CoeffFormBiKZG::<Bn256>::partial_open(pp, party_id, rows, &(x, y)),
+                            ),
+                            (
+                                LagrangeFormBiKZG::<Bn256>::partial_commit(
+                                    lag_pp, party_id, lag_rows,
+                                ),
+                                LagrangeFormBiKZG::<Bn256>::partial_open(
+                                    lag_pp,
+                                    party_id,
+                                    lag_rows,
+                                    &(x, y),
+                                ),
+                            ),
+                        )
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles.into_iter().map(|h| h.join().unwrap()).unzip()
+        });
+
+        // the coordinator aggregates the partial results into the standard ones
+        let (coms, proofs): (Vec<_>, Vec<_>) = coeff_partials.into_iter().unzip();
+        let commit = CoeffFormBiKZG::<Bn256>::aggregate_commitments(&coms);
+        let (proof, eval) = CoeffFormBiKZG::<Bn256>::aggregate_proofs(&pp, &proofs, &(x, y));
+        assert_eq!(commit.com, CoeffFormBiKZG::<Bn256>::commit(&pp, &poly).com);
+        assert_eq!(eval, poly.evaluate(&x, &y));
+        assert!(CoeffFormBiKZG::<Bn256>::verify(
+            &vk,
+            &commit,
+            &(x, y),
+            &eval,
+            &proof
+        ));
+        assert!(!CoeffFormBiKZG::<Bn256>::verify(
+            &vk,
+            &commit,
+            &(x, y),
+            &(eval + Fr::ONE),
+            &proof
+        ));
+
+        let (coms, proofs): (Vec<_>, Vec<_>) = lag_partials.into_iter().unzip();
+        let lag_commit = LagrangeFormBiKZG::<Bn256>::aggregate_commitments(&coms);
+        let (proof, eval) = LagrangeFormBiKZG::<Bn256>::aggregate_proofs(&lag_pp, &proofs, &(x, y));
+        assert_eq!(lag_commit.com, commit.com);
+        assert_eq!(eval, poly.evaluate(&x, &y));
+        assert!(LagrangeFormBiKZG::<Bn256>::verify(
+            &vk,
+            &lag_commit,
+            &(x, y),
+            &eval,
+            &proof
+        ));
+
+        // the coordinator may receive the partial proofs in any order
+        let mut reversed = proofs.clone();
+        reversed.reverse();
+        let (reversed_proof, reversed_eval) =
+            LagrangeFormBiKZG::<Bn256>::aggregate_proofs(&lag_pp, &reversed, &(x, y));
+        assert_eq!(reversed_proof.pi1, proof.pi1);
+        assert_eq!(reversed_eval, eval);
+
+        // a party lying about its evaluations is caught
+        let mut wrong_proofs = proofs.clone();
+        wrong_proofs[num_parties - 1].evals[0] += Fr::ONE;
+        let (proof, eval) =
+            LagrangeFormBiKZG::<Bn256>::aggregate_proofs(&lag_pp, &wrong_proofs, &(x, y));
+        assert!(!LagrangeFormBiKZG::<Bn256>::verify(
+            &vk,
+            &lag_commit,
+            &(x, y),
+            &eval,
+            &proof
+        ));
+    }
+}
+
+#[test]
+#[should_panic(expected = "do not follow")]
+fn test_bi_kzg_distributed_missing_rows() {
+    let mut rng = test_rng();
+    let (n, m) = (4, 8);
+    let srs = CoeffFormBiKZG::<Bn256>::gen_srs_for_testing(&mut rng, n, m);
+    let (pp, _) = CoeffFormBiKZG::<Bn256>::trim(&srs, n, m);
+    let poly = BivariatePolynomial::<Fr>::random(&mut rng, n, m);
+    let point = (Fr::random(&mut rng), Fr::random(&mut rng));
+
+    // the second of the four parties is missing
+    let proofs = poly
+        .coefficients
+        .chunks(n * m / 4)
+        .enumerate()
+        .filter(|(party_id, _)| *party_id != 1)
+        .map(|(party_id, rows)| CoeffFormBiKZG::<Bn256>::partial_open(&pp, party_id, rows, &point))
+        .collect::<Vec<_>>();
+    assert_eq!(proofs[1].rows, 4..6);
+    CoeffFormBiKZG::<Bn256>::aggregate_proofs(&pp, &proofs, &point);
+}
+
+#[test]
+fn test_bi_kzg_ceremony() {
+    let mut rng = test_rng();
+    let (n, m) = (8, 4);
+    let mut ceremony = BiKZGCeremony::<Bn256>::new(n, m);
+    // the initial SRS is not the result of a contribution
+    assert!(!ceremony.verify(&mut rng));
+    for _ in 0..3 {
+        ceremony.contribute(&mut rng);
+        assert!(ceremony.verify(&mut rng));
+    }
+
+    let (pp, vk) = CoeffFormBiKZG::<Bn256>::trim(&ceremony.srs, n, m);
+    let (lag_pp, _) = LagrangeFormBiKZG::<Bn256>::trim(&ceremony.srs, n, m);
+    let poly = BivariatePolynomial::<Fr>::random(&mut rng, n, m);
+    let lag_poly = BivariateLagrangePolynomial::from(&poly);
+    let commit = CoeffFormBiKZG::<Bn256>::commit(&pp, &poly);
+    assert_eq!(
+        commit.com,
+        LagrangeFormBiKZG::<Bn256>::commit(&lag_pp, &lag_poly).com
+    );
+    let x = Fr::random(&mut rng);
+    let y = Fr::random(&mut rng);
+    let (proof, eval) = CoeffFormBiKZG::<Bn256>::open(&pp, &poly, &(x, y));
+    assert!(CoeffFormBiKZG::<Bn256>::verify(
+        &vk,
+        &commit,
+        &(x, y),
+        &eval,
+        &proof
+    ));
+    let (proof, eval) = LagrangeFormBiKZG::<Bn256>::open(&lag_pp, &lag_poly, &(x, y));
+    assert!(LagrangeFormBiKZG::<Bn256>::verify(
+        &vk,
+        &commit,
+        &(x, y),
+        &eval,
+        &proof
+    ));
+
+    // the transcript survives a roundtrip through a file
+    let path = std::env::temp_dir().join("bi_kzg_test_ceremony.bin");
+    ceremony.save_to_file(&path).unwrap();
+    let loaded = BiKZGCeremony::<Bn256>::load_from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.contributions.len(), 3);
+    assert_eq!(loaded.srs.powers_of_g, ceremony.srs.powers_of_g);
+    assert!(loaded.verify(&mut rng));
+
+    // tampering with a contribution or with the SRS is detected
+    let mut tampered = loaded.clone();
+    tampered.contributions[1].tau_0_h = tampered.contributions[0].tau_0_h;
+    assert!(!tampered.verify(&mut rng));
+    let mut tampered = loaded.clone();
+    tampered.contributions[2].s_1_pok.1 += Fr::ONE;
+    assert!(!tampered.verify(&mut rng));
+    let mut tampered = loaded.clone();
+    tampered.srs.powers_of_g[n + 3] = tampered.srs.powers_of_g[n + 2];
+    assert!(!tampered.verify(&mut rng));
+    let mut tampered = loaded;
+    tampered.srs.powers_of_g_lagrange_over_both_roots[1] =
+        tampered.srs.powers_of_g_lagrange_over_both_roots[2];
+    assert!(!tampered.verify(&mut rng));
+}
+
 #[test]
 fn test_tensor_product() {
     let vec1 = vec![Fr::from(1u64), Fr::from(2u64), Fr::from(3u64)];
@@ -1,5 +1,6 @@
-use halo2curves::ff::Field;
+use halo2curves::ff::{Field, PrimeField};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use sha2::{Digest, Sha256};
 
 pub(crate) fn powers_of_field_elements<F: Field>(x: &F, n: usize) -> Vec<F> {
     let mut powers = vec![F::ONE];
@@ -11,6 +12,21 @@ pub(crate) fn powers_of_field_elements<F: Field>(x: &F, n: usize) -> Vec<F> {
     powers
 }
 
+/// Derive a field element from the hash of the data.
+/// 64 bytes are reduced modulo the field order so that the result is close to uniform.
+pub(crate) fn hash_to_field<F: PrimeField>(data: &[u8]) -> F {
+    let base = F::from(256u64);
+    [0u8, 1u8]
+        .iter()
+        .flat_map(|domain| {
+            let mut hasher = Sha256::new();
+            hasher.update([*domain]);
+            hasher.update(data);
+            hasher.finalize()
+        })
+        .fold(F::ZERO, |acc, byte| acc * base + F::from(byte as u64))
+}
+
 pub(crate) fn tensor_product_parallel<F: Field>(vec1: &[F], vec2: &[F]) -> Vec<F> {
     vec2.par_iter()
         .flat_map(|&i| vec1.iter().map(|&j| i * j).collect::<Vec<_>>())
@@ -0,0 +1,191 @@
+//! Batch proving of many instances of one circuit.
+//!
+//! The instances share the wiring of the circuit, and the values of a layer are a polynomial
+//! V(x, k) over the variables x of the layer and the `log2(instance_num)` instance variables
+//! k, the instance index being the high variables. This is the data-parallel GKR: a claim on
+//! the outputs of a layer at (rz, rk) is reduced by a sumcheck over k of eq(rk, k) times the
+//! gates of one instance evaluated with V(., k), then by the sumcheck of a single instance on
+//! the values V(., rk') folded at its challenges, see `batch_gkr_prove`.
+//!
+//! The gates are never copied: the prover is linear in N |C| for N instances of a circuit C,
+//! and the verifier only evaluates the wiring of a single instance, in O(|C| + log N) per
+//! layer, besides reading the public inputs and outputs of every instance.
+//!
+//! The private inputs of all the instances are committed together, instance after instance,
+//! and the random gate coefficients are shared by all the instances.
+
+use arith::{Field, MultiLinearPoly};
+use sha2::{Digest, Sha256};
+
+use crate::{Circuit, ExpectedOutputs, GKRConfig};
+
+/// `instance_num` instances of a circuit proven together
+#[derive(Debug, Clone, Default)]
+pub struct BatchCircuit<C: GKRConfig> {
+    /// The circuit of one instance, whose wiring is shared by all the instances. Its values
+    /// are not used, they are held by the batch for every instance.
+    pub circuit: Circuit<C>,
+    pub instance_num: usize,
+    /// The inputs of every layer for every instance, `layer_vals[i][k]` for layer i and
+    /// instance k
+    pub(crate) layer_vals: Vec<Vec<Vec<C::Field>>>,
+    /// The outputs of every instance
+    pub(crate) output_vals: Vec<Vec<C::Field>>,
+}
+
+impl<C: GKRConfig> BatchCircuit<C> {
+    /// A batch of `instance_num` instances of `circuit`, a power of two
+    pub fn new(circuit: &Circuit<C>, instance_num: usize) -> Self {
+        assert!(
+            instance_num.is_power_of_two(),
+            "the number of instances must be a power of two"
+        );
+        let mut circuit = circuit.clone();
+        for layer in circuit.layers.iter_mut() {
+            layer.input_vals.evals = vec![];
+            layer.output_vals.evals = vec![];
+        }
+
+        Self {
+            circuit,
+            instance_num,
+            layer_vals: vec![],
+            output_vals: vec![],
+        }
+    }
+
+    /// Number of instance variables, the high variables of every layer
+    pub fn instance_var_num(&self) -> usize {
+        self.instance_num.trailing_zeros() as usize
+    }
+
+    /// Number of variables of the private inputs of all the instances, as committed
+    pub fn log_private_input_size(&self) -> usize {
+        self.circuit.log_private_input_size() + self.instance_var_num()
+    }
+
+    /// A digest of the circuit of an instance and of the number of instances
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"expander batch");
+        hasher.update(self.circuit.digest());
+        hasher.update((self.instance_num as u64).to_le_bytes());
+        hasher.finalize().into()
+    }
+
+    /// Set the inputs of every instance, the private inputs followed by the public ones as
+    /// in the input layer of a single instance, and evaluate every instance
+    pub fn set_inputs(&mut self, inputs: &[Vec<C::Field>]) {
+        let input_size = 1 << self.circuit.log_input_size();
+        assert_eq!(inputs.len(), self.instance_num);
+        assert!(inputs.iter().all(|vals| vals.len() == input_size));
+        self.layer_vals = vec![inputs.to_vec()];
+        self.evaluate();
+    }
+
+    /// Set only the public inputs of every instance, for the verifier. The private inputs
+    /// are set to zero and the instances are not evaluated.
+    pub fn set_public_inputs(&mut self, public_inputs: &[Vec<C::Field>]) {
+        let private_size = 1 << self.circuit.log_private_input_size();
+        let public_size = (1 << self.circuit.log_input_size()) - private_size;
+        assert_eq!(public_inputs.len(), self.instance_num);
+        assert!(public_inputs.iter().all(|vals| vals.len() == public_size));
+        let inputs = public_inputs
+            .iter()
+            .map(|vals| {
+                let mut inputs = vec![C::Field::zero(); private_size];
+                inputs.extend_from_slice(vals);
+                inputs
+            })
+            .collect();
+        self.layer_vals = vec![inputs];
+        self.output_vals = vec![];
+    }
+
+    /// Evaluate the layers of every instance from their inputs
+    pub(crate) fn evaluate(&mut self) {
+        self.layer_vals.truncate(1);
+        for layer in self.circuit.layers.iter() {
+            let outputs = self
+                .layer_vals
+                .last()
+                .unwrap()
+                .iter()
+                .map(|vals| layer.evaluate_on(vals))
+                .collect();
+            self.layer_vals.push(outputs);
+        }
+        self.output_vals = self.layer_vals.pop().unwrap();
+    }
+
+    /// The outputs of every instance, once the instances are evaluated
+    pub fn outputs(&self) -> Vec<Vec<C::Field>> {
+        self.output_vals.clone()
+    }
+
+    /// The private inputs of all the instances, instance after instance
+    pub(crate) fn private_input_vals(&self) -> Vec<C::Field> {
+        let private_size = 1 << self.circuit.log_private_input_size();
+        self.layer_vals[0]
+            .iter()
+            .flat_map(|vals| vals[..private_size].iter().cloned())
+            .collect()
+    }
+
+    /// The public inputs of all the instances, instance after instance
+    pub(crate) fn public_input_vals(&self) -> Vec<C::Field> {
+        let private_size = 1 << self.circuit.log_private_input_size();
+        self.layer_vals[0]
+            .iter()
+            .flat_map(|vals| vals[private_size..].iter().cloned())
+            .collect()
+    }
+
+    /// The contribution of the public inputs to the MLE of the input layer at r, the input
+    /// variables followed by the instance variables, see `Circuit::eval_public_inputs`
+    pub(crate) fn eval_public_inputs(&self, r: &[C::ChallengeField]) -> C::Field {
+        let private_size = 1 << self.circuit.log_private_input_size();
+        let evals = self.layer_vals[0]
+            .iter()
+            .flat_map(|vals| {
+                std::iter::repeat(C::Field::zero())
+                    .take(private_size)
+                    .chain(vals[private_size..].iter().cloned())
+            })
+            .collect::<Vec<_>>();
+        MultiLinearPoly::<C::Field>::eval_multilinear(&evals, r)
+    }
+
+    /// The outputs of all the instances, instance after instance, from the outputs expected
+    /// from every instance, or None if a number of values does not match the outputs of an
+    /// instance
+    pub fn expected_outputs(
+        &self,
+        expected_outputs: &[ExpectedOutputs<C>],
+    ) -> Option<ExpectedOutputs<C>> {
+        if expected_outputs.len() != self.instance_num {
+            return None;
+        }
+        if expected_outputs
+            .iter()
+            .all(|outputs| matches!(outputs, ExpectedOutputs::Zero))
+        {
+            return Some(ExpectedOutputs::Zero);
+        }
+
+        let output_size = 1 << self.circuit.layers.last().unwrap().output_var_num;
+        let mut values = Vec::with_capacity(output_size * self.instance_num);
+        for outputs in expected_outputs {
+            match outputs {
+                ExpectedOutputs::Zero => {
+                    values.resize(values.len() + output_size, C::Field::zero())
+                }
+                ExpectedOutputs::Values(vals) if vals.len() == output_size => {
+                    values.extend_from_slice(vals)
+                }
+                ExpectedOutputs::Values(_) => return None,
+            }
+        }
+        Some(ExpectedOutputs::Values(values))
+    }
+}
@@ -1,12 +1,13 @@
 use arith::{BinomialExtensionField, Field, FieldSerde, MultiLinearPoly};
 use ark_std::test_rng;
+use sha2::{Digest, Sha256};
 use std::{
     collections::HashMap,
     fs,
     io::{Cursor, Read},
 };
 
-use crate::{GKRConfig, Transcript};
+use crate::{FiatShamirHash, GKRConfig, Transcript};
 
 #[derive(Debug, Clone)]
 pub struct Gate<C: GKRConfig, const INPUT_NUM: usize> {
@@ -21,6 +22,40 @@ pub type GateAdd<C> = Gate<C, 1>;
 pub type GateUni<C> = Gate<C, 1>;
 pub type GateConst<C> = Gate<C, 0>;
 
+/// Type of the unary gate computing `coef * x^5`
+pub const GATE_TYPE_POW5: usize = 12345;
+/// Type of the unary gate computing `coef * x`
+pub const GATE_TYPE_POW1: usize = 12346;
+
+/// Split an index into the concatenation of the mul, add, const and uni gates into the kind
+/// of the gate and its index among the gates of this kind
+#[inline]
+fn split_gate_idx(gate_nums: [usize; 4], mut idx: usize) -> (usize, usize) {
+    for (kind, num) in gate_nums.iter().enumerate() {
+        if idx < *num {
+            return (kind, idx);
+        }
+        idx -= num;
+    }
+    panic!("gate index out of range")
+}
+
+#[inline]
+fn gate_coef_mut<'a, C: GKRConfig>(
+    mul: &'a mut [GateMul<C>],
+    add: &'a mut [GateAdd<C>],
+    const_: &'a mut [GateConst<C>],
+    uni: &'a mut [GateUni<C>],
+    idx: usize,
+) -> &'a mut C::CircuitField {
+    match split_gate_idx([mul.len(), add.len(), const_.len(), uni.len()], idx) {
+        (0, i) => &mut mul[i].coef,
+        (1, i) => &mut add[i].coef,
+        (2, i) => &mut const_[i].coef,
+        (_, i) => &mut uni[i].coef,
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct CircuitLayer<C: GKRConfig> {
     pub input_var_num: usize,
@@ -33,19 +68,62 @@ pub struct CircuitLayer<C: GKRConfig> {
     pub add: Vec<GateAdd<C>>,
     pub const_: Vec<GateConst<C>>,
     pub uni: Vec<GateUni<C>>,
+
+    /// The gates with a random coefficient, indexed in the concatenation of the mul, add,
+    /// const and uni gates. The coefficients are sampled from the transcript after the
+    /// witness commitment, they are placeholders until then.
+    pub rand_coef_idx: Vec<usize>,
+}
+
+impl<C: GKRConfig, const INPUT_NUM: usize> Gate<C, INPUT_NUM> {
+    fn absorb_into(&self, hasher: &mut Sha256) {
+        self.i_ids
+            .iter()
+            .for_each(|id| hasher.update((*id as u64).to_le_bytes()));
+        hasher.update((self.o_id as u64).to_le_bytes());
+        let mut coef = vec![];
+        self.coef.serialize_into(&mut coef);
+        hasher.update(coef);
+        hasher.update((self.gate_type as u64).to_le_bytes());
+    }
+}
+
+#[inline]
+fn absorb_gates<C: GKRConfig, const INPUT_NUM: usize>(
+    gates: &[Gate<C, INPUT_NUM>],
+    hasher: &mut Sha256,
+) {
+    hasher.update((gates.len() as u64).to_le_bytes());
+    gates.iter().for_each(|gate| gate.absorb_into(hasher));
 }
 
 impl<C: GKRConfig> CircuitLayer<C> {
+    #[inline]
+    pub fn coef_mut(&mut self, idx: usize) -> &mut C::CircuitField {
+        gate_coef_mut::<C>(
+            &mut self.mul,
+            &mut self.add,
+            &mut self.const_,
+            &mut self.uni,
+            idx,
+        )
+    }
+
     pub fn evaluate(&self) -> Vec<C::Field> {
+        self.evaluate_on(&self.input_vals.evals)
+    }
+
+    /// The outputs of the layer on the inputs `input_vals` instead of its own
+    pub(crate) fn evaluate_on(&self, input_vals: &[C::Field]) -> Vec<C::Field> {
         let mut res = vec![C::Field::zero(); 1 << self.output_var_num];
         for gate in &self.mul {
-            let i0 = &self.input_vals.evals[gate.i_ids[0]];
-            let i1 = &self.input_vals.evals[gate.i_ids[1]];
+            let i0 = &input_vals[gate.i_ids[0]];
+            let i1 = &input_vals[gate.i_ids[1]];
             let o = &mut res[gate.o_id];
             *o += C::field_mul_circuit_field(&(*i0 * i1), &gate.coef);
         }
         for gate in &self.add {
-            let i0 = &self.input_vals.evals[gate.i_ids[0]];
+            let i0 = &input_vals[gate.i_ids[0]];
             let o = &mut res[gate.o_id];
             *o += C::field_mul_circuit_field(i0, &gate.coef);
         }
@@ -54,18 +132,16 @@ impl<C: GKRConfig> CircuitLayer<C> {
             *o = C::field_add_circuit_field(o, &gate.coef);
         }
         for gate in &self.uni {
-            let i0 = &self.input_vals.evals[gate.i_ids[0]];
+            let i0 = &input_vals[gate.i_ids[0]];
             let o = &mut res[gate.o_id];
             match gate.gate_type {
-                12345 => {
-                    // pow5
+                GATE_TYPE_POW5 => {
                     let i0_2 = i0.square();
                     let i0_4 = i0_2.square();
                     let i0_5 = i0_4 * i0;
                     *o += C::field_mul_circuit_field(&i0_5, &gate.coef);
                 }
-                12346 => {
-                    // pow1
+                GATE_TYPE_POW1 => {
                     *o += C::field_mul_circuit_field(i0, &gate.coef);
                 }
                 _ => panic!("Unknown gate type: {}", gate.gate_type),
@@ -78,6 +154,9 @@ impl<C: GKRConfig> CircuitLayer<C> {
 #[derive(Debug, Clone, Default)]
 pub struct Circuit<C: GKRConfig> {
     pub layers: Vec<CircuitLayer<C>>,
+    /// The inputs of the first layer from this index on are public, the ones before it are
+    /// the private witness committed by the prover. None if all the inputs are private.
+    pub public_input_offset: Option<usize>,
 }
 
 impl<C: GKRConfig> Circuit<C> {
@@ -90,6 +169,88 @@ impl<C: GKRConfig> Circuit<C> {
         self.layers[0].input_var_num
     }
 
+    /// Declare the inputs from `offset` on as public. The offset must be a power of two,
+    /// so that the private inputs are the evaluations of a polynomial over the low variables.
+    pub fn set_public_input_offset(&mut self, offset: usize) {
+        assert!(offset.is_power_of_two() && offset <= 1 << self.log_input_size());
+        self.public_input_offset = Some(offset);
+    }
+
+    /// Number of variables of the private inputs, the part of the input layer committed
+    /// by the prover
+    pub fn log_private_input_size(&self) -> usize {
+        match self.public_input_offset {
+            Some(offset) => offset.trailing_zeros() as usize,
+            None => self.log_input_size(),
+        }
+    }
+
+    /// The point of the private inputs in a claim on the input layer at `rz`: the variables
+    /// of the private inputs, followed by the instance variables of a batch if any
+    pub(crate) fn private_input_point<F: Clone>(&self, rz: &[F]) -> Vec<F> {
+        [
+            &rz[..self.log_private_input_size()],
+            &rz[self.log_input_size()..],
+        ]
+        .concat()
+    }
+
+    pub fn private_input_vals(&self) -> &[C::Field] {
+        &self.layers[0].input_vals.evals[..1 << self.log_private_input_size()]
+    }
+
+    pub fn public_input_vals(&self) -> &[C::Field] {
+        &self.layers[0].input_vals.evals[1 << self.log_private_input_size()..]
+    }
+
+    /// The contribution of the public inputs to the MLE of the input layer at r,
+    /// i.e. the MLE of the input layer with the private inputs set to zero.
+    pub fn eval_public_inputs(&self, r: &[C::ChallengeField]) -> C::Field {
+        let mut evals = vec![C::Field::zero(); 1 << self.log_private_input_size()];
+        evals.extend_from_slice(self.public_input_vals());
+        MultiLinearPoly::<C::Field>::eval_multilinear(&evals, r)
+    }
+
+    /// A digest of the circuit description: the shape of the layers, the gates with their
+    /// coefficients and the split of the inputs. It does not depend on the input values.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"expander circuit");
+        hasher.update((self.log_private_input_size() as u64).to_le_bytes());
+        hasher.update((self.layers.len() as u64).to_le_bytes());
+        for layer in &self.layers {
+            hasher.update((layer.input_var_num as u64).to_le_bytes());
+            hasher.update((layer.output_var_num as u64).to_le_bytes());
+            absorb_gates(&layer.mul, &mut hasher);
+            absorb_gates(&layer.add, &mut hasher);
+            absorb_gates(&layer.const_, &mut hasher);
+            absorb_gates(&layer.uni, &mut hasher);
+            hasher.update((layer.rand_coef_idx.len() as u64).to_le_bytes());
+            layer
+                .rand_coef_idx
+                .iter()
+                .for_each(|idx| hasher.update((*idx as u64).to_le_bytes()));
+        }
+        hasher.finalize().into()
+    }
+
+    pub fn has_rand_coef(&self) -> bool {
+        self.layers
+            .iter()
+            .any(|layer| !layer.rand_coef_idx.is_empty())
+    }
+
+    /// Sample the random gate coefficients from the transcript, layer by layer. Both the
+    /// prover and the verifier call it right after the witness commitment.
+    pub fn fill_rand_coef<H: FiatShamirHash>(&mut self, transcript: &mut Transcript<H>) {
+        for layer in self.layers.iter_mut() {
+            for i in 0..layer.rand_coef_idx.len() {
+                let idx = layer.rand_coef_idx[i];
+                *layer.coef_mut(idx) = transcript.challenge_f::<C>().first_base_field();
+            }
+        }
+    }
+
     // Build a random mock circuit with binary inputs
     pub fn set_random_bool_input_for_test(&mut self) {
         let mut rng = test_rng();
@@ -144,6 +305,7 @@ pub struct Segment<C: GKRConfig> {
     pub gate_adds: Vec<GateAdd<C>>,
     pub gate_consts: Vec<GateConst<C>>,
     pub gate_uni: Vec<GateUni<C>>,
+    pub rand_coef_idx: Vec<usize>,
 }
 
 impl<C: GKRConfig> Circuit<C> {
@@ -161,6 +323,27 @@ impl<C: GKRConfig> Circuit<C> {
             .map(|_| C::Field::deserialize_from_ecc_format(&mut cursor))
             .collect();
     }
+
+    /// Load only the public inputs, in the same format as the witness, so that the
+    /// circuit can be verified without the private inputs, which are set to zero.
+    pub fn load_public_input_file(&mut self, filename: &str) {
+        let file_bytes = fs::read(filename).unwrap();
+        self.load_public_input_bytes(&file_bytes);
+    }
+
+    pub fn load_public_input_bytes(&mut self, file_bytes: &[u8]) {
+        let private_size = 1 << self.log_private_input_size();
+        let public_size = (1 << self.log_input_size()) - private_size;
+        log::trace!("public input file size: {} bytes", file_bytes.len());
+        log::trace!("expecting: {} bytes", 32 * public_size);
+
+        let mut cursor = Cursor::new(file_bytes);
+        self.layers[0].input_vals.evals = vec![C::Field::zero(); private_size];
+        self.layers[0]
+            .input_vals
+            .evals
+            .extend((0..public_size).map(|_| C::Field::deserialize_from_ecc_format(&mut cursor)));
+    }
 }
 impl<C: GKRConfig> Segment<C> {
     pub fn contain_gates(&self) -> bool {
@@ -170,6 +353,27 @@ impl<C: GKRConfig> Segment<C> {
             || !self.gate_uni.is_empty()
     }
 
+    #[inline]
+    fn gate_nums(&self) -> [usize; 4] {
+        [
+            self.gate_muls.len(),
+            self.gate_adds.len(),
+            self.gate_consts.len(),
+            self.gate_uni.len(),
+        ]
+    }
+
+    #[inline]
+    fn coef_mut(&mut self, idx: usize) -> &mut C::CircuitField {
+        gate_coef_mut::<C>(
+            &mut self.gate_muls,
+            &mut self.gate_adds,
+            &mut self.gate_consts,
+            &mut self.gate_uni,
+            idx,
+        )
+    }
+
     pub(crate) fn read<R: Read>(mut reader: R) -> Self {
         let i_len = u64::deserialize_from(&mut reader) as usize;
         let o_len = u64::deserialize_from(&mut reader) as usize;
@@ -184,6 +388,7 @@ impl<C: GKRConfig> Segment<C> {
             gate_adds: Vec::new(),
             gate_consts: Vec::new(),
             gate_uni: Vec::new(),
+            rand_coef_idx: Vec::new(),
         };
 
         let child_segs_num = u64::deserialize_from(&mut reader) as usize;
@@ -266,23 +471,12 @@ impl<C: GKRConfig> Segment<C> {
             gate_custom_num
         );
 
+        // the random coefficients are zero until they are sampled from the transcript
         let rand_coef_idx_num = u64::deserialize_from(&mut reader) as usize;
-        let mut t = Transcript::new(); // FIXME LATER: use an empty transcript to align the randomness
         for _ in 0..rand_coef_idx_num {
             let idx = u64::deserialize_from(&mut reader) as usize;
-
-            let rand_coef = t.challenge_f::<C>().first_base_field(); // ZZ: THIS SEEMS SUSPICIOUS
-            if idx < ret.gate_muls.len() {
-                ret.gate_muls[idx].coef = rand_coef;
-            } else if idx < ret.gate_muls.len() + ret.gate_adds.len() {
-                ret.gate_adds[idx - ret.gate_muls.len()].coef = rand_coef;
-            } else if idx < ret.gate_muls.len() + ret.gate_adds.len() + ret.gate_consts.len() {
-                ret.gate_consts[idx - ret.gate_muls.len() - ret.gate_adds.len()].coef = rand_coef;
-            } else {
-                ret.gate_uni
-                    [idx - ret.gate_muls.len() - ret.gate_adds.len() - ret.gate_consts.len()]
-                .coef = rand_coef;
-            }
+            *ret.coef_mut(idx) = C::CircuitField::zero();
+            ret.rand_coef_idx.push(idx);
         }
         ret
     }
@@ -379,10 +573,23 @@ impl<C: GKRConfig> RecursiveCircuit<C> {
                 add: vec![],
                 const_: vec![],
                 uni: vec![],
+                rand_coef_idx: vec![],
             };
+            // the random gates as their kind and index among the gates of this kind
+            let mut rand_gates = vec![];
             for (leaf_seg_id, leaf_allocs) in leaves {
                 let leaf_seg = &self.segments[leaf_seg_id];
                 for alloc in leaf_allocs {
+                    let offsets = [
+                        ret_layer.mul.len(),
+                        ret_layer.add.len(),
+                        ret_layer.const_.len(),
+                        ret_layer.uni.len(),
+                    ];
+                    rand_gates.extend(leaf_seg.rand_coef_idx.iter().map(|idx| {
+                        let (kind, i) = split_gate_idx(leaf_seg.gate_nums(), *idx);
+                        (kind, offsets[kind] + i)
+                    }));
                     for gate in &leaf_seg.gate_muls {
                         let mut gate = gate.clone();
                         gate.i_ids[0] += alloc.i_offset;
@@ -409,6 +616,16 @@ impl<C: GKRConfig> RecursiveCircuit<C> {
                     }
                 }
             }
+            let kind_offsets = [
+                0,
+                ret_layer.mul.len(),
+                ret_layer.mul.len() + ret_layer.add.len(),
+                ret_layer.mul.len() + ret_layer.add.len() + ret_layer.const_.len(),
+            ];
+            ret_layer.rand_coef_idx = rand_gates
+                .into_iter()
+                .map(|(kind, i)| kind_offsets[kind] + i)
+                .collect();
             // debug print layer
             log::trace!(
                 "layer {} mul: {} add: {} const:{} uni:{} i_var_num: {} o_var_num: {}",
@@ -1,5 +1,11 @@
 use arith::{BinomialExtensionField, Field, FieldSerde, M31Ext3, SimdField, SimdM31Ext3, M31};
 use halo2curves::bn256::Fr;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    BasefoldPCS, FiatShamirHash, HyraxPCS, MultilinearKZG, MultilinearKZGParams, OrionPCS,
+    PCSForGKR, PCSInstance, PedersenZK, ZKCommitmentForGKR,
+};
 
 #[derive(Debug, Clone, PartialEq, Default)]
 pub enum PolynomialCommitmentType {
@@ -8,6 +14,7 @@ pub enum PolynomialCommitmentType {
     KZG,
     Orion,
     FRI,
+    Hyrax,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -41,14 +48,102 @@ pub enum FiatShamirHashType {
     Keccak256,
     Poseidon,
     Animoe,
+    /// MiMC over BN254 as in gnark, see `MiMC5hasher`; the name predates the move of gnark to
+    /// the exponent 5
     MIMC7,
 }
 
+impl PolynomialCommitmentType {
+    /// Stable code of the scheme in the proof header
+    pub fn tag(&self) -> u8 {
+        match self {
+            PolynomialCommitmentType::Raw => 0,
+            PolynomialCommitmentType::KZG => 1,
+            PolynomialCommitmentType::Orion => 2,
+            PolynomialCommitmentType::FRI => 3,
+            PolynomialCommitmentType::Hyrax => 4,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(PolynomialCommitmentType::Raw),
+            1 => Some(PolynomialCommitmentType::KZG),
+            2 => Some(PolynomialCommitmentType::Orion),
+            3 => Some(PolynomialCommitmentType::FRI),
+            4 => Some(PolynomialCommitmentType::Hyrax),
+            _ => None,
+        }
+    }
+}
+
+impl FieldType {
+    /// Stable code of the field in the proof header
+    pub fn tag(&self) -> u8 {
+        match self {
+            FieldType::M31 => 0,
+            FieldType::BabyBear => 1,
+            FieldType::BN254 => 2,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(FieldType::M31),
+            1 => Some(FieldType::BabyBear),
+            2 => Some(FieldType::BN254),
+            _ => None,
+        }
+    }
+}
+
+impl GKRScheme {
+    /// Stable code of the scheme in the proof header
+    pub fn tag(&self) -> u8 {
+        match self {
+            GKRScheme::Vanilla => 0,
+            GKRScheme::GkrSquare => 1,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(GKRScheme::Vanilla),
+            1 => Some(GKRScheme::GkrSquare),
+            _ => None,
+        }
+    }
+}
+
+impl FiatShamirHashType {
+    /// Stable code of the hash in the proof header
+    pub fn tag(&self) -> u8 {
+        match self {
+            FiatShamirHashType::SHA256 => 0,
+            FiatShamirHashType::Keccak256 => 1,
+            FiatShamirHashType::Poseidon => 2,
+            FiatShamirHashType::Animoe => 3,
+            FiatShamirHashType::MIMC7 => 4,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(FiatShamirHashType::SHA256),
+            1 => Some(FiatShamirHashType::Keccak256),
+            2 => Some(FiatShamirHashType::Poseidon),
+            3 => Some(FiatShamirHashType::Animoe),
+            4 => Some(FiatShamirHashType::MIMC7),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct Config<C: GKRConfig> {
-    // Field size for the variables
+    // Bits of the challenge field, for the soundness of GKR
     pub field_size: usize,
-    // Targeted security level for the scheme
+    // Targeted security level for the scheme, see `Soundness`
     pub security_bits: usize,
     #[cfg(feature = "grinding")]
     // Grinding bits to achieve the target security level
@@ -61,6 +156,36 @@ pub struct Config<C: GKRConfig> {
     pub gkr_config: C,
     // Whether to use GKR^2
     pub gkr_scheme: GKRScheme,
+    // File with the structured reference string of the commitments with a trusted setup,
+    // i.e. KZG, written by `MultilinearKZGParams::save`
+    pub pcs_srs_path: Option<String>,
+    // Whether to hide the witness: the layers are masked and the input layer is committed
+    // with the hiding commitment of the field instead of `polynomial_commitment_type`
+    pub zk: bool,
+}
+
+impl<C: GKRConfig> Config<C> {
+    /// A fingerprint of the proof system: the field, the scheme, the commitment, the hash and
+    /// the security parameters. Proofs made under different configurations are not
+    /// interchangeable. The parameters of a trusted setup are not read here, the statement
+    /// binds their digest next to the fingerprint.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"expander config");
+        // every entry has a fixed width, the codes are the ones of the proof header
+        hasher.update([C::FIELD_TYPE.tag()]);
+        hasher.update((self.field_size as u64).to_le_bytes());
+        hasher.update((self.security_bits as u64).to_le_bytes());
+        #[cfg(feature = "grinding")]
+        hasher.update((self.grinding_bits as u64).to_le_bytes());
+        hasher.update([
+            self.polynomial_commitment_type.tag(),
+            self.fs_hash.tag(),
+            self.gkr_scheme.tag(),
+            self.zk as u8,
+        ]);
+        hasher.finalize().into()
+    }
 }
 
 impl Config<M31ExtConfig> {
@@ -74,6 +199,8 @@ impl Config<M31ExtConfig> {
             fs_hash: FiatShamirHashType::SHA256,
             gkr_config: M31ExtConfig,
             gkr_scheme,
+            pcs_srs_path: None,
+            zk: false,
         }
     }
 }
@@ -81,7 +208,7 @@ impl Config<M31ExtConfig> {
 impl Config<BN254Config> {
     pub fn new(gkr_scheme: GKRScheme) -> Self {
         Config {
-            field_size: 93,
+            field_size: 254,
             security_bits: 100,
             #[cfg(feature = "grinding")]
             grinding_bits: 10,
@@ -89,6 +216,8 @@ impl Config<BN254Config> {
             fs_hash: FiatShamirHashType::SHA256,
             gkr_config: BN254Config,
             gkr_scheme,
+            pcs_srs_path: None,
+            zk: false,
         }
     }
 }
@@ -117,6 +246,26 @@ pub trait GKRConfig: Default + Clone + Send + Sync + 'static {
 
     /// API to allow for addition between the main field and the circuit field
     fn field_add_circuit_field(a: &Self::Field, b: &Self::CircuitField) -> Self::Field;
+
+    /// Instantiate a polynomial commitment scheme specific to this configuration,
+    /// returns None if the scheme is not supported for the field.
+    /// Raw is handled for all configurations by `new_pcs_for_gkr`.
+    fn new_pcs<H: FiatShamirHash>(
+        _config: &Config<Self>,
+        _num_vars: usize,
+    ) -> Option<Box<dyn PCSForGKR<Self, H>>> {
+        None
+    }
+
+    /// Instantiate the hiding commitment used by the zero-knowledge mode with the polynomial
+    /// commitment of the configuration, for vectors of at most `max_len` entries, returns
+    /// None if the field has no such commitment.
+    fn new_zk_commitment<H: FiatShamirHash>(
+        _config: &Config<Self>,
+        _max_len: usize,
+    ) -> Option<Box<dyn ZKCommitmentForGKR<Self, H>>> {
+        None
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -152,6 +301,24 @@ impl GKRConfig for M31ExtConfig {
         // skipping the conversion M31 -> M31Ext3
         *a + *b
     }
+
+    fn new_pcs<H: FiatShamirHash>(
+        config: &Config<Self>,
+        num_vars: usize,
+    ) -> Option<Box<dyn PCSForGKR<Self, H>>> {
+        match config.polynomial_commitment_type {
+            PolynomialCommitmentType::Orion => Some(Box::new(
+                PCSInstance::<Self, OrionPCS>::new_with_security_bits(
+                    num_vars,
+                    config.security_bits,
+                ),
+            )),
+            PolynomialCommitmentType::FRI => {
+                Some(Box::new(PCSInstance::<Self, BasefoldPCS>::new(num_vars)))
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -183,4 +350,36 @@ impl GKRConfig for BN254Config {
     fn field_add_circuit_field(a: &Self::Field, b: &Self::CircuitField) -> Self::Field {
         *a + *b
     }
+
+    fn new_pcs<H: FiatShamirHash>(
+        config: &Config<Self>,
+        num_vars: usize,
+    ) -> Option<Box<dyn PCSForGKR<Self, H>>> {
+        match config.polynomial_commitment_type {
+            PolynomialCommitmentType::KZG => {
+                let path = config
+                    .pcs_srs_path
+                    .as_ref()
+                    .expect("KZG needs the parameters of a trusted setup in pcs_srs_path");
+                let params = MultilinearKZGParams::load_trimmed(path, num_vars);
+                Some(Box::new(
+                    PCSInstance::<Self, MultilinearKZG>::new_with_shared_params(params),
+                ))
+            }
+            PolynomialCommitmentType::Hyrax => {
+                Some(Box::new(PCSInstance::<Self, HyraxPCS>::new(num_vars)))
+            }
+            _ => None,
+        }
+    }
+
+    fn new_zk_commitment<H: FiatShamirHash>(
+        config: &Config<Self>,
+        max_len: usize,
+    ) -> Option<Box<dyn ZKCommitmentForGKR<Self, H>>> {
+        match config.polynomial_commitment_type {
+            PolynomialCommitmentType::Hyrax => Some(Box::new(PedersenZK::new(max_len))),
+            _ => None,
+        }
+    }
 }
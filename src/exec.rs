@@ -7,28 +7,43 @@ use std::{
 
 use arith::{Field, FieldSerde};
 use expander_rs::{
-    BN254Config, Circuit, Config, FieldType, GKRConfig, GKRScheme, M31ExtConfig, Proof, Prover,
-    Verifier, SENTINEL_BN254, SENTINEL_M31,
+    BN254Config, Circuit, Config, ExpectedOutputs, FieldType, GKRConfig, GKRScheme, M31ExtConfig,
+    Proof, ProofFormatError, Prover, Verifier, SENTINEL_BN254, SENTINEL_M31,
 };
 use log::{debug, info};
 use warp::Filter;
 
-fn dump_proof_and_claimed_v<F: Field + FieldSerde>(proof: &Proof, claimed_v: &F) -> Vec<u8> {
+fn dump_proof_and_claimed_v<C: GKRConfig>(
+    circuit: &Circuit<C>,
+    config: &Config<C>,
+    proof: &Proof,
+    claimed_v: &C::Field,
+) -> Vec<u8> {
     let mut bytes = Vec::new();
 
-    proof.serialize_into(&mut bytes);
+    proof
+        .serialize_with_header(circuit, config, &mut bytes)
+        .expect("the proof does not follow the layout of the circuit");
     claimed_v.serialize_into(&mut bytes);
 
     bytes
 }
 
-fn load_proof_and_claimed_v<F: Field + FieldSerde>(bytes: &[u8]) -> (Proof, F) {
+fn load_proof_and_claimed_v<C: GKRConfig>(
+    circuit: &Circuit<C>,
+    config: &Config<C>,
+    bytes: &[u8],
+) -> Result<(Proof, C::Field), ProofFormatError> {
     let mut cursor = Cursor::new(bytes);
 
-    let proof = Proof::deserialize_from(&mut cursor);
-    let claimed_v = F::deserialize_from(&mut cursor);
+    let proof = Proof::deserialize_with_header(circuit, config, &mut cursor)?;
+    if bytes.len() - (cursor.position() as usize) < C::Field::SIZE {
+        return Err(ProofFormatError::Truncated("claimed output"));
+    }
+    let claimed_v = C::Field::try_deserialize_from(&mut cursor)
+        .ok_or(ProofFormatError::NonCanonical("claimed output"))?;
 
-    (proof, claimed_v)
+    Ok((proof, claimed_v))
 }
 
 fn detect_field_type_from_circuit_file(circuit_file: &str) -> FieldType {
@@ -56,23 +71,41 @@ async fn run_command<C: GKRConfig>(
             let witness_file = &args[3];
             let output_file = &args[4];
             let mut circuit = Circuit::<C>::load_circuit(circuit_file);
+            if let Some(offset) = args.get(5) {
+                circuit.set_public_input_offset(offset.parse().unwrap());
+            }
             circuit.load_witness_file(witness_file);
             circuit.evaluate();
             let mut prover = Prover::new(&config);
             prover.prepare_mem(&circuit);
             let (claimed_v, proof) = prover.prove(&circuit);
-            let bytes = dump_proof_and_claimed_v(&proof, &claimed_v);
+            let bytes = dump_proof_and_claimed_v(&circuit, &config, &proof, &claimed_v);
             fs::write(output_file, bytes).expect("Unable to write proof to file.");
         }
         "verify" => {
             let witness_file = &args[3];
             let output_file = &args[4];
             let mut circuit = Circuit::<C>::load_circuit(circuit_file);
-            circuit.load_witness_file(witness_file);
+            // with a public input offset, only the public inputs are needed
+            match args.get(5) {
+                Some(offset) => {
+                    circuit.set_public_input_offset(offset.parse().unwrap());
+                    circuit.load_public_input_file(witness_file);
+                }
+                None => circuit.load_witness_file(witness_file),
+            }
             let bytes = fs::read(output_file).expect("Unable to read proof from file.");
-            let (proof, claimed_v) = load_proof_and_claimed_v(&bytes);
+            // the claimed output in the proof file is not trusted, the circuit is expected
+            // to output zeros
+            let (proof, _claimed_v) = match load_proof_and_claimed_v(&circuit, &config, &bytes) {
+                Ok(proof_and_claimed_v) => proof_and_claimed_v,
+                Err(e) => {
+                    println!("Invalid proof file: {}", e);
+                    exit(1);
+                }
+            };
             let verifier = Verifier::new(&config);
-            assert!(verifier.verify(&circuit, &claimed_v, &proof));
+            assert!(verifier.verify_with_outputs(&circuit, &ExpectedOutputs::Zero, &proof));
             println!("success");
         }
         "serve" => {
@@ -91,6 +124,7 @@ async fn run_command<C: GKRConfig>(
             let circuit_clone_for_verifier = circuit.clone();
             let prover = Arc::new(Mutex::new(prover));
             let verifier = Arc::new(Mutex::new(verifier));
+            let verifier_config = config.clone();
 
             let prove =
                 warp::path("prove")
@@ -103,7 +137,7 @@ async fn run_command<C: GKRConfig>(
                         circuit.load_witness_bytes(&witness_bytes);
                         circuit.evaluate();
                         let (claimed_v, proof) = prover.prove(&circuit);
-                        dump_proof_and_claimed_v(&proof, &claimed_v)
+                        dump_proof_and_claimed_v(&circuit, &config, &proof, &claimed_v)
                     });
             let verify =
                 warp::path("verify")
@@ -125,8 +159,20 @@ async fn run_command<C: GKRConfig>(
                         let mut circuit = circuit_clone_for_verifier.lock().unwrap();
                         let verifier = verifier.lock().unwrap();
                         circuit.load_witness_bytes(witness_bytes);
-                        let (proof, claimed_v) = load_proof_and_claimed_v(proof_bytes);
-                        if verifier.verify(&circuit, &claimed_v, &proof) {
+                        let verified =
+                            match load_proof_and_claimed_v(&circuit, &verifier_config, proof_bytes)
+                            {
+                                Ok((proof, _claimed_v)) => verifier.verify_with_outputs(
+                                    &circuit,
+                                    &ExpectedOutputs::Zero,
+                                    &proof,
+                                ),
+                                Err(e) => {
+                                    debug!("Invalid proof: {}", e);
+                                    false
+                                }
+                            };
+                        if verified {
                             "success".to_string()
                         } else {
                             "failure".to_string()
@@ -145,18 +191,22 @@ async fn run_command<C: GKRConfig>(
 #[tokio::main]
 async fn main() {
     // examples:
-    // expander-exec prove <input:circuit_file> <input:witness_file> <output:proof>
+    // expander-exec prove <input:circuit_file> <input:witness_file> <output:proof> [<input:public_input_offset>]
     // expander-exec verify <input:circuit_file> <input:witness_file> <input:proof>
+    // expander-exec verify <input:circuit_file> <input:public_input_file> <input:proof> <input:public_input_offset>
     // expander-exec serve <input:circuit_file> <input:ip> <input:port>
     env_logger::init();
     let args = std::env::args().collect::<Vec<String>>();
     if args.len() < 4 {
         println!(
-            "Usage: expander-exec prove <input:circuit_file> <input:witness_file> <output:proof>"
+            "Usage: expander-exec prove <input:circuit_file> <input:witness_file> <output:proof> [<input:public_input_offset>]"
         );
         println!(
             "Usage: expander-exec verify <input:circuit_file> <input:witness_file> <input:proof>"
         );
+        println!(
+            "Usage: expander-exec verify <input:circuit_file> <input:public_input_file> <input:proof> <input:public_input_offset>"
+        );
         println!("Usage: expander-exec serve <input:circuit_file> <input:host> <input:port>");
         return;
     }
@@ -1,2 +1,8 @@
 pub mod hashes;
 pub use self::hashes::*;
+
+pub mod poseidon;
+pub use self::poseidon::*;
+
+pub mod bn254;
+pub use self::bn254::*;
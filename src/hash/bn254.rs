@@ -0,0 +1,283 @@
+//! Algebraic hashes over the BN254 scalar field for the Fiat-Shamir transcript, cheap to
+//! recompute in a verifier circuit. `MiMC5hasher` follows the MiMC of gnark-crypto and
+//! `Anemoihasher` the Anemoi reference sponge, see their docs for the exact versions.
+//!
+//! The input of these hashes is a sequence of field elements, each serialized as 32 bytes in
+//! little endian, and the digest is a single field element. Field elements over BN254 are
+//! absorbed as they are, and anything else as bytes packed 31 per element after the length.
+
+use arith::{Field, FieldSerde};
+use halo2curves::{bn256::Fr, ff::PrimeField};
+use sha3::{Digest, Keccak256};
+
+use crate::{FiatShamirHash, FiatShamirHashType, FieldType, GKRConfig};
+
+const FR_BYTES: usize = 32;
+// the number of bytes always below the modulus
+const FR_PACKED_BYTES: usize = 31;
+
+#[inline]
+fn read_frs(input: &[u8]) -> Vec<Fr> {
+    assert_eq!(
+        input.len() % FR_BYTES,
+        0,
+        "the input is not a sequence of elements"
+    );
+    input
+        .chunks(FR_BYTES)
+        .map(|bytes| Fr::from_uniform_bytes(bytes.try_into().unwrap()))
+        .collect()
+}
+
+#[inline]
+fn write_fr(output: &mut [u8], f: &Fr) {
+    f.serialize_into(&mut output[..FR_BYTES]);
+}
+
+#[inline]
+fn absorb_bytes_as_frs(input: &mut Vec<u8>, bytes: &[u8]) {
+    Fr::from(bytes.len() as u32).serialize_into(&mut *input);
+    bytes.chunks(FR_PACKED_BYTES).for_each(|chunk| {
+        let mut element = [0u8; FR_BYTES];
+        element[..chunk.len()].copy_from_slice(chunk);
+        input.extend_from_slice(&element);
+    });
+}
+
+#[inline]
+fn absorb_f_as_frs<C: GKRConfig>(input: &mut Vec<u8>, f: &C::Field) {
+    let mut bytes = vec![];
+    f.serialize_into(&mut bytes);
+    if C::FIELD_TYPE == FieldType::BN254 {
+        // the serialization is already a canonical element
+        input.extend_from_slice(&bytes);
+    } else {
+        absorb_bytes_as_frs(input, &bytes);
+    }
+}
+
+#[inline]
+fn squeeze_f_from_fr<C: GKRConfig>(digest: &[u8; 32]) -> C::ChallengeField {
+    if C::FIELD_TYPE == FieldType::BN254 {
+        // the digest is an element, it is the challenge itself
+        C::ChallengeField::deserialize_from(&digest[..])
+    } else {
+        C::ChallengeField::from_uniform_bytes(digest)
+    }
+}
+
+/// The exponent e with x^(alpha e) = x, i.e. the inverse of alpha modulo r - 1
+fn inverse_exponent(alpha: u64) -> [u64; 4] {
+    let r_minus_one = (-Fr::one()).to_repr();
+    let r_minus_one: [u64; 4] = std::array::from_fn(|i| {
+        u64::from_le_bytes(r_minus_one.as_ref()[i * 8..i * 8 + 8].try_into().unwrap())
+    });
+
+    // find the k such that alpha divides k (r - 1) + 1, the exponent is the quotient
+    for k in 1..alpha {
+        let mut n = [0u64; 5];
+        let mut carry = 1u128;
+        for (n, limb) in n.iter_mut().zip(r_minus_one.iter()) {
+            let v = *limb as u128 * k as u128 + carry;
+            *n = v as u64;
+            carry = v >> 64;
+        }
+        n[4] = carry as u64;
+
+        let mut quotient = [0u64; 5];
+        let mut rem = 0u128;
+        for (q, limb) in quotient.iter_mut().zip(n.iter()).rev() {
+            let v = (rem << 64) | *limb as u128;
+            *q = (v / alpha as u128) as u64;
+            rem = v % alpha as u128;
+        }
+        if rem == 0 {
+            return quotient[..4].try_into().unwrap();
+        }
+    }
+    panic!("x^{} is not a permutation of the field", alpha)
+}
+
+/// MiMC with the exponent 5 and 110 rounds, keyed by the running hash in the
+/// Miyaguchi-Preneel mode. The round constants are the iterated Keccak256 of "seed", read
+/// in big endian. This is `ecc/bn254/fr/mimc` of gnark-crypto since v0.10.0, and the
+/// `std/hash/mimc` of the gnark versions built on it, which the verifier circuits use.
+/// Only the hash of field elements, `hash_frs`, is shared: gnark reads the bytes of a block
+/// in big endian.
+#[derive(Debug, Clone)]
+pub struct MiMC5hasher {
+    pub constants: Vec<Fr>,
+}
+
+impl MiMC5hasher {
+    pub const NUM_ROUNDS: usize = 110;
+    const SEED: &'static [u8] = b"seed";
+
+    /// Encrypt the message with the key
+    pub fn encrypt(&self, message: &Fr, key: &Fr) -> Fr {
+        let mut m = *message;
+        for c in self.constants.iter() {
+            let t = m + key + c;
+            m = t.square().square() * t;
+        }
+        m + key
+    }
+
+    pub fn hash_frs(&self, inputs: &[Fr]) -> Fr {
+        inputs
+            .iter()
+            .fold(Fr::zero(), |h, x| self.encrypt(x, &h) + h + x)
+    }
+}
+
+impl Default for MiMC5hasher {
+    fn default() -> Self {
+        let mut rnd = Keccak256::digest(Self::SEED).to_vec();
+        let constants = (0..Self::NUM_ROUNDS)
+            .map(|_| {
+                rnd = Keccak256::digest(&rnd).to_vec();
+                let mut le_bytes = rnd.clone();
+                le_bytes.reverse();
+                Fr::from_uniform_bytes(&le_bytes.try_into().unwrap())
+            })
+            .collect();
+        Self { constants }
+    }
+}
+
+impl FiatShamirHash for MiMC5hasher {
+    const HASH_TYPE: FiatShamirHashType = FiatShamirHashType::MIMC7;
+
+    fn hash(&self, output: &mut [u8], input: &[u8], input_len: usize) {
+        write_fr(output, &self.hash_frs(&read_frs(&input[..input_len])));
+    }
+
+    fn hash_inplace(&self, buffer: &mut [u8], input_len: usize) {
+        let digest = self.hash_frs(&read_frs(&buffer[..input_len]));
+        write_fr(buffer, &digest);
+    }
+
+    fn absorb_bytes(&self, input: &mut Vec<u8>, bytes: &[u8]) {
+        absorb_bytes_as_frs(input, bytes);
+    }
+
+    fn absorb_f<C: GKRConfig>(&self, input: &mut Vec<u8>, f: &C::Field) {
+        absorb_f_as_frs::<C>(input, f);
+    }
+
+    fn squeeze_f<C: GKRConfig>(&self, digest: &[u8; 32]) -> C::ChallengeField {
+        squeeze_f_from_fr::<C>(digest)
+    }
+}
+
+/// The Anemoi permutation on two elements (x, y) with the open Flystel of exponent 5,
+/// g = 5 and 21 rounds, used as a sponge of rate one on x. The round constants are
+/// derived from the digits of pi as in the reference implementation of the paper
+/// (Bouvier et al., CRYPTO 2023) for a single column. Neither gnark nor gnark-crypto has
+/// Anemoi, so there is no gnark counterpart to match: a verifier circuit has to implement
+/// this construction itself.
+#[derive(Debug, Clone)]
+pub struct Anemoihasher {
+    pub c: Vec<Fr>,
+    pub d: Vec<Fr>,
+    alpha_inv: [u64; 4],
+}
+
+impl Anemoihasher {
+    pub const NUM_ROUNDS: usize = 21;
+    pub const ALPHA: u64 = 5;
+    const PI_0: u64 = 1415926535;
+
+    #[inline]
+    fn g() -> Fr {
+        Fr::from(5u32)
+    }
+
+    #[inline]
+    fn pow_alpha(x: &Fr) -> Fr {
+        let x2 = x.square();
+        x2.square() * x
+    }
+
+    #[inline]
+    fn linear_layer(x: &mut Fr, y: &mut Fr) {
+        // pseudo-Hadamard transform
+        *y += *x;
+        *x += *y;
+    }
+
+    #[inline]
+    fn flystel(&self, x: &mut Fr, y: &mut Fr) {
+        let g = Self::g();
+        *x -= g * y.square() + g.inv().unwrap();
+        *y -= halo2curves::ff::Field::pow_vartime(x, self.alpha_inv);
+        *x += g * y.square();
+    }
+
+    pub fn permute(&self, x: &mut Fr, y: &mut Fr) {
+        for (c, d) in self.c.iter().zip(self.d.iter()) {
+            *x += c;
+            *y += d;
+            Self::linear_layer(x, y);
+            self.flystel(x, y);
+        }
+        Self::linear_layer(x, y);
+    }
+
+    pub fn hash_frs(&self, inputs: &[Fr]) -> Fr {
+        let (mut x, mut y) = (Fr::zero(), Fr::zero());
+        inputs.iter().for_each(|m| {
+            x += m;
+            self.permute(&mut x, &mut y);
+        });
+        x
+    }
+}
+
+impl Default for Anemoihasher {
+    fn default() -> Self {
+        let g = Self::g();
+        let pi_0 = Fr::from(Self::PI_0);
+        let (c, d) = (0..Self::NUM_ROUNDS)
+            .map(|r| {
+                let pi_0_r = halo2curves::ff::Field::pow_vartime(&pi_0, [r as u64]);
+                // with a single column the powers of the second constant of pi are all one
+                let pow_alpha = Self::pow_alpha(&(pi_0_r + Fr::one()));
+                (
+                    g * pi_0_r.square() + pow_alpha,
+                    g + pow_alpha + g.inv().unwrap(),
+                )
+            })
+            .unzip();
+        Self {
+            c,
+            d,
+            alpha_inv: inverse_exponent(Self::ALPHA),
+        }
+    }
+}
+
+impl FiatShamirHash for Anemoihasher {
+    const HASH_TYPE: FiatShamirHashType = FiatShamirHashType::Animoe;
+
+    fn hash(&self, output: &mut [u8], input: &[u8], input_len: usize) {
+        write_fr(output, &self.hash_frs(&read_frs(&input[..input_len])));
+    }
+
+    fn hash_inplace(&self, buffer: &mut [u8], input_len: usize) {
+        let digest = self.hash_frs(&read_frs(&buffer[..input_len]));
+        write_fr(buffer, &digest);
+    }
+
+    fn absorb_bytes(&self, input: &mut Vec<u8>, bytes: &[u8]) {
+        absorb_bytes_as_frs(input, bytes);
+    }
+
+    fn absorb_f<C: GKRConfig>(&self, input: &mut Vec<u8>, f: &C::Field) {
+        absorb_f_as_frs::<C>(input, f);
+    }
+
+    fn squeeze_f<C: GKRConfig>(&self, digest: &[u8; 32]) -> C::ChallengeField {
+        squeeze_f_from_fr::<C>(digest)
+    }
+}
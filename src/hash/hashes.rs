@@ -1,15 +1,75 @@
+use std::fmt::Debug;
+
+use arith::{Field, FieldSerde};
 use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+use crate::{FiatShamirHashType, GKRConfig};
+
+/// A hash function for the Fiat-Shamir transcript, from bytes to a digest of 32 bytes.
+/// The messages of the transcript are encoded into the input of the hash with `absorb_bytes`
+/// and `absorb_f`, and the challenges are read from the digest with `squeeze_f`. Hashes over
+/// bytes keep the defaults, hashes over a field override them to work on field elements.
+pub trait FiatShamirHash: Debug + Clone + Default + Send + Sync + 'static {
+    /// The value of `Config::fs_hash` selecting this hash
+    const HASH_TYPE: FiatShamirHashType;
+
+    /// Hash the first `input_len` bytes of `input` into `output`
+    fn hash(&self, output: &mut [u8], input: &[u8], input_len: usize);
+
+    /// Hash the first `input_len` bytes of `buffer` into the first 32 bytes of `buffer`
+    fn hash_inplace(&self, buffer: &mut [u8], input_len: usize);
+
+    /// Encode raw bytes appended to the transcript into the input of the next hash
+    #[inline]
+    fn absorb_bytes(&self, input: &mut Vec<u8>, bytes: &[u8]) {
+        input.extend_from_slice(bytes);
+    }
+
+    /// Encode a field element appended to the transcript into the input of the next hash
+    #[inline]
+    fn absorb_f<C: GKRConfig>(&self, input: &mut Vec<u8>, f: &C::Field) {
+        f.serialize_into(input);
+    }
+
+    /// Read a challenge from a digest
+    #[inline]
+    fn squeeze_f<C: GKRConfig>(&self, digest: &[u8; 32]) -> C::ChallengeField {
+        C::ChallengeField::from_uniform_bytes(digest)
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct SHA256hasher;
 
-impl SHA256hasher {
-    pub fn hash(&self, output: &mut [u8], input: &[u8], input_len: usize) {
+impl FiatShamirHash for SHA256hasher {
+    const HASH_TYPE: FiatShamirHashType = FiatShamirHashType::SHA256;
+
+    fn hash(&self, output: &mut [u8], input: &[u8], input_len: usize) {
         let hashed = Sha256::digest(&input[..input_len]);
         output.copy_from_slice(&hashed[..]);
     }
 
-    pub fn hash_inplace(&self, buffer: &mut [u8], input_len: usize) {
+    fn hash_inplace(&self, buffer: &mut [u8], input_len: usize) {
         let hashed = Sha256::digest(&buffer[..input_len]);
-        buffer.copy_from_slice(&hashed[..]);
+        buffer[..hashed.len()].copy_from_slice(&hashed[..]);
+    }
+}
+
+/// Keccak256 as in the EVM, for transcripts verified on chain
+#[derive(Debug, Clone, Default)]
+pub struct Keccak256hasher;
+
+impl FiatShamirHash for Keccak256hasher {
+    const HASH_TYPE: FiatShamirHashType = FiatShamirHashType::Keccak256;
+
+    fn hash(&self, output: &mut [u8], input: &[u8], input_len: usize) {
+        let hashed = Keccak256::digest(&input[..input_len]);
+        output.copy_from_slice(&hashed[..]);
+    }
+
+    fn hash_inplace(&self, buffer: &mut [u8], input_len: usize) {
+        let hashed = Keccak256::digest(&buffer[..input_len]);
+        buffer[..hashed.len()].copy_from_slice(&hashed[..]);
     }
 }
@@ -0,0 +1,243 @@
+//! Poseidon2 over M31 for the Fiat-Shamir transcript, so that a verifier circuit over M31 can
+//! recompute the challenges cheaply.
+//!
+//! The permutation is the Poseidon2 instance of Plonky3 for Mersenne31 with width 16: the
+//! S-box x^5, 8 external and 14 internal rounds, the external layer built from the 4x4
+//! circulant matrix circ(2, 3, 1, 1), and the internal layer 1 + diag(V) with
+//! V = [-2, 2^0, 2^1, 2^2, 2^3, 2^4, 2^5, 2^6, 2^7, 2^8, 2^10, 2^12, 2^13, 2^14, 2^15, 2^16].
+//! The round constants are drawn as by `Poseidon2Mersenne31::new_from_rng_128` from
+//! `Xoroshiro128Plus::seed_from_u64(1)`. The sponge has rate 8 and capacity 8, and a digest
+//! is the 8 words of the rate part of the state, serialized in little endian.
+
+use arith::{Field, FieldSerde, M31, M31_MOD};
+use rand::{RngCore, SeedableRng};
+use rand_xoshiro::Xoroshiro128Plus;
+
+use crate::{FiatShamirHash, FiatShamirHashType, FieldType, GKRConfig};
+
+pub const POSEIDON_M31_WIDTH: usize = 16;
+pub const POSEIDON_M31_RATE: usize = 8;
+pub const POSEIDON_M31_EXTERNAL_ROUNDS: usize = 8;
+pub const POSEIDON_M31_INTERNAL_ROUNDS: usize = 14;
+
+/// The seed of the round constants in Plonky3
+const SEED: u64 = 1;
+
+/// The exponents of the powers of 2 of the internal diagonal after its first entry -2
+const INTERNAL_DIAG_SHIFTS: [u32; POSEIDON_M31_WIDTH - 1] =
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 10, 12, 13, 14, 15, 16];
+
+#[inline]
+fn canonical_u32(x: &M31) -> u32 {
+    let mut bytes = [0u8; 4];
+    x.serialize_into(&mut bytes[..]);
+    let v = u32::from_le_bytes(bytes);
+    if v == M31_MOD {
+        0
+    } else {
+        v
+    }
+}
+
+/// A uniform M31 element, from the top 31 bits of the next u32 rejecting p, as Plonky3 does
+#[inline]
+fn sample_m31(rng: &mut impl RngCore) -> M31 {
+    loop {
+        let v = rng.next_u32() >> 1;
+        if v != M31_MOD {
+            return M31::from(v);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoseidonM31Params {
+    /// the constants of the 4 initial then the 4 terminal external rounds
+    pub external_round_constants: Vec<[M31; POSEIDON_M31_WIDTH]>,
+    /// the constants of the internal rounds, added to the first word only
+    pub internal_round_constants: Vec<M31>,
+}
+
+impl Default for PoseidonM31Params {
+    fn default() -> Self {
+        let mut rng = Xoroshiro128Plus::seed_from_u64(SEED);
+        let external_round_constants = (0..POSEIDON_M31_EXTERNAL_ROUNDS)
+            .map(|_| {
+                let mut constants = [M31::zero(); POSEIDON_M31_WIDTH];
+                constants.iter_mut().for_each(|c| *c = sample_m31(&mut rng));
+                constants
+            })
+            .collect();
+        let internal_round_constants = (0..POSEIDON_M31_INTERNAL_ROUNDS)
+            .map(|_| sample_m31(&mut rng))
+            .collect();
+
+        Self {
+            external_round_constants,
+            internal_round_constants,
+        }
+    }
+}
+
+impl PoseidonM31Params {
+    #[inline]
+    fn sbox(x: &mut M31) {
+        let x2 = x.square();
+        *x *= x2.square();
+    }
+
+    /// circ(2, 3, 1, 1) on 4 words
+    #[inline]
+    fn apply_mat4(x: &mut [M31]) {
+        let t01 = x[0] + x[1];
+        let t23 = x[2] + x[3];
+        let t0123 = t01 + t23;
+        let t01123 = t0123 + x[1];
+        let t01233 = t0123 + x[3];
+        x[3] = t01233 + x[0].double();
+        x[1] = t01123 + x[2].double();
+        x[0] = t01123 + t01;
+        x[2] = t01233 + t23;
+    }
+
+    /// circ(2 M4, M4, M4, M4): M4 on each block of 4 words, then the sums of the words at the
+    /// same position of all the blocks added to each of them
+    #[inline]
+    fn external_layer(state: &mut [M31; POSEIDON_M31_WIDTH]) {
+        state.chunks_exact_mut(4).for_each(Self::apply_mat4);
+        let sums: [M31; 4] = std::array::from_fn(|k| {
+            state
+                .iter()
+                .skip(k)
+                .step_by(4)
+                .fold(M31::zero(), |acc, s| acc + s)
+        });
+        state
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, s)| *s += sums[i % 4]);
+    }
+
+    /// 1 + diag(V): each word times its entry of V, plus the sum of all the words
+    #[inline]
+    fn internal_layer(state: &mut [M31; POSEIDON_M31_WIDTH]) {
+        let sum = state.iter().fold(M31::zero(), |acc, s| acc + s);
+        state[0] = sum - state[0].double();
+        state[1..]
+            .iter_mut()
+            .zip(INTERNAL_DIAG_SHIFTS.iter())
+            .for_each(|(s, shift)| *s = *s * M31::from(1u32 << shift) + sum);
+    }
+
+    #[inline]
+    fn external_round(state: &mut [M31; POSEIDON_M31_WIDTH], constants: &[M31]) {
+        state.iter_mut().zip(constants.iter()).for_each(|(s, c)| {
+            *s += c;
+            Self::sbox(s);
+        });
+        Self::external_layer(state);
+    }
+
+    pub fn permute(&self, state: &mut [M31; POSEIDON_M31_WIDTH]) {
+        let (initial, terminal) = self
+            .external_round_constants
+            .split_at(POSEIDON_M31_EXTERNAL_ROUNDS / 2);
+
+        Self::external_layer(state);
+        initial.iter().for_each(|c| Self::external_round(state, c));
+        self.internal_round_constants.iter().for_each(|c| {
+            state[0] += c;
+            Self::sbox(&mut state[0]);
+            Self::internal_layer(state);
+        });
+        terminal.iter().for_each(|c| Self::external_round(state, c));
+    }
+
+    /// Sponge over the words, padded with a one and then zeros to a multiple of the rate
+    pub fn hash(&self, words: &[M31]) -> [M31; POSEIDON_M31_RATE] {
+        let mut padded = words.to_vec();
+        padded.push(M31::one());
+        padded.resize(
+            padded.len().next_multiple_of(POSEIDON_M31_RATE),
+            M31::zero(),
+        );
+
+        let mut state = [M31::zero(); POSEIDON_M31_WIDTH];
+        for chunk in padded.chunks(POSEIDON_M31_RATE) {
+            state
+                .iter_mut()
+                .zip(chunk.iter())
+                .for_each(|(s, w)| *s += w);
+            self.permute(&mut state);
+        }
+        state[..POSEIDON_M31_RATE].try_into().unwrap()
+    }
+}
+
+/// Poseidon2 over M31 as a transcript hash. The input of the hash is a sequence of M31
+/// words, each serialized as 4 bytes in little endian. Field elements over M31 are absorbed
+/// as their words, and anything else as bytes packed 3 per word after the length.
+#[derive(Debug, Clone, Default)]
+pub struct PoseidonM31hasher {
+    pub params: PoseidonM31Params,
+}
+
+impl PoseidonM31hasher {
+    fn hash_words(&self, output: &mut [u8], input: &[u8]) {
+        assert_eq!(input.len() % 4, 0, "the input is not a sequence of words");
+        let words = input
+            .chunks(4)
+            .map(|bytes| M31::from(u32::from_le_bytes(bytes.try_into().unwrap())))
+            .collect::<Vec<_>>();
+        let digest = self.params.hash(&words);
+        output
+            .chunks_mut(4)
+            .zip(digest.iter())
+            .for_each(|(bytes, w)| bytes.copy_from_slice(&canonical_u32(w).to_le_bytes()));
+    }
+}
+
+impl FiatShamirHash for PoseidonM31hasher {
+    const HASH_TYPE: FiatShamirHashType = FiatShamirHashType::Poseidon;
+
+    fn hash(&self, output: &mut [u8], input: &[u8], input_len: usize) {
+        self.hash_words(output, &input[..input_len]);
+    }
+
+    fn hash_inplace(&self, buffer: &mut [u8], input_len: usize) {
+        let input = buffer[..input_len].to_vec();
+        self.hash_words(buffer, &input);
+    }
+
+    fn absorb_bytes(&self, input: &mut Vec<u8>, bytes: &[u8]) {
+        input.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        bytes.chunks(3).for_each(|chunk| {
+            let mut word = [0u8; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            input.extend_from_slice(&word);
+        });
+    }
+
+    fn absorb_f<C: GKRConfig>(&self, input: &mut Vec<u8>, f: &C::Field) {
+        let mut bytes = vec![];
+        f.serialize_into(&mut bytes);
+        if C::FIELD_TYPE == FieldType::M31 {
+            // the serialization is already a sequence of M31 words
+            bytes.chunks(4).for_each(|w| {
+                let w = M31::from(u32::from_le_bytes(w.try_into().unwrap()));
+                input.extend_from_slice(&canonical_u32(&w).to_le_bytes());
+            });
+        } else {
+            self.absorb_bytes(input, &bytes);
+        }
+    }
+
+    fn squeeze_f<C: GKRConfig>(&self, digest: &[u8; 32]) -> C::ChallengeField {
+        if C::FIELD_TYPE == FieldType::M31 {
+            // the digest words are M31 elements, read the challenge directly from them
+            C::ChallengeField::deserialize_from(&digest[..])
+        } else {
+            C::ChallengeField::from_uniform_bytes(digest)
+        }
+    }
+}
@@ -1,5 +1,8 @@
 #![cfg_attr(target_arch = "x86_64", feature(stdarch_x86_avx512))]
 
+pub mod batch;
+pub use batch::*;
+
 pub mod circuit;
 pub use circuit::*;
 
@@ -15,5 +18,11 @@ pub use poly_commit::*;
 pub mod prover;
 pub use prover::*;
 
+pub mod soundness;
+pub use soundness::*;
+
+pub mod sumcheck_poly;
+pub use sumcheck_poly::*;
+
 pub mod verifier;
 pub use verifier::*;
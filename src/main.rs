@@ -5,7 +5,9 @@ use std::{
 };
 
 use clap::Parser;
-use expander_rs::{BN254Config, Circuit, Config, GKRConfig, GKRScheme, M31ExtConfig, Prover};
+use expander_rs::{
+    BN254Config, BatchCircuit, Circuit, Config, GKRConfig, GKRScheme, M31ExtConfig, Prover,
+};
 
 // circuit for repeating Keccak for 8 times
 const KECCAK_CIRCUIT: &str = "data/circuit.txt";
@@ -33,6 +35,10 @@ struct Args {
     /// number of thread
     #[arg(short, long, default_value_t = 1)]
     threads: u64,
+
+    /// number of instances proven together, a power of two
+    #[arg(short, long, default_value_t = 1)]
+    batch: usize,
 }
 
 fn main() {
@@ -98,7 +104,10 @@ fn run_benchmark<C: GKRConfig>(args: &Args, config: Config<C>) {
             let mut c = circuit_template.clone();
             c.set_random_bool_input_for_test();
             c.evaluate();
-            c
+            let inputs = vec![c.layers[0].input_vals.evals.clone(); args.batch];
+            let mut batch = BatchCircuit::new(&c, args.batch);
+            batch.set_inputs(&inputs);
+            batch
         })
         .collect::<Vec<_>>();
 
@@ -113,11 +122,11 @@ fn run_benchmark<C: GKRConfig>(args: &Args, config: Config<C>) {
                 loop {
                     // bench func
                     let mut prover = Prover::new(&local_config);
-                    prover.prepare_mem(&c);
-                    prover.prove(&c);
+                    prover.prepare_mem(&c.circuit);
+                    prover.prove_batch(&c);
                     // update cnt
                     let mut cnt = partial_proof_cnt.lock().unwrap();
-                    let proof_cnt_this_round = circuit_copy_size * pack_size;
+                    let proof_cnt_this_round = circuit_copy_size * pack_size * c.instance_num;
                     *cnt += proof_cnt_this_round;
                 }
             })
@@ -146,6 +155,7 @@ fn print_info(args: &Args) {
     );
     println!("field:          {}", args.field);
     println!("#threads:       {}", args.threads);
+    println!("#batch:         {}", args.batch);
     println!("#bench repeats: {}", args.repeats);
     println!("hash scheme:    {}", args.scheme);
     println!("===============================")
@@ -1,4 +1,434 @@
+use std::{
+    io::{Read, Write},
+    marker::PhantomData,
+    sync::Arc,
+};
+
+use arith::FieldSerde;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    check_zk_support, zk_commitment_len, Circuit, Config, FiatShamirHash, GKRConfig,
+    InputClaimWeights, PolynomialCommitmentType, Proof, Transcript, ZKSupportError,
+};
+
 pub mod raw;
 pub use self::raw::*;
 
-pub trait PolynomialCommitment {}
+pub mod kzg;
+pub use self::kzg::*;
+
+pub mod hyrax;
+pub use self::hyrax::*;
+
+pub mod merkle;
+pub use self::merkle::*;
+
+pub mod orion;
+pub use self::orion::*;
+
+pub mod basefold;
+pub use self::basefold::*;
+
+pub mod pedersen;
+pub use self::pedersen::*;
+
+/// A polynomial commitment scheme for multilinear polynomials given by their evaluations
+/// over the boolean hypercube, as used for the input layer of GKR.
+pub trait PolynomialCommitment<C: GKRConfig> {
+    /// Public parameters for polynomials of a given number of variables
+    type Params: Send + Sync;
+    /// The commitment sent to the verifier
+    type Commitment: Send;
+    /// The proof for an evaluation at a single point
+    type Opening: Send;
+    /// Data computed during commit and kept by the prover for the openings
+    type CommitmentAux: Send;
+
+    /// Whether `setup` samples a trapdoor. The parameters of such a scheme must then come
+    /// from a ceremony and are never generated by `PCSInstance::new`.
+    const TRUSTED_SETUP: bool = false;
+
+    /// Generate the public parameters for polynomials with `num_vars` variables
+    fn setup(num_vars: usize, rng: impl RngCore) -> Self::Params;
+
+    /// Generate the public parameters for a target of `security_bits` bits of security, for
+    /// the schemes whose parameters depend on it. The other schemes ignore the target.
+    fn setup_with_security_bits(
+        num_vars: usize,
+        _security_bits: usize,
+        rng: impl RngCore,
+    ) -> Self::Params {
+        Self::setup(num_vars, rng)
+    }
+
+    /// A digest of the parameters of a scheme with a trusted setup. It is bound into the
+    /// statement, so that a proof only verifies with the parameters it was made with.
+    /// The parameters of the transparent schemes follow from the configuration.
+    fn params_digest(_params: &Self::Params) -> Option<[u8; 32]> {
+        None
+    }
+
+    /// Commit to the polynomial with evaluations `poly_vals`
+    fn commit(
+        params: &Self::Params,
+        poly_vals: &[C::Field],
+    ) -> (Self::Commitment, Self::CommitmentAux);
+
+    /// Prove the evaluation of the committed polynomial at `x`.
+    /// The transcript is only used to draw challenges, the serialized opening is
+    /// appended to it by the caller afterwards.
+    fn open<H: FiatShamirHash>(
+        params: &Self::Params,
+        poly_vals: &[C::Field],
+        aux: &Self::CommitmentAux,
+        x: &[C::ChallengeField],
+        transcript: &mut Transcript<H>,
+    ) -> Self::Opening;
+
+    /// Check that the committed polynomial evaluates to `y` at `x`
+    fn verify<H: FiatShamirHash>(
+        params: &Self::Params,
+        commitment: &Self::Commitment,
+        x: &[C::ChallengeField],
+        y: C::Field,
+        opening: &Self::Opening,
+        transcript: &mut Transcript<H>,
+    ) -> bool;
+
+    /// Size of the serialized commitment in bytes
+    fn commitment_size(params: &Self::Params) -> usize;
+
+    /// Size of the serialized opening in bytes
+    fn opening_size(params: &Self::Params) -> usize;
+
+    fn serialize_commitment<W: Write>(commitment: &Self::Commitment, writer: W);
+
+    /// None if the bytes are not a well-formed commitment
+    fn deserialize_commitment<R: Read>(
+        params: &Self::Params,
+        reader: R,
+    ) -> Option<Self::Commitment>;
+
+    fn serialize_opening<W: Write>(opening: &Self::Opening, writer: W);
+
+    /// None if the bytes are not a well-formed opening
+    fn deserialize_opening<R: Read>(params: &Self::Params, reader: R) -> Option<Self::Opening>;
+}
+
+/// Object safe interface for the GKR prover and verifier to a polynomial commitment,
+/// so that the scheme can be selected at runtime by `Config::polynomial_commitment_type`.
+/// The commitment and openings go through the transcript.
+pub trait PCSForGKR<C: GKRConfig, H: FiatShamirHash>: Send {
+    /// Commit to the input layer and append the commitment to the transcript
+    fn commit(&mut self, poly_vals: &[C::Field], transcript: &mut Transcript<H>);
+
+    /// Open the committed input layer at `x` and append the opening to the transcript
+    fn open(
+        &mut self,
+        poly_vals: &[C::Field],
+        x: &[C::ChallengeField],
+        transcript: &mut Transcript<H>,
+    );
+
+    /// Size of the serialized commitment in bytes
+    fn commitment_size(&self) -> usize;
+
+    /// Size of the serialized opening in bytes
+    fn opening_size(&self) -> usize;
+
+    /// The digest of the parameters for a scheme with a trusted setup, see
+    /// `PolynomialCommitment::params_digest`
+    fn params_digest(&self) -> Option<[u8; 32]>;
+
+    /// Read the commitment from the proof and append it to the transcript. Returns false if
+    /// the proof does not hold a well-formed commitment.
+    fn read_commitment(&mut self, proof: &mut Proof, transcript: &mut Transcript<H>) -> bool;
+
+    /// Read an opening from the proof and check it against the claim `y` at `x`
+    fn verify(
+        &mut self,
+        x: &[C::ChallengeField],
+        y: C::Field,
+        proof: &mut Proof,
+        transcript: &mut Transcript<H>,
+    ) -> bool;
+}
+
+/// Hiding commitments to vectors for the zero-knowledge mode of GKR. The committed vectors
+/// are never opened: the prover shows in zero knowledge that a committed vector has a given
+/// inner product with a public vector, the value being known to the verifier.
+/// The commitments are indexed in the order they are made or read.
+pub trait ZKCommitmentForGKR<C: GKRConfig, H: FiatShamirHash>: Send {
+    /// Commit to `vals` with a fresh blinding factor, append the commitment to the transcript
+    /// and return its index
+    fn commit(&mut self, vals: &[C::Field], transcript: &mut Transcript<H>) -> usize;
+
+    /// Prove that the inner product of the committed vector `idx` with `a` is the value
+    /// already sent to the verifier, and append the proof to the transcript
+    fn prove_inner_product(
+        &mut self,
+        idx: usize,
+        a: &[C::ChallengeField],
+        transcript: &mut Transcript<H>,
+    );
+
+    /// Size of a serialized commitment in bytes
+    fn commitment_size(&self) -> usize;
+
+    /// Size in bytes of the serialized proof of an inner product with a vector of `len`
+    /// entries
+    fn inner_product_proof_size(&self, len: usize) -> usize;
+
+    /// Read the commitment to a vector of `len` entries from the proof, append it to the
+    /// transcript and return its index, or None if the proof does not hold a commitment
+    fn read_commitment(
+        &mut self,
+        len: usize,
+        proof: &mut Proof,
+        transcript: &mut Transcript<H>,
+    ) -> Option<usize>;
+
+    /// Read a proof and check that the inner product of the committed vector `idx`
+    /// with `a` is `y`. A truncated or malformed proof is rejected.
+    fn verify_inner_product(
+        &mut self,
+        idx: usize,
+        a: &[C::ChallengeField],
+        y: C::Field,
+        proof: &mut Proof,
+        transcript: &mut Transcript<H>,
+    ) -> bool;
+
+    /// Commit to the private inputs together with the mask of the input layer, append the
+    /// commitment to the transcript and return its index. The claims on the inputs are
+    /// proven in size sublinear in the number of inputs.
+    fn commit_input(
+        &mut self,
+        inputs: &[C::Field],
+        mask: &[C::Field],
+        transcript: &mut Transcript<H>,
+    ) -> usize;
+
+    /// Prove that the committed inputs and mask `idx` weighted by `weights` sum to the value
+    /// already sent to the verifier, and append the proof to the transcript
+    fn prove_input_claim(
+        &mut self,
+        idx: usize,
+        weights: &InputClaimWeights<C::ChallengeField>,
+        transcript: &mut Transcript<H>,
+    );
+
+    /// Size in bytes of the serialized commitment to `2^num_vars` inputs and a mask
+    fn input_commitment_size(&self, num_vars: usize) -> usize;
+
+    /// Size in bytes of the serialized proof of a claim on `2^num_vars` inputs and a mask of
+    /// `mask_len` entries
+    fn input_claim_proof_size(&self, num_vars: usize, mask_len: usize) -> usize;
+
+    /// Read the commitment to `2^num_vars` inputs and a mask of `mask_len` entries from the
+    /// proof, append it to the transcript and return its index, or None if the proof does not
+    /// hold a commitment
+    fn read_input_commitment(
+        &mut self,
+        num_vars: usize,
+        mask_len: usize,
+        proof: &mut Proof,
+        transcript: &mut Transcript<H>,
+    ) -> Option<usize>;
+
+    /// Read a proof and check that the committed inputs and mask `idx` weighted by `weights`
+    /// sum to `y`. A truncated or malformed proof is rejected.
+    fn verify_input_claim(
+        &mut self,
+        idx: usize,
+        weights: &InputClaimWeights<C::ChallengeField>,
+        y: C::Field,
+        proof: &mut Proof,
+        transcript: &mut Transcript<H>,
+    ) -> bool;
+}
+
+/// Randomness for the parameters of the transparent schemes, derived from `domain` so that
+/// the prover and the verifier agree on them. Unlike `StdRng`, the output of ChaCha20 for a
+/// seed does not change between versions of rand.
+pub fn transparent_setup_rng(domain: &[u8]) -> ChaCha20Rng {
+    ChaCha20Rng::from_seed(Sha256::digest(domain).into())
+}
+
+/// A polynomial commitment scheme together with its parameters and the state
+/// of the current commitment.
+pub struct PCSInstance<C: GKRConfig, P: PolynomialCommitment<C>> {
+    params: Arc<P::Params>,
+    commitment: Option<P::Commitment>,
+    aux: Option<P::CommitmentAux>,
+    _marker: PhantomData<C>,
+}
+
+impl<C: GKRConfig, P: PolynomialCommitment<C>> PCSInstance<C, P> {
+    // The parameters of the transparent schemes, e.g., the Hyrax generators, are generated
+    // from a fixed domain, so that the prover and the verifier agree on them.
+    const SETUP_DOMAIN: &'static [u8] = b"expander-rs pcs setup";
+
+    /// Panics for a scheme with a trusted setup, whose trapdoor would be public:
+    /// use `new_with_params` with parameters from a ceremony instead.
+    pub fn new(num_vars: usize) -> Self {
+        assert!(
+            !P::TRUSTED_SETUP,
+            "the parameters of a scheme with a trusted setup must be loaded, not generated"
+        );
+        Self::new_with_params(P::setup(
+            num_vars,
+            transparent_setup_rng(Self::SETUP_DOMAIN),
+        ))
+    }
+
+    /// As `new`, with the parameters for a target of `security_bits` bits of security
+    pub fn new_with_security_bits(num_vars: usize, security_bits: usize) -> Self {
+        assert!(
+            !P::TRUSTED_SETUP,
+            "the parameters of a scheme with a trusted setup must be loaded, not generated"
+        );
+        Self::new_with_params(P::setup_with_security_bits(
+            num_vars,
+            security_bits,
+            transparent_setup_rng(Self::SETUP_DOMAIN),
+        ))
+    }
+
+    pub fn new_with_params(params: P::Params) -> Self {
+        Self::new_with_shared_params(Arc::new(params))
+    }
+
+    /// Parameters shared between instances, e.g., loaded once from a ceremony file
+    pub fn new_with_shared_params(params: Arc<P::Params>) -> Self {
+        Self {
+            params,
+            commitment: None,
+            aux: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<C: GKRConfig, P: PolynomialCommitment<C>, H: FiatShamirHash> PCSForGKR<C, H>
+    for PCSInstance<C, P>
+{
+    fn commit(&mut self, poly_vals: &[C::Field], transcript: &mut Transcript<H>) {
+        let (commitment, aux) = P::commit(&self.params, poly_vals);
+
+        let mut buffer = vec![];
+        P::serialize_commitment(&commitment, &mut buffer);
+        transcript.append_u8_slice(&buffer);
+
+        self.commitment = Some(commitment);
+        self.aux = Some(aux);
+    }
+
+    fn open(
+        &mut self,
+        poly_vals: &[C::Field],
+        x: &[C::ChallengeField],
+        transcript: &mut Transcript<H>,
+    ) {
+        let aux = self.aux.as_ref().expect("open is called before commit");
+        let opening = P::open(&self.params, poly_vals, aux, x, transcript);
+
+        let mut buffer = vec![];
+        P::serialize_opening(&opening, &mut buffer);
+        transcript.append_u8_slice(&buffer);
+    }
+
+    fn commitment_size(&self) -> usize {
+        P::commitment_size(&self.params)
+    }
+
+    fn opening_size(&self) -> usize {
+        P::opening_size(&self.params)
+    }
+
+    fn params_digest(&self) -> Option<[u8; 32]> {
+        P::params_digest(&self.params)
+    }
+
+    fn read_commitment(&mut self, proof: &mut Proof, transcript: &mut Transcript<H>) -> bool {
+        let Some(bytes) = proof.get_next_bytes_and_step(P::commitment_size(&self.params)) else {
+            return false;
+        };
+        self.commitment = P::deserialize_commitment(&self.params, bytes);
+        transcript.append_u8_slice(bytes);
+        self.commitment.is_some()
+    }
+
+    fn verify(
+        &mut self,
+        x: &[C::ChallengeField],
+        y: C::Field,
+        proof: &mut Proof,
+        transcript: &mut Transcript<H>,
+    ) -> bool {
+        let commitment = self
+            .commitment
+            .as_ref()
+            .expect("verify is called before read_commitment");
+        let Some(bytes) = proof.get_next_bytes_and_step(P::opening_size(&self.params)) else {
+            return false;
+        };
+        let Some(opening) = P::deserialize_opening(&self.params, bytes) else {
+            return false;
+        };
+
+        // same order as the prover: challenges are drawn before the opening is appended
+        let verified = P::verify(&self.params, commitment, x, y, &opening, transcript);
+        transcript.append_u8_slice(bytes);
+        verified
+    }
+}
+
+/// Start a transcript for the messages inside an opening, bound to the state of the
+/// outer transcript. Schemes with interactive openings absorb their messages there,
+/// since the outer transcript only absorbs the opening once it is complete.
+pub fn new_opening_transcript<C: GKRConfig, H: FiatShamirHash>(
+    transcript: &mut Transcript<H>,
+) -> Transcript<H> {
+    let mut seed = vec![];
+    transcript
+        .challenge_fs::<C>(2)
+        .iter()
+        .for_each(|c| c.serialize_into(&mut seed));
+
+    let mut opening_transcript = Transcript::new();
+    opening_transcript.append_u8_slice(&seed);
+    opening_transcript
+}
+
+/// Instantiate the polynomial commitment scheme of the configuration for the input layer
+/// of a circuit with `num_vars` input variables.
+pub fn new_pcs_for_gkr<C: GKRConfig, H: FiatShamirHash>(
+    config: &Config<C>,
+    num_vars: usize,
+) -> Box<dyn PCSForGKR<C, H>> {
+    match config.polynomial_commitment_type {
+        PolynomialCommitmentType::Raw => Box::new(PCSInstance::<C, RawPCS>::new(num_vars)),
+        _ => C::new_pcs::<H>(config, num_vars).unwrap_or_else(|| {
+            panic!(
+                "{:?} is not supported for field {:?}",
+                config.polynomial_commitment_type,
+                C::FIELD_TYPE
+            )
+        }),
+    }
+}
+
+/// Instantiate the hiding commitment of the zero-knowledge mode for `circuit`, or the reason
+/// why the circuit cannot be proven in zero knowledge with `config`.
+pub fn new_zk_commitment_for_gkr<C: GKRConfig, H: FiatShamirHash>(
+    circuit: &Circuit<C>,
+    config: &Config<C>,
+) -> Result<Box<dyn ZKCommitmentForGKR<C, H>>, ZKSupportError> {
+    check_zk_support(circuit, config)?;
+    C::new_zk_commitment::<H>(config, zk_commitment_len(circuit))
+        .ok_or(ZKSupportError::UnsupportedCommitment)
+}
@@ -0,0 +1,467 @@
+//! Basefold polynomial commitment, a FRI style commitment for multilinear polynomials,
+//! for the M31 configuration.
+//!
+//! The evaluations are encoded with a random foldable code: a message m of length 2k is
+//! encoded as (E(m_even) + t E(m_odd), E(m_even) - t E(m_odd)) for public weights t, and
+//! a message of length 1 with the repetition code. The codeword of the input is committed
+//! with a Merkle tree. To open at x, the prover runs the sumcheck for
+//! sum_b f(b) eq(b, x) = y, and folds the codeword with each sumcheck challenge, which
+//! gives the codeword of f with one less variable. The last message is f(r), and the
+//! folding is checked at a number of queried positions.
+
+use std::io::{Read, Write};
+
+use arith::{BinomialExtensionField, Field, FieldSerde, M31Ext3, SimdM31Ext3, M31};
+use rand::{Rng, RngCore};
+
+use crate::{
+    eq_evals_at_primitive, hash_leaf, new_opening_transcript, FiatShamirHash, M31ExtConfig,
+    MerkleHash, MerkleTree, PolynomialCommitment, Transcript, MERKLE_HASH_SIZE,
+};
+
+/// The codewords are 2^BASEFOLD_LOG_RATE times longer than the messages
+const BASEFOLD_LOG_RATE: usize = 3;
+
+const BASEFOLD_SECURITY_BITS: usize = 100;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BasefoldParams {
+    pub num_vars: usize,
+    pub log_rate: usize,
+    pub query_num: usize,
+    /// weights[k] are the weights t used to encode messages of length 2^(k+1),
+    /// of length 2^(k + log_rate)
+    pub weights: Vec<Vec<M31>>,
+    /// 1 / (2 t), used for folding
+    pub inv_two_weights: Vec<Vec<M31>>,
+}
+
+impl BasefoldParams {
+    /// Length of the codeword folded `layer` times
+    #[inline]
+    pub fn codeword_len(&self, layer: usize) -> usize {
+        1 << (self.num_vars + self.log_rate - layer)
+    }
+
+    /// Number of codewords queried in an opening, the last one is only committed
+    /// implicitly by the final value when there is at least one variable
+    #[inline]
+    pub fn layer_num(&self) -> usize {
+        self.num_vars.max(1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BasefoldCommitment {
+    pub root: MerkleHash,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BasefoldCommitmentAux {
+    pub codeword: Vec<SimdM31Ext3>,
+    pub tree: MerkleTree,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BasefoldOpening {
+    /// evaluations at 0, 1 and 2 of the sumcheck polynomial of each round
+    pub sumcheck_evals: Vec<[SimdM31Ext3; 3]>,
+    /// roots of the folded codewords, for layers 1..layer_num
+    pub roots: Vec<MerkleHash>,
+    /// f(r), the fully folded message
+    pub final_value: SimdM31Ext3,
+    /// for each query, the pair of positions (p, p + half) opened in each layer
+    pub pairs: Vec<Vec<(SimdM31Ext3, SimdM31Ext3)>>,
+    pub paths: Vec<Vec<Vec<MerkleHash>>>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BasefoldPCS;
+
+impl BasefoldPCS {
+    /// A codeword far from the code passes each query with probability at most
+    /// (1 + rate) / 2 within the unique decoding radius.
+    fn query_num() -> usize {
+        let rate = 1.0 / (1 << BASEFOLD_LOG_RATE) as f64;
+        (BASEFOLD_SECURITY_BITS as f64 / -((1.0 + rate) / 2.0).log2()).ceil() as usize
+    }
+
+    fn encode(params: &BasefoldParams, msg: &[SimdM31Ext3]) -> Vec<SimdM31Ext3> {
+        if msg.len() == 1 {
+            return vec![msg[0]; 1 << params.log_rate];
+        }
+        let weights = &params.weights[msg.len().trailing_zeros() as usize - 1];
+        let even = msg.iter().step_by(2).cloned().collect::<Vec<_>>();
+        let odd = msg.iter().skip(1).step_by(2).cloned().collect::<Vec<_>>();
+        let even = Self::encode(params, &even);
+        let odd = Self::encode(params, &odd);
+
+        let half = even.len();
+        let mut codeword = vec![SimdM31Ext3::zero(); half * 2];
+        for j in 0..half {
+            let t_odd = odd[j] * weights[j];
+            codeword[j] = even[j] + t_odd;
+            codeword[j + half] = even[j] - t_odd;
+        }
+        codeword
+    }
+
+    /// The position p of the codeword of f(r, .) from the positions p and p + half
+    /// of the codeword of f, where k is the level of the weights used to encode f
+    #[inline]
+    fn fold_pair(
+        params: &BasefoldParams,
+        k: usize,
+        p: usize,
+        pair: &(SimdM31Ext3, SimdM31Ext3),
+        r: &M31Ext3,
+    ) -> SimdM31Ext3 {
+        let inv_two = M31::from(2).inv().unwrap();
+        let even = (pair.0 + pair.1) * inv_two;
+        let odd = (pair.0 - pair.1) * params.inv_two_weights[k][p];
+        even + (odd - even) * *r
+    }
+
+    fn fold_codeword(
+        params: &BasefoldParams,
+        codeword: &[SimdM31Ext3],
+        r: &M31Ext3,
+    ) -> Vec<SimdM31Ext3> {
+        let half = codeword.len() / 2;
+        let k = (half >> params.log_rate).trailing_zeros() as usize;
+        (0..half)
+            .map(|p| Self::fold_pair(params, k, p, &(codeword[p], codeword[p + half]), r))
+            .collect()
+    }
+
+    fn pair_hash(pair: &(SimdM31Ext3, SimdM31Ext3)) -> MerkleHash {
+        let mut buffer = vec![];
+        pair.0.serialize_into(&mut buffer);
+        pair.1.serialize_into(&mut buffer);
+        hash_leaf(&buffer)
+    }
+
+    fn build_tree(codeword: &[SimdM31Ext3]) -> MerkleTree {
+        let half = codeword.len() / 2;
+        MerkleTree::new(
+            (0..half)
+                .map(|p| Self::pair_hash(&(codeword[p], codeword[p + half])))
+                .collect(),
+        )
+    }
+
+    /// Evaluate the degree 2 polynomial given by its evaluations at 0, 1, 2 at r
+    fn eval_degree_2(evals: &[SimdM31Ext3; 3], r: &M31Ext3) -> SimdM31Ext3 {
+        let one = M31Ext3::one();
+        let two = M31Ext3::from(2u32);
+        let inv_two = M31::from(2).inv().unwrap();
+        let l0 = ((*r - one) * (*r - two)).mul_by_base_field(&inv_two);
+        let l1 = -(*r * (*r - two));
+        let l2 = (*r * (*r - one)).mul_by_base_field(&inv_two);
+        evals[0] * l0 + evals[1] * l1 + evals[2] * l2
+    }
+
+    fn sample_queries<H: FiatShamirHash>(
+        params: &BasefoldParams,
+        opening_transcript: &mut Transcript<H>,
+    ) -> Vec<usize> {
+        opening_transcript
+            .challenge_fs::<M31ExtConfig>(params.query_num)
+            .iter()
+            .map(|c| c.as_u32_unchecked() as usize % (params.codeword_len(0) / 2))
+            .collect()
+    }
+
+    fn read_hash<R: Read>(mut reader: R) -> Option<MerkleHash> {
+        let mut h = [0u8; MERKLE_HASH_SIZE];
+        reader.read_exact(&mut h).ok()?;
+        Some(h)
+    }
+}
+
+impl PolynomialCommitment<M31ExtConfig> for BasefoldPCS {
+    type Params = BasefoldParams;
+    type Commitment = BasefoldCommitment;
+    type Opening = BasefoldOpening;
+    type CommitmentAux = BasefoldCommitmentAux;
+
+    fn setup(num_vars: usize, mut rng: impl RngCore) -> Self::Params {
+        let log_rate = BASEFOLD_LOG_RATE;
+        let weights = (0..num_vars)
+            .map(|k| {
+                (0..1 << (k + log_rate))
+                    .map(|_| M31::from(rng.gen_range(1..(1u32 << 31) - 1)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let inv_two_weights = weights
+            .iter()
+            .map(|ws| {
+                ws.iter()
+                    .map(|w| (*w + w).inv().unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        BasefoldParams {
+            num_vars,
+            log_rate,
+            query_num: Self::query_num(),
+            weights,
+            inv_two_weights,
+        }
+    }
+
+    fn commit(
+        params: &Self::Params,
+        poly_vals: &[SimdM31Ext3],
+    ) -> (Self::Commitment, Self::CommitmentAux) {
+        assert_eq!(poly_vals.len(), 1 << params.num_vars);
+        let codeword = Self::encode(params, poly_vals);
+        let tree = Self::build_tree(&codeword);
+        (
+            BasefoldCommitment { root: tree.root() },
+            BasefoldCommitmentAux { codeword, tree },
+        )
+    }
+
+    fn open<H: FiatShamirHash>(
+        params: &Self::Params,
+        poly_vals: &[SimdM31Ext3],
+        aux: &Self::CommitmentAux,
+        x: &[M31Ext3],
+        transcript: &mut Transcript<H>,
+    ) -> Self::Opening {
+        assert_eq!(x.len(), params.num_vars);
+        let mut opening_transcript = new_opening_transcript::<M31ExtConfig, H>(transcript);
+
+        let mut f = poly_vals.to_vec();
+        let mut eq = vec![M31Ext3::zero(); 1 << params.num_vars];
+        eq_evals_at_primitive(x, &M31Ext3::one(), &mut eq);
+
+        let mut codewords = vec![aux.codeword.clone()];
+        let mut trees = vec![aux.tree.clone()];
+        let mut sumcheck_evals = Vec::with_capacity(params.num_vars);
+        let mut roots = vec![];
+        for i_var in 0..params.num_vars {
+            let half = f.len() / 2;
+            let mut evals = [SimdM31Ext3::zero(); 3];
+            for i in 0..half {
+                let (f_even, f_odd) = (f[2 * i], f[2 * i + 1]);
+                let (eq_even, eq_odd) = (eq[2 * i], eq[2 * i + 1]);
+                evals[0] += f_even * eq_even;
+                evals[1] += f_odd * eq_odd;
+                evals[2] += (f_odd + f_odd - f_even) * (eq_odd + eq_odd - eq_even);
+            }
+            evals
+                .iter()
+                .for_each(|e| opening_transcript.append_f::<M31ExtConfig>(*e));
+            sumcheck_evals.push(evals);
+            let r = opening_transcript.challenge_f::<M31ExtConfig>();
+
+            for i in 0..half {
+                f[i] = f[2 * i] + (f[2 * i + 1] - f[2 * i]) * r;
+                eq[i] = eq[2 * i] + (eq[2 * i + 1] - eq[2 * i]) * r;
+            }
+            f.truncate(half);
+            eq.truncate(half);
+
+            if i_var + 1 < params.layer_num() {
+                let codeword = Self::fold_codeword(params, codewords.last().unwrap(), &r);
+                let tree = Self::build_tree(&codeword);
+                roots.push(tree.root());
+                opening_transcript.append_u8_slice(&tree.root());
+                codewords.push(codeword);
+                trees.push(tree);
+            }
+        }
+        let final_value = f[0];
+        opening_transcript.append_f::<M31ExtConfig>(final_value);
+
+        let queries = Self::sample_queries(params, &mut opening_transcript);
+        let mut pairs = Vec::with_capacity(params.query_num);
+        let mut paths = Vec::with_capacity(params.query_num);
+        for idx in queries {
+            let mut query_pairs = Vec::with_capacity(params.layer_num());
+            let mut query_paths = Vec::with_capacity(params.layer_num());
+            for (codeword, tree) in codewords.iter().zip(trees.iter()) {
+                let half = codeword.len() / 2;
+                let p = idx % half;
+                query_pairs.push((codeword[p], codeword[p + half]));
+                query_paths.push(tree.path(p));
+            }
+            pairs.push(query_pairs);
+            paths.push(query_paths);
+        }
+
+        BasefoldOpening {
+            sumcheck_evals,
+            roots,
+            final_value,
+            pairs,
+            paths,
+        }
+    }
+
+    fn verify<H: FiatShamirHash>(
+        params: &Self::Params,
+        commitment: &Self::Commitment,
+        x: &[M31Ext3],
+        y: SimdM31Ext3,
+        opening: &Self::Opening,
+        transcript: &mut Transcript<H>,
+    ) -> bool {
+        assert_eq!(x.len(), params.num_vars);
+        let mut opening_transcript = new_opening_transcript::<M31ExtConfig, H>(transcript);
+
+        let mut verified = true;
+        let mut claim = y;
+        let mut eq_r_x = M31Ext3::one();
+        let mut rs = Vec::with_capacity(params.num_vars);
+        for (i_var, (evals, x_i)) in opening.sumcheck_evals.iter().zip(x.iter()).enumerate() {
+            verified &= evals[0] + evals[1] == claim;
+            evals
+                .iter()
+                .for_each(|e| opening_transcript.append_f::<M31ExtConfig>(*e));
+            let r = opening_transcript.challenge_f::<M31ExtConfig>();
+
+            claim = Self::eval_degree_2(evals, &r);
+            eq_r_x *= r * x_i + (M31Ext3::one() - r) * (M31Ext3::one() - x_i);
+            rs.push(r);
+
+            if i_var + 1 < params.layer_num() {
+                opening_transcript.append_u8_slice(&opening.roots[i_var]);
+            }
+        }
+        verified &= claim == opening.final_value * eq_r_x;
+        opening_transcript.append_f::<M31ExtConfig>(opening.final_value);
+
+        let queries = Self::sample_queries(params, &mut opening_transcript);
+        let roots = std::iter::once(&commitment.root)
+            .chain(opening.roots.iter())
+            .collect::<Vec<_>>();
+        for ((idx, query_pairs), query_paths) in queries
+            .iter()
+            .zip(opening.pairs.iter())
+            .zip(opening.paths.iter())
+        {
+            // the value at position pos of the current layer, folded from the previous one
+            let mut expected = None;
+            let mut pos = *idx;
+            for (layer, (pair, path)) in query_pairs.iter().zip(query_paths.iter()).enumerate() {
+                let half = params.codeword_len(layer) / 2;
+                let p = pos % half;
+                verified &= MerkleTree::verify_path(roots[layer], &Self::pair_hash(pair), p, path);
+                if let Some(expected) = expected {
+                    verified &= expected == if pos < half { pair.0 } else { pair.1 };
+                }
+
+                if layer < params.num_vars {
+                    let k = params.num_vars - layer - 1;
+                    expected = Some(Self::fold_pair(params, k, p, pair, &rs[layer]));
+                } else {
+                    // no variable, the codeword repeats the value
+                    verified &= pair.0 == opening.final_value && pair.1 == opening.final_value;
+                }
+                pos = p;
+            }
+            if let Some(expected) = expected {
+                verified &= expected == opening.final_value;
+            }
+        }
+        verified
+    }
+
+    fn commitment_size(_params: &Self::Params) -> usize {
+        MERKLE_HASH_SIZE
+    }
+
+    fn opening_size(params: &Self::Params) -> usize {
+        let query_size = (0..params.layer_num())
+            .map(|layer| {
+                let depth = params.codeword_len(layer).trailing_zeros() as usize - 1;
+                2 * SimdM31Ext3::SIZE + depth * MERKLE_HASH_SIZE
+            })
+            .sum::<usize>();
+        (3 * params.num_vars + 1) * SimdM31Ext3::SIZE
+            + (params.layer_num() - 1) * MERKLE_HASH_SIZE
+            + params.query_num * query_size
+    }
+
+    fn serialize_commitment<W: Write>(commitment: &Self::Commitment, mut writer: W) {
+        writer.write_all(&commitment.root).unwrap();
+    }
+
+    fn deserialize_commitment<R: Read>(
+        _params: &Self::Params,
+        reader: R,
+    ) -> Option<Self::Commitment> {
+        Some(BasefoldCommitment {
+            root: Self::read_hash(reader)?,
+        })
+    }
+
+    fn serialize_opening<W: Write>(opening: &Self::Opening, mut writer: W) {
+        opening
+            .sumcheck_evals
+            .iter()
+            .flatten()
+            .for_each(|v| v.serialize_into(&mut writer));
+        opening
+            .roots
+            .iter()
+            .for_each(|h| writer.write_all(h).unwrap());
+        opening.final_value.serialize_into(&mut writer);
+        for (query_pairs, query_paths) in opening.pairs.iter().zip(opening.paths.iter()) {
+            for (pair, path) in query_pairs.iter().zip(query_paths.iter()) {
+                pair.0.serialize_into(&mut writer);
+                pair.1.serialize_into(&mut writer);
+                path.iter().for_each(|h| writer.write_all(h).unwrap());
+            }
+        }
+    }
+
+    fn deserialize_opening<R: Read>(params: &Self::Params, mut reader: R) -> Option<Self::Opening> {
+        let sumcheck_evals = (0..params.num_vars)
+            .map(|_| {
+                Some([
+                    SimdM31Ext3::try_deserialize_from(&mut reader)?,
+                    SimdM31Ext3::try_deserialize_from(&mut reader)?,
+                    SimdM31Ext3::try_deserialize_from(&mut reader)?,
+                ])
+            })
+            .collect::<Option<_>>()?;
+        let roots = (1..params.layer_num())
+            .map(|_| Self::read_hash(&mut reader))
+            .collect::<Option<_>>()?;
+        let final_value = SimdM31Ext3::try_deserialize_from(&mut reader)?;
+
+        let mut pairs = Vec::with_capacity(params.query_num);
+        let mut paths = Vec::with_capacity(params.query_num);
+        for _ in 0..params.query_num {
+            let mut query_pairs = Vec::with_capacity(params.layer_num());
+            let mut query_paths = Vec::with_capacity(params.layer_num());
+            for layer in 0..params.layer_num() {
+                let depth = params.codeword_len(layer).trailing_zeros() as usize - 1;
+                query_pairs.push((
+                    SimdM31Ext3::try_deserialize_from(&mut reader)?,
+                    SimdM31Ext3::try_deserialize_from(&mut reader)?,
+                ));
+                query_paths.push(
+                    (0..depth)
+                        .map(|_| Self::read_hash(&mut reader))
+                        .collect::<Option<_>>()?,
+                );
+            }
+            pairs.push(query_pairs);
+            paths.push(query_paths);
+        }
+
+        Some(BasefoldOpening {
+            sumcheck_evals,
+            roots,
+            final_value,
+            pairs,
+            paths,
+        })
+    }
+}
@@ -0,0 +1,197 @@
+//! Hyrax commitment over the bn256 G1, without a trusted setup.
+//!
+//! The evaluations are arranged as a matrix with rows of 2^col_vars entries,
+//! and each row is committed with a Pedersen vector commitment. To open at r = (r_lo, r_hi),
+//! the prover sends the combination of the rows w = eq(r_hi, .)^T M. The verifier checks
+//! w against the same combination of the row commitments, and y = <w, eq(r_lo, .)>.
+//! The generators are drawn from the rng of `setup`, which `PCSInstance` derives from a fixed
+//! domain with `transparent_setup_rng`.
+
+use std::io::{Read, Write};
+
+use arith::{Field, FieldSerde};
+use halo2curves::{
+    bn256::{Fr, G1Affine, G1},
+    group::{prime::PrimeCurveAffine, Curve, Group, GroupEncoding},
+    msm::best_multiexp,
+};
+use rand::RngCore;
+
+use crate::{eq_evals_at_primitive, BN254Config, FiatShamirHash, PolynomialCommitment, Transcript};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HyraxParams {
+    pub num_vars: usize,
+    /// the number of variables indexing the columns, the low bits of the index
+    pub col_vars: usize,
+    /// the Pedersen generators, one per column
+    pub generators: Vec<G1Affine>,
+}
+
+impl HyraxParams {
+    #[inline]
+    pub fn row_num(&self) -> usize {
+        1 << (self.num_vars - self.col_vars)
+    }
+
+    #[inline]
+    pub fn row_len(&self) -> usize {
+        1 << self.col_vars
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HyraxCommitment {
+    /// the commitments to the rows
+    pub row_coms: Vec<G1Affine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HyraxOpening {
+    /// the rows combined by eq(r_hi, .)
+    pub eval_row: Vec<Fr>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HyraxPCS;
+
+impl HyraxPCS {
+    /// Size of a compressed G1 element
+    #[inline]
+    pub(crate) fn g1_size() -> usize {
+        <G1Affine as PrimeCurveAffine>::identity()
+            .to_bytes()
+            .as_ref()
+            .len()
+    }
+
+    /// None if the reader ends early or the bytes do not encode a point, as for the KZG
+    /// commitment
+    #[inline]
+    pub(crate) fn read_g1<R: Read>(mut reader: R) -> Option<G1Affine> {
+        let mut repr = <G1Affine as GroupEncoding>::Repr::default();
+        reader.read_exact(repr.as_mut()).ok()?;
+        Option::from(G1Affine::from_bytes(&repr))
+    }
+
+    pub(crate) fn eq_evals(r: &[Fr]) -> Vec<Fr> {
+        let mut eq_evals = vec![Fr::zero(); 1 << r.len()];
+        eq_evals_at_primitive(r, &Fr::one(), &mut eq_evals);
+        eq_evals
+    }
+}
+
+impl PolynomialCommitment<BN254Config> for HyraxPCS {
+    type Params = HyraxParams;
+    type Commitment = HyraxCommitment;
+    type Opening = HyraxOpening;
+    type CommitmentAux = ();
+
+    fn setup(num_vars: usize, mut rng: impl RngCore) -> Self::Params {
+        let col_vars = num_vars.div_ceil(2);
+        let proj_generators = (0..1 << col_vars)
+            .map(|_| <G1 as Group>::random(&mut rng))
+            .collect::<Vec<_>>();
+        let mut generators = vec![<G1Affine as PrimeCurveAffine>::identity(); 1 << col_vars];
+        <G1 as Curve>::batch_normalize(&proj_generators, &mut generators);
+        HyraxParams {
+            num_vars,
+            col_vars,
+            generators,
+        }
+    }
+
+    fn commit(params: &Self::Params, poly_vals: &[Fr]) -> (Self::Commitment, Self::CommitmentAux) {
+        assert_eq!(poly_vals.len(), 1 << params.num_vars);
+        let proj_coms = poly_vals
+            .chunks(params.row_len())
+            .map(|row| best_multiexp(row, &params.generators))
+            .collect::<Vec<_>>();
+        let mut row_coms = vec![<G1Affine as PrimeCurveAffine>::identity(); params.row_num()];
+        <G1 as Curve>::batch_normalize(&proj_coms, &mut row_coms);
+        (HyraxCommitment { row_coms }, ())
+    }
+
+    fn open<H: FiatShamirHash>(
+        params: &Self::Params,
+        poly_vals: &[Fr],
+        _aux: &Self::CommitmentAux,
+        x: &[Fr],
+        _transcript: &mut Transcript<H>,
+    ) -> Self::Opening {
+        assert_eq!(x.len(), params.num_vars);
+        let eq_hi = Self::eq_evals(&x[params.col_vars..]);
+
+        let mut eval_row = vec![Fr::zero(); params.row_len()];
+        for (row, coef) in poly_vals.chunks(params.row_len()).zip(eq_hi.iter()) {
+            for (w, v) in eval_row.iter_mut().zip(row.iter()) {
+                *w += *v * coef;
+            }
+        }
+        HyraxOpening { eval_row }
+    }
+
+    fn verify<H: FiatShamirHash>(
+        params: &Self::Params,
+        commitment: &Self::Commitment,
+        x: &[Fr],
+        y: Fr,
+        opening: &Self::Opening,
+        _transcript: &mut Transcript<H>,
+    ) -> bool {
+        assert_eq!(x.len(), params.num_vars);
+        let (r_lo, r_hi) = x.split_at(params.col_vars);
+
+        let combined_com = best_multiexp(&Self::eq_evals(r_hi), &commitment.row_coms);
+        let eval_row_com = best_multiexp(&opening.eval_row, &params.generators);
+        let eval = opening
+            .eval_row
+            .iter()
+            .zip(Self::eq_evals(r_lo).iter())
+            .map(|(w, eq)| *w * eq)
+            .sum::<Fr>();
+
+        combined_com == eval_row_com && eval == y
+    }
+
+    fn commitment_size(params: &Self::Params) -> usize {
+        params.row_num() * Self::g1_size()
+    }
+
+    fn opening_size(params: &Self::Params) -> usize {
+        params.row_len() * Fr::SIZE
+    }
+
+    fn serialize_commitment<W: Write>(commitment: &Self::Commitment, mut writer: W) {
+        commitment
+            .row_coms
+            .iter()
+            .for_each(|p| writer.write_all(p.to_bytes().as_ref()).unwrap());
+    }
+
+    fn deserialize_commitment<R: Read>(
+        params: &Self::Params,
+        mut reader: R,
+    ) -> Option<Self::Commitment> {
+        Some(HyraxCommitment {
+            row_coms: (0..params.row_num())
+                .map(|_| Self::read_g1(&mut reader))
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    fn serialize_opening<W: Write>(opening: &Self::Opening, mut writer: W) {
+        opening
+            .eval_row
+            .iter()
+            .for_each(|v| v.serialize_into(&mut writer));
+    }
+
+    fn deserialize_opening<R: Read>(params: &Self::Params, mut reader: R) -> Option<Self::Opening> {
+        Some(HyraxOpening {
+            eval_row: (0..params.row_len())
+                .map(|_| Fr::try_deserialize_from(&mut reader))
+                .collect::<Option<_>>()?,
+        })
+    }
+}
@@ -0,0 +1,365 @@
+//! Multilinear KZG commitment, a.k.a. PST13, over the bn256 pairing.
+//!
+//! A polynomial f with evaluations f_b over the boolean hypercube is committed as
+//! g^{f(tau)} = prod_b (g^{eq(b, tau)})^{f_b}. To open at r, the prover writes
+//! f(x) - f(r) = sum_k (x_k - r_k) q_k(x_{k+1}, ..., x_{n-1})
+//! and sends the commitments to the quotients q_k. The verifier checks
+//! e(C - g^{f(r)}, h) = prod_k e(pi_k, h^{tau_k - r_k}).
+//!
+//! Anyone who knows tau can open a commitment to any value, so the parameters must come
+//! from a ceremony and are loaded with `MultilinearKZGParams::load`. `setup` is only meant
+//! for tests and for running such a ceremony.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use halo2curves::{
+    bn256::{Bn256, Fr, G1Affine, G2Affine, G2Prepared, G1},
+    ff::Field as Halo2Field,
+    group::{prime::PrimeCurveAffine, Curve, Group, GroupEncoding},
+    msm::best_multiexp,
+    pairing::{MillerLoopResult, MultiMillerLoop},
+};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::{BN254Config, FiatShamirHash, PolynomialCommitment, Transcript};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MultilinearKZGParams {
+    pub num_vars: usize,
+    /// lagrange_bases[k][b] = g^{eq(b, (tau_k, ..., tau_{n-1}))}, the basis for the
+    /// polynomials in the last n - k variables
+    pub lagrange_bases: Vec<Vec<G1Affine>>,
+    /// The generator of G1.
+    pub g: G1Affine,
+    /// The generator of G2.
+    pub h: G2Affine,
+    /// h^{tau_k} for k in 0..n
+    pub tau_h: Vec<G2Affine>,
+}
+
+impl MultilinearKZGParams {
+    /// Write the structured reference string: the number of variables, g, h, the h^{tau_k}
+    /// and the largest basis, the others being derived from it when loading.
+    pub fn serialize_into<W: Write>(&self, mut writer: W) {
+        writer
+            .write_all(&(self.num_vars as u64).to_le_bytes())
+            .unwrap();
+        MultilinearKZG::write_g1(&self.g, &mut writer);
+        MultilinearKZG::write_g2(&self.h, &mut writer);
+        self.tau_h
+            .iter()
+            .for_each(|p| MultilinearKZG::write_g2(p, &mut writer));
+        self.lagrange_bases[0]
+            .iter()
+            .for_each(|p| MultilinearKZG::write_g1(p, &mut writer));
+    }
+
+    /// Read a structured reference string written by `serialize_into`.
+    /// Panics on an invalid point: unlike a proof, the parameters are trusted input.
+    pub fn deserialize_from<R: Read>(mut reader: R) -> Self {
+        let mut num_vars = [0u8; 8];
+        reader.read_exact(&mut num_vars).unwrap();
+        let num_vars = u64::from_le_bytes(num_vars) as usize;
+
+        let g = MultilinearKZG::read_g1_strict(&mut reader);
+        let h = MultilinearKZG::read_g2_strict(&mut reader);
+        let tau_h = (0..num_vars)
+            .map(|_| MultilinearKZG::read_g2_strict(&mut reader))
+            .collect();
+
+        // basis_{k+1}[i] = basis_k[2i] + basis_k[2i+1], since eq(., tau_k) sums to one
+        let mut lagrange_bases = vec![(0..1usize << num_vars)
+            .map(|_| MultilinearKZG::read_g1_strict(&mut reader))
+            .collect::<Vec<_>>()];
+        for _ in 0..num_vars {
+            let last = lagrange_bases.last().unwrap();
+            let proj_bases = last
+                .chunks(2)
+                .map(|pair| G1::from(pair[0]) + pair[1])
+                .collect::<Vec<G1>>();
+            let mut bases = vec![<G1Affine as PrimeCurveAffine>::identity(); proj_bases.len()];
+            G1::batch_normalize(&proj_bases, &mut bases);
+            lagrange_bases.push(bases);
+        }
+
+        Self {
+            num_vars,
+            lagrange_bases,
+            g,
+            h,
+            tau_h,
+        }
+    }
+
+    /// SHA256 of the serialized parameters, which identifies the ceremony and the size
+    pub fn digest(&self) -> [u8; 32] {
+        let mut buffer = vec![];
+        self.serialize_into(&mut buffer);
+        Sha256::digest(&buffer).into()
+    }
+
+    pub fn save(&self, path: &str) {
+        let file = File::create(path).unwrap();
+        let mut writer = BufWriter::new(file);
+        self.serialize_into(&mut writer);
+        writer.flush().unwrap();
+    }
+
+    pub fn load(path: &str) -> Self {
+        let file = File::open(path)
+            .unwrap_or_else(|e| panic!("cannot open the KZG parameters at {}: {}", path, e));
+        Self::deserialize_from(BufReader::new(file))
+    }
+
+    /// The parameters at `path` trimmed to `num_vars` variables. The file is read and trimmed
+    /// once per process for each pair of path and number of variables, the prover and the
+    /// verifier then share the parameters across proofs. Replacing the file while the
+    /// process runs has no effect.
+    pub fn load_trimmed(path: &str, num_vars: usize) -> Arc<Self> {
+        static LOADED: OnceLock<Mutex<HashMap<(String, usize), Arc<MultilinearKZGParams>>>> =
+            OnceLock::new();
+        let mut loaded = LOADED.get_or_init(Default::default).lock().unwrap();
+        loaded
+            .entry((path.to_string(), num_vars))
+            .or_insert_with(|| Arc::new(Self::load(path).trim(num_vars)))
+            .clone()
+    }
+
+    /// The parameters for polynomials in the last `num_vars` variables, so that a single
+    /// ceremony serves all the smaller circuits.
+    pub fn trim(&self, num_vars: usize) -> Self {
+        assert!(
+            num_vars <= self.num_vars,
+            "the KZG parameters support {} variables, {} are needed",
+            self.num_vars,
+            num_vars
+        );
+        let skip = self.num_vars - num_vars;
+        Self {
+            num_vars,
+            lagrange_bases: self.lagrange_bases[skip..].to_vec(),
+            g: self.g,
+            h: self.h,
+            tau_h: self.tau_h[skip..].to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MultilinearKZGCommitment {
+    pub com: G1Affine,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MultilinearKZGOpening {
+    /// commitments to the quotients q_k, for k in 0..n
+    pub pi: Vec<G1Affine>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MultilinearKZG;
+
+impl MultilinearKZG {
+    /// Size of a compressed G1 element
+    #[inline]
+    fn g1_size() -> usize {
+        <G1Affine as PrimeCurveAffine>::identity()
+            .to_bytes()
+            .as_ref()
+            .len()
+    }
+
+    #[inline]
+    fn write_g1<W: Write>(p: &G1Affine, mut writer: W) {
+        writer.write_all(p.to_bytes().as_ref()).unwrap();
+    }
+
+    /// None if the reader ends early or the bytes do not encode a point, so that a malformed
+    /// proof is rejected instead of making the verifier panic.
+    #[inline]
+    fn read_g1<R: Read>(mut reader: R) -> Option<G1Affine> {
+        let mut repr = <G1Affine as GroupEncoding>::Repr::default();
+        reader.read_exact(repr.as_mut()).ok()?;
+        Option::from(G1Affine::from_bytes(&repr))
+    }
+
+    #[inline]
+    fn read_g1_strict<R: Read>(mut reader: R) -> G1Affine {
+        let mut repr = <G1Affine as GroupEncoding>::Repr::default();
+        reader.read_exact(repr.as_mut()).unwrap();
+        Option::from(G1Affine::from_bytes(&repr)).expect("invalid G1 point in the KZG parameters")
+    }
+
+    #[inline]
+    fn write_g2<W: Write>(p: &G2Affine, mut writer: W) {
+        writer.write_all(p.to_bytes().as_ref()).unwrap();
+    }
+
+    #[inline]
+    fn read_g2_strict<R: Read>(mut reader: R) -> G2Affine {
+        let mut repr = <G2Affine as GroupEncoding>::Repr::default();
+        reader.read_exact(repr.as_mut()).unwrap();
+        Option::from(G2Affine::from_bytes(&repr)).expect("invalid G2 point in the KZG parameters")
+    }
+}
+
+impl PolynomialCommitment<BN254Config> for MultilinearKZG {
+    type Params = MultilinearKZGParams;
+    type Commitment = MultilinearKZGCommitment;
+    type Opening = MultilinearKZGOpening;
+    type CommitmentAux = ();
+
+    const TRUSTED_SETUP: bool = true;
+
+    fn params_digest(params: &Self::Params) -> Option<[u8; 32]> {
+        Some(params.digest())
+    }
+
+    fn setup(num_vars: usize, mut rng: impl RngCore) -> Self::Params {
+        let tau = (0..num_vars)
+            .map(|_| <Fr as Halo2Field>::random(&mut rng))
+            .collect::<Vec<_>>();
+        let g = <G1Affine as PrimeCurveAffine>::generator();
+        let h = <G2Affine as PrimeCurveAffine>::generator();
+
+        // the eq tables over the suffixes of tau, from the shortest one;
+        // tau_k becomes the lowest bit when going from k + 1 to k
+        let mut eq_tables = vec![vec![Fr::one()]];
+        for tau_k in tau.iter().rev() {
+            let last = eq_tables.last().unwrap();
+            let mut table = vec![Fr::zero(); last.len() * 2];
+            for (i, v) in last.iter().enumerate() {
+                table[2 * i] = *v * (Fr::one() - tau_k);
+                table[2 * i + 1] = *v * tau_k;
+            }
+            eq_tables.push(table);
+        }
+        eq_tables.reverse();
+
+        let lagrange_bases = eq_tables
+            .iter()
+            .map(|table| {
+                let proj_bases = table.iter().map(|s| g * s).collect::<Vec<G1>>();
+                let mut bases = vec![<G1Affine as PrimeCurveAffine>::identity(); table.len()];
+                G1::batch_normalize(&proj_bases, &mut bases);
+                bases
+            })
+            .collect();
+
+        MultilinearKZGParams {
+            num_vars,
+            lagrange_bases,
+            g,
+            h,
+            tau_h: tau.iter().map(|t| (h * t).to_affine()).collect(),
+        }
+    }
+
+    fn commit(params: &Self::Params, poly_vals: &[Fr]) -> (Self::Commitment, Self::CommitmentAux) {
+        assert_eq!(poly_vals.len(), 1 << params.num_vars);
+        let com = best_multiexp(poly_vals, &params.lagrange_bases[0]).to_affine();
+        (MultilinearKZGCommitment { com }, ())
+    }
+
+    fn open<H: FiatShamirHash>(
+        params: &Self::Params,
+        poly_vals: &[Fr],
+        _aux: &Self::CommitmentAux,
+        x: &[Fr],
+        _transcript: &mut Transcript<H>,
+    ) -> Self::Opening {
+        assert_eq!(x.len(), params.num_vars);
+
+        // fold the variables one by one, q_k = f(r_0, ..., r_{k-1}, 1, ...) - f(r_0, ..., r_{k-1}, 0, ...)
+        let mut f = poly_vals.to_vec();
+        let mut pi = Vec::with_capacity(params.num_vars);
+        for (k, r) in x.iter().enumerate() {
+            let half = f.len() >> 1;
+            let q = (0..half)
+                .map(|i| f[2 * i + 1] - f[2 * i])
+                .collect::<Vec<_>>();
+            pi.push(best_multiexp(&q, &params.lagrange_bases[k + 1]).to_affine());
+
+            for i in 0..half {
+                f[i] = f[2 * i] + q[i] * r;
+            }
+            f.truncate(half);
+        }
+        MultilinearKZGOpening { pi }
+    }
+
+    fn verify<H: FiatShamirHash>(
+        params: &Self::Params,
+        commitment: &Self::Commitment,
+        x: &[Fr],
+        y: Fr,
+        opening: &Self::Opening,
+        _transcript: &mut Transcript<H>,
+    ) -> bool {
+        assert_eq!(x.len(), params.num_vars);
+        assert_eq!(opening.pi.len(), params.num_vars);
+
+        // e(C - y g + sum_k r_k pi_k, h) * prod_k e(-pi_k, h^{tau_k}) = 1
+        let mut scalars = vec![Fr::one(), -y];
+        scalars.extend_from_slice(x);
+        let mut bases = vec![commitment.com, params.g];
+        bases.extend_from_slice(&opening.pi);
+        let lhs = best_multiexp(&scalars, &bases).to_affine();
+
+        let neg_pi = opening.pi.iter().map(|p| -*p).collect::<Vec<_>>();
+        let g2_prepared = std::iter::once(params.h)
+            .chain(params.tau_h.iter().cloned())
+            .map(G2Prepared::from)
+            .collect::<Vec<_>>();
+        let terms = std::iter::once(&lhs)
+            .chain(neg_pi.iter())
+            .zip(g2_prepared.iter())
+            .collect::<Vec<_>>();
+
+        MillerLoopResult::final_exponentiation(&Bn256::multi_miller_loop(&terms))
+            .is_identity()
+            .into()
+    }
+
+    fn commitment_size(_params: &Self::Params) -> usize {
+        Self::g1_size()
+    }
+
+    fn opening_size(params: &Self::Params) -> usize {
+        params.num_vars * Self::g1_size()
+    }
+
+    fn serialize_commitment<W: Write>(commitment: &Self::Commitment, writer: W) {
+        Self::write_g1(&commitment.com, writer);
+    }
+
+    fn deserialize_commitment<R: Read>(
+        _params: &Self::Params,
+        reader: R,
+    ) -> Option<Self::Commitment> {
+        Some(MultilinearKZGCommitment {
+            com: Self::read_g1(reader)?,
+        })
+    }
+
+    fn serialize_opening<W: Write>(opening: &Self::Opening, mut writer: W) {
+        opening
+            .pi
+            .iter()
+            .for_each(|p| Self::write_g1(p, &mut writer));
+    }
+
+    fn deserialize_opening<R: Read>(params: &Self::Params, mut reader: R) -> Option<Self::Opening> {
+        Some(MultilinearKZGOpening {
+            pi: (0..params.num_vars)
+                .map(|_| Self::read_g1(&mut reader))
+                .collect::<Option<_>>()?,
+        })
+    }
+}
@@ -0,0 +1,86 @@
+//! A binary Merkle tree over SHA256, shared by the hash based commitment schemes.
+
+use sha2::{Digest, Sha256};
+
+pub type MerkleHash = [u8; 32];
+
+pub const MERKLE_HASH_SIZE: usize = 32;
+
+#[inline]
+pub fn hash_leaf(leaf: &[u8]) -> MerkleHash {
+    Sha256::digest(leaf).into()
+}
+
+#[inline]
+fn hash_children(left: &MerkleHash, right: &MerkleHash) -> MerkleHash {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MerkleTree {
+    /// nodes[1] is the root, the children of nodes[i] are nodes[2i] and nodes[2i+1],
+    /// the leaves are stored from index leaf_num
+    nodes: Vec<MerkleHash>,
+    leaf_num: usize,
+}
+
+impl MerkleTree {
+    /// Build the tree over the hashes of the leaves, the number of leaves must be a power of 2
+    pub fn new(leaf_hashes: Vec<MerkleHash>) -> Self {
+        let leaf_num = leaf_hashes.len();
+        assert!(leaf_num.is_power_of_two());
+
+        let mut nodes = vec![[0u8; MERKLE_HASH_SIZE]; leaf_num];
+        nodes.extend(leaf_hashes);
+        for i in (1..leaf_num).rev() {
+            nodes[i] = hash_children(&nodes[2 * i], &nodes[2 * i + 1]);
+        }
+        Self { nodes, leaf_num }
+    }
+
+    #[inline]
+    pub fn root(&self) -> MerkleHash {
+        self.nodes[1]
+    }
+
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.leaf_num.trailing_zeros() as usize
+    }
+
+    /// The siblings on the path from the leaf to the root
+    pub fn path(&self, leaf_idx: usize) -> Vec<MerkleHash> {
+        let mut idx = leaf_idx + self.leaf_num;
+        let mut path = Vec::with_capacity(self.depth());
+        while idx > 1 {
+            path.push(self.nodes[idx ^ 1]);
+            idx >>= 1;
+        }
+        path
+    }
+
+    pub fn verify_path(
+        root: &MerkleHash,
+        leaf_hash: &MerkleHash,
+        leaf_idx: usize,
+        path: &[MerkleHash],
+    ) -> bool {
+        if leaf_idx >> path.len() != 0 {
+            return false;
+        }
+        let mut idx = leaf_idx;
+        let mut cur = *leaf_hash;
+        for sibling in path {
+            cur = if idx & 1 == 0 {
+                hash_children(&cur, sibling)
+            } else {
+                hash_children(sibling, &cur)
+            };
+            idx >>= 1;
+        }
+        cur == *root
+    }
+}
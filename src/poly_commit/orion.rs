@@ -0,0 +1,475 @@
+//! Orion polynomial commitment, for the M31 configuration.
+//!
+//! The evaluations are arranged as a matrix whose rows are encoded with the linear-time
+//! expander code of Brakedown (Golovnev, Lee, Setty, Thaler, Wahby), which Orion (Xie, Zhang,
+//! Song) builds on, and the columns of the encoded matrix are committed with a Merkle tree.
+//! To open at r = (r_lo, r_hi), the prover sends the combination of the rows by eq(r_hi, .),
+//! together with a random combination of the rows for the proximity test. The verifier
+//! encodes both and checks them against a number of queried columns.
+
+use std::io::{Read, Write};
+
+use arith::{Field, FieldSerde, M31Ext3, SimdField, SimdM31Ext3, M31};
+use rand::{seq::index::sample, Rng, RngCore};
+
+use crate::{
+    eq_evals_at_primitive, hash_leaf, new_opening_transcript, FiatShamirHash, M31ExtConfig,
+    MerkleHash, MerkleTree, PolynomialCommitment, Transcript, MERKLE_HASH_SIZE,
+};
+
+/// The parameters (alpha, beta, r) of the code, from the last row of Figure 2 of Brakedown:
+/// a message of length n is compressed to alpha n entries, and encoded to r n entries.
+pub const ORION_CODE_ALPHA: f64 = 0.238;
+pub const ORION_CODE_BETA: f64 = 0.1205;
+pub const ORION_CODE_R: f64 = 1.72;
+
+/// Relative distance of the code, beta / r. With the degrees of the sparse matrices from
+/// Lemma 1 of Brakedown, the sampled code has it except with probability 2^-100 over the
+/// matrices.
+pub const ORION_CODE_DISTANCE: f64 = ORION_CODE_BETA / ORION_CODE_R;
+
+/// Bits of security of the proximity test for the parameters generated by `setup`, the GKR
+/// configuration sets its own with `setup_with_security_bits`
+pub const ORION_DEFAULT_SECURITY_BITS: usize = 100;
+
+/// Messages up to this length are encoded with Reed-Solomon, as in Brakedown
+const ORION_BASE_CODE_LEN: usize = 20;
+
+/// log2 of the size of the field of the entries of the sparse matrices
+const ORION_LOG_FIELD_SIZE: f64 = 31.0;
+
+/// The binary entropy function
+#[inline]
+fn entropy(x: f64) -> f64 {
+    -x * x.log2() - (1.0 - x) * (1.0 - x).log2()
+}
+
+/// A sparse matrix given by its non zero entries, column by column
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SparseMatrix {
+    out_len: usize,
+    entries: Vec<Vec<(usize, M31)>>,
+}
+
+impl SparseMatrix {
+    /// A matrix with `degree` non zero entries at distinct random positions per column
+    fn random(in_len: usize, out_len: usize, degree: usize, mut rng: impl RngCore) -> Self {
+        let degree = degree.min(out_len);
+        let entries = (0..in_len)
+            .map(|_| {
+                sample(&mut rng, out_len, degree)
+                    .into_iter()
+                    .map(|j| (j, M31::from(rng.gen_range(1..(1u32 << 31) - 1))))
+                    .collect()
+            })
+            .collect();
+        Self { out_len, entries }
+    }
+
+    fn mul(&self, x: &[SimdM31Ext3]) -> Vec<SimdM31Ext3> {
+        let mut res = vec![SimdM31Ext3::zero(); self.out_len];
+        for (x_i, col) in x.iter().zip(self.entries.iter()) {
+            for (j, coef) in col {
+                res[*j] += *x_i * *coef;
+            }
+        }
+        res
+    }
+}
+
+/// The recursive expander code of Brakedown, with rate 1 / r.
+/// A message x of length n is encoded as (x, z, B z) where z = Enc(A x) and A x has
+/// alpha n entries.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrionCode {
+    pub msg_len: usize,
+    /// the matrices (A, B) of each level of the recursion
+    levels: Vec<(SparseMatrix, SparseMatrix)>,
+}
+
+impl OrionCode {
+    pub fn new(msg_len: usize, mut rng: impl RngCore) -> Self {
+        let mut levels = vec![];
+        let mut n = msg_len;
+        while n > ORION_BASE_CODE_LEN {
+            let compressed_len = (ORION_CODE_ALPHA * n as f64).ceil() as usize;
+            let z_len = Self::codeword_len_of(compressed_len);
+            let v_len = Self::codeword_len_of(n) - n - z_len;
+            let a = SparseMatrix::random(n, compressed_len, Self::a_degree(n), &mut rng);
+            let b = SparseMatrix::random(z_len, v_len, Self::b_degree(n), &mut rng);
+            levels.push((a, b));
+            n = compressed_len;
+        }
+        Self { msg_len, levels }
+    }
+
+    #[inline]
+    fn codeword_len_of(msg_len: usize) -> usize {
+        (ORION_CODE_R * msg_len as f64).ceil() as usize
+    }
+
+    /// c_n of Brakedown, the number of non zero entries per column of A
+    fn a_degree(n: usize) -> usize {
+        let (alpha, beta, n) = (ORION_CODE_ALPHA, ORION_CODE_BETA, n as f64);
+        let small = f64::max(1.28 * beta * n, beta * n + 4.0).ceil();
+        let large = ((110.0 / n + entropy(beta) + alpha * entropy(1.28 * beta / alpha))
+            / (beta * (alpha / (1.28 * beta)).log2()))
+        .ceil();
+        small.min(large) as usize
+    }
+
+    /// d_n of Brakedown, the number of non zero entries per column of B
+    fn b_degree(n: usize) -> usize {
+        let (alpha, beta, r, n) = (ORION_CODE_ALPHA, ORION_CODE_BETA, ORION_CODE_R, n as f64);
+        let mu = r - 1.0 - r * alpha;
+        let nu = beta + alpha * beta + 0.03;
+        let small = ((2.0 * beta + (r - 1.0 + 110.0 / n) / ORION_LOG_FIELD_SIZE) * n).ceil();
+        let large = ((r * alpha * entropy(beta / r) + mu * entropy(nu / mu) + 110.0 / n)
+            / (alpha * beta * (mu / nu).log2()))
+        .ceil();
+        small.min(large) as usize
+    }
+
+    #[inline]
+    pub fn codeword_len(&self) -> usize {
+        Self::codeword_len_of(self.msg_len)
+    }
+
+    pub fn encode(&self, msg: &[SimdM31Ext3]) -> Vec<SimdM31Ext3> {
+        assert_eq!(msg.len(), self.msg_len);
+        self.encode_level(msg, 0)
+    }
+
+    fn encode_level(&self, msg: &[SimdM31Ext3], level: usize) -> Vec<SimdM31Ext3> {
+        if level == self.levels.len() {
+            return Self::reed_solomon_encode(msg);
+        }
+        let (a, b) = &self.levels[level];
+        let z = self.encode_level(&a.mul(msg), level + 1);
+        let v = b.mul(&z);
+
+        let mut codeword = msg.to_vec();
+        codeword.extend_from_slice(&z);
+        codeword.extend_from_slice(&v);
+        codeword
+    }
+
+    /// Evaluate the polynomial whose coefficients are the message at 1, 2, ..., r k
+    fn reed_solomon_encode(msg: &[SimdM31Ext3]) -> Vec<SimdM31Ext3> {
+        (1..=Self::codeword_len_of(msg.len()) as u32)
+            .map(|p| {
+                let p = M31::from(p);
+                msg.iter()
+                    .rev()
+                    .fold(SimdM31Ext3::zero(), |acc, c| acc * p + c)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrionParams {
+    pub num_vars: usize,
+    /// the matrix has 2^log_row_num rows of length 2^(num_vars - log_row_num)
+    pub log_row_num: usize,
+    pub query_num: usize,
+    pub code: OrionCode,
+}
+
+impl OrionParams {
+    #[inline]
+    pub fn row_num(&self) -> usize {
+        1 << self.log_row_num
+    }
+
+    #[inline]
+    pub fn msg_len(&self) -> usize {
+        1 << (self.num_vars - self.log_row_num)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OrionCommitment {
+    pub root: MerkleHash,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OrionCommitmentAux {
+    /// the encoded rows
+    pub codewords: Vec<Vec<SimdM31Ext3>>,
+    pub tree: MerkleTree,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrionOpening {
+    /// the rows combined by eq(r_hi, .)
+    pub eval_row: Vec<SimdM31Ext3>,
+    /// the rows combined by random coefficients
+    pub proximity_row: Vec<SimdM31Ext3>,
+    /// the queried columns of the encoded matrix
+    pub columns: Vec<Vec<SimdM31Ext3>>,
+    pub paths: Vec<Vec<MerkleHash>>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OrionPCS;
+
+impl OrionPCS {
+    /// As in the testing phase of Brakedown, a committed matrix that passes the proximity
+    /// test is within a third of the distance of the code, so each query catches an
+    /// inconsistent column with probability at least a third of the distance.
+    fn query_num(security_bits: usize) -> usize {
+        (security_bits as f64 / -(1.0 - ORION_CODE_DISTANCE / 3.0).log2()).ceil() as usize
+    }
+
+    /// Depth of the Merkle tree over the columns, padded to a power of 2
+    #[inline]
+    fn tree_depth(params: &OrionParams) -> usize {
+        params
+            .code
+            .codeword_len()
+            .next_power_of_two()
+            .trailing_zeros() as usize
+    }
+
+    fn column_hash(column: &[SimdM31Ext3]) -> MerkleHash {
+        let mut buffer = vec![];
+        column.iter().for_each(|v| v.serialize_into(&mut buffer));
+        hash_leaf(&buffer)
+    }
+
+    fn combine_rows(
+        poly_vals: &[SimdM31Ext3],
+        coefs: &[M31Ext3],
+        msg_len: usize,
+    ) -> Vec<SimdM31Ext3> {
+        let mut res = vec![SimdM31Ext3::zero(); msg_len];
+        for (row, coef) in poly_vals.chunks(msg_len).zip(coefs.iter()) {
+            for (r, v) in res.iter_mut().zip(row.iter()) {
+                *r += v.scale(coef);
+            }
+        }
+        res
+    }
+
+    fn eq_evals(r: &[M31Ext3]) -> Vec<M31Ext3> {
+        let mut eq_evals = vec![M31Ext3::zero(); 1 << r.len()];
+        eq_evals_at_primitive(r, &M31Ext3::one(), &mut eq_evals);
+        eq_evals
+    }
+
+    fn sample_queries<H: FiatShamirHash>(
+        params: &OrionParams,
+        eval_row: &[SimdM31Ext3],
+        proximity_row: &[SimdM31Ext3],
+        transcript: &mut Transcript<H>,
+    ) -> Vec<usize> {
+        let mut opening_transcript = new_opening_transcript::<M31ExtConfig, H>(transcript);
+        let mut buffer = vec![];
+        eval_row
+            .iter()
+            .chain(proximity_row.iter())
+            .for_each(|v| v.serialize_into(&mut buffer));
+        opening_transcript.append_u8_slice(&buffer);
+
+        opening_transcript
+            .challenge_fs::<M31ExtConfig>(params.query_num)
+            .iter()
+            .map(|c| c.as_u32_unchecked() as usize % params.code.codeword_len())
+            .collect()
+    }
+}
+
+impl PolynomialCommitment<M31ExtConfig> for OrionPCS {
+    type Params = OrionParams;
+    type Commitment = OrionCommitment;
+    type Opening = OrionOpening;
+    type CommitmentAux = OrionCommitmentAux;
+
+    fn setup(num_vars: usize, rng: impl RngCore) -> Self::Params {
+        Self::setup_with_security_bits(num_vars, ORION_DEFAULT_SECURITY_BITS, rng)
+    }
+
+    fn setup_with_security_bits(
+        num_vars: usize,
+        security_bits: usize,
+        rng: impl RngCore,
+    ) -> Self::Params {
+        let query_num = Self::query_num(security_bits);
+        // balance the rows sent in the opening against the queried columns
+        let log_query_num = query_num.next_power_of_two().trailing_zeros() as usize;
+        let log_row_num = ((num_vars + 1).saturating_sub(log_query_num) / 2).min(num_vars);
+        OrionParams {
+            num_vars,
+            log_row_num,
+            query_num,
+            code: OrionCode::new(1 << (num_vars - log_row_num), rng),
+        }
+    }
+
+    fn commit(
+        params: &Self::Params,
+        poly_vals: &[SimdM31Ext3],
+    ) -> (Self::Commitment, Self::CommitmentAux) {
+        assert_eq!(poly_vals.len(), 1 << params.num_vars);
+        let codewords = poly_vals
+            .chunks(params.msg_len())
+            .map(|row| params.code.encode(row))
+            .collect::<Vec<_>>();
+
+        let mut leaf_hashes = (0..params.code.codeword_len())
+            .map(|j| {
+                let column = codewords.iter().map(|row| row[j]).collect::<Vec<_>>();
+                Self::column_hash(&column)
+            })
+            .collect::<Vec<_>>();
+        // the columns are never queried beyond the codeword
+        leaf_hashes.resize(1 << Self::tree_depth(params), [0u8; MERKLE_HASH_SIZE]);
+        let tree = MerkleTree::new(leaf_hashes);
+
+        (
+            OrionCommitment { root: tree.root() },
+            OrionCommitmentAux { codewords, tree },
+        )
+    }
+
+    fn open<H: FiatShamirHash>(
+        params: &Self::Params,
+        poly_vals: &[SimdM31Ext3],
+        aux: &Self::CommitmentAux,
+        x: &[M31Ext3],
+        transcript: &mut Transcript<H>,
+    ) -> Self::Opening {
+        assert_eq!(x.len(), params.num_vars);
+        let msg_len = params.msg_len();
+        let r_hi = &x[params.num_vars - params.log_row_num..];
+
+        let gamma = transcript.challenge_fs::<M31ExtConfig>(params.row_num());
+        let eval_row = Self::combine_rows(poly_vals, &Self::eq_evals(r_hi), msg_len);
+        let proximity_row = Self::combine_rows(poly_vals, &gamma, msg_len);
+
+        let queries = Self::sample_queries(params, &eval_row, &proximity_row, transcript);
+        let columns = queries
+            .iter()
+            .map(|j| aux.codewords.iter().map(|row| row[*j]).collect())
+            .collect();
+        let paths = queries.iter().map(|j| aux.tree.path(*j)).collect();
+
+        OrionOpening {
+            eval_row,
+            proximity_row,
+            columns,
+            paths,
+        }
+    }
+
+    fn verify<H: FiatShamirHash>(
+        params: &Self::Params,
+        commitment: &Self::Commitment,
+        x: &[M31Ext3],
+        y: SimdM31Ext3,
+        opening: &Self::Opening,
+        transcript: &mut Transcript<H>,
+    ) -> bool {
+        assert_eq!(x.len(), params.num_vars);
+        let (r_lo, r_hi) = x.split_at(params.num_vars - params.log_row_num);
+
+        let gamma = transcript.challenge_fs::<M31ExtConfig>(params.row_num());
+        let queries = Self::sample_queries(
+            params,
+            &opening.eval_row,
+            &opening.proximity_row,
+            transcript,
+        );
+
+        let mut verified = y
+            == opening
+                .eval_row
+                .iter()
+                .zip(Self::eq_evals(r_lo).iter())
+                .map(|(v, eq)| v.scale(eq))
+                .sum::<SimdM31Ext3>();
+
+        let eq_hi = Self::eq_evals(r_hi);
+        let encoded_eval_row = params.code.encode(&opening.eval_row);
+        let encoded_proximity_row = params.code.encode(&opening.proximity_row);
+        for ((j, column), path) in queries
+            .iter()
+            .zip(opening.columns.iter())
+            .zip(opening.paths.iter())
+        {
+            verified &=
+                MerkleTree::verify_path(&commitment.root, &Self::column_hash(column), *j, path);
+            verified &= Self::combine_rows(column, &eq_hi, 1)[0] == encoded_eval_row[*j];
+            verified &= Self::combine_rows(column, &gamma, 1)[0] == encoded_proximity_row[*j];
+        }
+        verified
+    }
+
+    fn commitment_size(_params: &Self::Params) -> usize {
+        MERKLE_HASH_SIZE
+    }
+
+    fn opening_size(params: &Self::Params) -> usize {
+        let depth = Self::tree_depth(params);
+        2 * params.msg_len() * SimdM31Ext3::SIZE
+            + params.query_num * (params.row_num() * SimdM31Ext3::SIZE + depth * MERKLE_HASH_SIZE)
+    }
+
+    fn serialize_commitment<W: Write>(commitment: &Self::Commitment, mut writer: W) {
+        writer.write_all(&commitment.root).unwrap();
+    }
+
+    fn deserialize_commitment<R: Read>(
+        _params: &Self::Params,
+        mut reader: R,
+    ) -> Option<Self::Commitment> {
+        let mut root = [0u8; MERKLE_HASH_SIZE];
+        reader.read_exact(&mut root).ok()?;
+        Some(OrionCommitment { root })
+    }
+
+    fn serialize_opening<W: Write>(opening: &Self::Opening, mut writer: W) {
+        opening
+            .eval_row
+            .iter()
+            .chain(opening.proximity_row.iter())
+            .for_each(|v| v.serialize_into(&mut writer));
+        for (column, path) in opening.columns.iter().zip(opening.paths.iter()) {
+            column.iter().for_each(|v| v.serialize_into(&mut writer));
+            path.iter().for_each(|h| writer.write_all(h).unwrap());
+        }
+    }
+
+    fn deserialize_opening<R: Read>(params: &Self::Params, mut reader: R) -> Option<Self::Opening> {
+        let depth = Self::tree_depth(params);
+        let read_vals = |n: usize, reader: &mut R| {
+            (0..n)
+                .map(|_| SimdM31Ext3::try_deserialize_from(&mut *reader))
+                .collect::<Option<Vec<_>>>()
+        };
+        let eval_row = read_vals(params.msg_len(), &mut reader)?;
+        let proximity_row = read_vals(params.msg_len(), &mut reader)?;
+
+        let mut columns = Vec::with_capacity(params.query_num);
+        let mut paths = Vec::with_capacity(params.query_num);
+        for _ in 0..params.query_num {
+            columns.push(read_vals(params.row_num(), &mut reader)?);
+            paths.push(
+                (0..depth)
+                    .map(|_| {
+                        let mut h = [0u8; MERKLE_HASH_SIZE];
+                        reader.read_exact(&mut h).ok()?;
+                        Some(h)
+                    })
+                    .collect::<Option<_>>()?,
+            );
+        }
+        Some(OrionOpening {
+            eval_row,
+            proximity_row,
+            columns,
+            paths,
+        })
+    }
+}
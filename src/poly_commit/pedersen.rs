@@ -0,0 +1,374 @@
+//! Pedersen vector commitments over the bn256 G1 with a blinding factor, for the
+//! zero-knowledge mode of GKR.
+//!
+//! A vector x is committed as C = <x, G> + b H. The inner product y = <x, a> with a public
+//! vector a is proven with the dot-product argument of Hyrax: the prover sends a commitment
+//! C_d = <d, G> + b_d H to a random vector d together with t = <d, a>, and after the
+//! challenge c answers z = c x + d, b_z = c b + b_d. The verifier checks
+//! <z, G> + b_z H = c C + C_d and <z, a> = c y + t.
+//!
+//! The private inputs are committed by rows as in Hyrax, each row with its own blinding
+//! factor, and the mask of the input layer on the generators after the ones of a row. For a
+//! claim with weights s eq(r, .) on the inputs, both parties combine the row commitments by
+//! s eq(r_hi, .), and the argument runs on the combined row and the mask with the weights
+//! eq(r_lo, .), so its proof has the size of a row.
+
+use arith::{Field, FieldSerde};
+use halo2curves::{
+    bn256::{Fr, G1Affine, G1},
+    group::{prime::PrimeCurveAffine, Curve, Group, GroupEncoding},
+    msm::best_multiexp,
+};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+use crate::{
+    input_col_vars, transparent_setup_rng, BN254Config, FiatShamirHash, HyraxPCS,
+    InputClaimWeights, Proof, Transcript, ZKCommitmentForGKR,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PedersenParams {
+    /// one generator per entry of the committed vectors
+    pub generators: Vec<G1Affine>,
+    /// the generator of the blinding factors
+    pub blinding_generator: G1Affine,
+}
+
+impl PedersenParams {
+    pub fn new(max_len: usize, mut rng: impl RngCore) -> Self {
+        let proj_generators = (0..max_len + 1)
+            .map(|_| <G1 as Group>::random(&mut rng))
+            .collect::<Vec<_>>();
+        let mut generators = vec![<G1Affine as PrimeCurveAffine>::identity(); max_len + 1];
+        <G1 as Curve>::batch_normalize(&proj_generators, &mut generators);
+        let blinding_generator = generators.pop().unwrap();
+        Self {
+            generators,
+            blinding_generator,
+        }
+    }
+
+    #[inline]
+    pub fn commit(&self, vals: &[Fr], blinding: &Fr) -> G1 {
+        self.commit_at(0, vals, blinding)
+    }
+
+    /// Commit to `vals` on the generators from `offset`
+    #[inline]
+    pub fn commit_at(&self, offset: usize, vals: &[Fr], blinding: &Fr) -> G1 {
+        assert!(offset + vals.len() <= self.generators.len());
+        best_multiexp(vals, &self.generators[offset..offset + vals.len()])
+            + self.blinding_generator * blinding
+    }
+}
+
+#[inline]
+fn inner_product(x: &[Fr], a: &[Fr]) -> Fr {
+    x.iter().zip(a.iter()).map(|(x, a)| *x * a).sum()
+}
+
+/// A committed vector: rows of `row_len` entries committed one by one, followed by a tail
+/// committed on the generators after the ones of a row. The masks only have a tail.
+#[derive(Debug, Clone)]
+struct Commitment {
+    row_coms: Vec<G1Affine>,
+    row_len: usize,
+    tail_com: G1Affine,
+    tail_len: usize,
+}
+
+/// The committed rows and tail with their blinding factors, only kept by the prover
+#[derive(Debug, Clone)]
+struct Opening {
+    rows: Vec<Vec<Fr>>,
+    row_blindings: Vec<Fr>,
+    tail: Vec<Fr>,
+    tail_blinding: Fr,
+}
+
+/// Pedersen commitments with the state of the vectors committed or read so far.
+pub struct PedersenZK {
+    params: PedersenParams,
+    rng: StdRng,
+    commitments: Vec<Commitment>,
+    openings: Vec<Opening>,
+}
+
+impl PedersenZK {
+    // the generators are derived from a fixed domain, no trusted setup is needed
+    const SETUP_DOMAIN: &'static [u8] = b"expander-rs pedersen zk generators";
+
+    pub fn new(max_len: usize) -> Self {
+        Self::new_with_params(PedersenParams::new(
+            max_len,
+            transparent_setup_rng(Self::SETUP_DOMAIN),
+        ))
+    }
+
+    pub fn new_with_params(params: PedersenParams) -> Self {
+        Self {
+            params,
+            rng: StdRng::from_entropy(),
+            commitments: vec![],
+            openings: vec![],
+        }
+    }
+
+    /// Commit to the rows and the tail, append the commitments to the transcript and return
+    /// the index
+    fn commit_rows<H: FiatShamirHash>(
+        &mut self,
+        rows: Vec<Vec<Fr>>,
+        row_len: usize,
+        tail: &[Fr],
+        transcript: &mut Transcript<H>,
+    ) -> usize {
+        let row_blindings = rows
+            .iter()
+            .map(|_| Fr::random_unsafe(&mut self.rng))
+            .collect::<Vec<_>>();
+        let tail_blinding = Fr::random_unsafe(&mut self.rng);
+        let mut proj_coms = rows
+            .iter()
+            .zip(row_blindings.iter())
+            .map(|(row, blinding)| self.params.commit(row, blinding))
+            .collect::<Vec<_>>();
+        proj_coms.push(self.params.commit_at(row_len, tail, &tail_blinding));
+        let mut coms = vec![<G1Affine as PrimeCurveAffine>::identity(); proj_coms.len()];
+        <G1 as Curve>::batch_normalize(&proj_coms, &mut coms);
+        coms.iter()
+            .for_each(|com| transcript.append_u8_slice(com.to_bytes().as_ref()));
+
+        let tail_com = coms.pop().unwrap();
+        self.commitments.push(Commitment {
+            row_coms: coms,
+            row_len,
+            tail_com,
+            tail_len: tail.len(),
+        });
+        self.openings.push(Opening {
+            rows,
+            row_blindings,
+            tail: tail.to_vec(),
+            tail_blinding,
+        });
+        self.commitments.len() - 1
+    }
+
+    /// Read the commitments to `row_num` rows and the tail from the proof
+    fn read_rows<H: FiatShamirHash>(
+        &mut self,
+        row_num: usize,
+        row_len: usize,
+        tail_len: usize,
+        proof: &mut Proof,
+        transcript: &mut Transcript<H>,
+    ) -> Option<usize> {
+        let mut coms = (0..row_num + 1)
+            .map(|_| {
+                let bytes = proof.get_next_bytes_and_step(HyraxPCS::g1_size())?;
+                transcript.append_u8_slice(bytes);
+                HyraxPCS::read_g1(bytes)
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let tail_com = coms.pop().unwrap();
+        self.commitments.push(Commitment {
+            row_coms: coms,
+            row_len,
+            tail_com,
+            tail_len,
+        });
+        Some(self.commitments.len() - 1)
+    }
+
+    /// The weights of the rows of the input commitment `idx` and of the combined row and
+    /// the mask for a claim
+    fn input_claim_weights(&self, idx: usize, weights: &InputClaimWeights<Fr>) -> [Vec<Fr>; 2] {
+        let row_len = self.commitments[idx].row_len;
+        let (r_lo, r_hi) = weights
+            .rz_private
+            .split_at(row_len.trailing_zeros() as usize);
+        let row_weights = HyraxPCS::eq_evals(r_hi)
+            .iter()
+            .map(|e| *e * weights.scale)
+            .collect();
+        let mut a = HyraxPCS::eq_evals(r_lo);
+        a.extend_from_slice(&weights.mask);
+        [row_weights, a]
+    }
+
+    /// Prove <(sum_i u_i row_i, tail), a> for the commitment `idx` with the dot-product
+    /// argument
+    fn prove_combined<H: FiatShamirHash>(
+        &mut self,
+        idx: usize,
+        u: &[Fr],
+        a: &[Fr],
+        transcript: &mut Transcript<H>,
+    ) {
+        let opening = &self.openings[idx];
+        assert_eq!(u.len(), opening.rows.len());
+        let mut x = vec![Fr::zero(); self.commitments[idx].row_len];
+        for (row, u) in opening.rows.iter().zip(u.iter()) {
+            x.iter_mut().zip(row.iter()).for_each(|(x, v)| *x += *v * u);
+        }
+        x.extend_from_slice(&opening.tail);
+        let blinding = inner_product(&opening.row_blindings, u) + opening.tail_blinding;
+        assert_eq!(x.len(), a.len());
+
+        let d = (0..x.len())
+            .map(|_| Fr::random_unsafe(&mut self.rng))
+            .collect::<Vec<_>>();
+        let blinding_d = Fr::random_unsafe(&mut self.rng);
+        let com_d = self.params.commit(&d, &blinding_d).to_affine();
+        transcript.append_u8_slice(com_d.to_bytes().as_ref());
+        transcript.append_f::<BN254Config>(inner_product(&d, a));
+
+        let c = transcript.challenge_f::<BN254Config>();
+        x.iter()
+            .zip(d.iter())
+            .for_each(|(x, d)| transcript.append_f::<BN254Config>(c * x + d));
+        transcript.append_f::<BN254Config>(c * blinding + blinding_d);
+    }
+
+    /// Read the dot-product proof of <(sum_i u_i row_i, tail), a> = y for the commitment
+    /// `idx` and check it, None if the proof is truncated or malformed
+    fn verify_combined<H: FiatShamirHash>(
+        &self,
+        idx: usize,
+        u: &[Fr],
+        a: &[Fr],
+        y: Fr,
+        proof: &mut Proof,
+        transcript: &mut Transcript<H>,
+    ) -> Option<bool> {
+        let com = &self.commitments[idx];
+        assert_eq!(u.len(), com.row_coms.len());
+        assert_eq!(com.row_len + com.tail_len, a.len());
+        let combined_com = best_multiexp(u, &com.row_coms) + com.tail_com;
+
+        let bytes = proof.get_next_bytes_and_step(HyraxPCS::g1_size())?;
+        let com_d = HyraxPCS::read_g1(bytes)?;
+        transcript.append_u8_slice(bytes);
+        let t: Fr = proof.get_next_and_step()?;
+        transcript.append_f::<BN254Config>(t);
+
+        let c = transcript.challenge_f::<BN254Config>();
+        let z = (0..a.len())
+            .map(|_| {
+                let z: Fr = proof.get_next_and_step()?;
+                transcript.append_f::<BN254Config>(z);
+                Some(z)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let blinding_z: Fr = proof.get_next_and_step()?;
+        transcript.append_f::<BN254Config>(blinding_z);
+
+        Some(
+            self.params.commit(&z, &blinding_z) == combined_com * c + com_d
+                && inner_product(&z, a) == c * y + t,
+        )
+    }
+}
+
+impl<H: FiatShamirHash> ZKCommitmentForGKR<BN254Config, H> for PedersenZK {
+    fn commit(&mut self, vals: &[Fr], transcript: &mut Transcript<H>) -> usize {
+        self.commit_rows(vec![], 0, vals, transcript)
+    }
+
+    fn prove_inner_product(&mut self, idx: usize, a: &[Fr], transcript: &mut Transcript<H>) {
+        self.prove_combined(idx, &[], a, transcript);
+    }
+
+    fn commitment_size(&self) -> usize {
+        HyraxPCS::g1_size()
+    }
+
+    fn inner_product_proof_size(&self, len: usize) -> usize {
+        // the commitment to d, <d, a>, then z and its blinding factor
+        HyraxPCS::g1_size() + (len + 2) * Fr::SIZE
+    }
+
+    fn read_commitment(
+        &mut self,
+        len: usize,
+        proof: &mut Proof,
+        transcript: &mut Transcript<H>,
+    ) -> Option<usize> {
+        self.read_rows(0, 0, len, proof, transcript)
+    }
+
+    fn verify_inner_product(
+        &mut self,
+        idx: usize,
+        a: &[Fr],
+        y: Fr,
+        proof: &mut Proof,
+        transcript: &mut Transcript<H>,
+    ) -> bool {
+        self.verify_combined(idx, &[], a, y, proof, transcript)
+            .unwrap_or(false)
+    }
+
+    fn commit_input(
+        &mut self,
+        inputs: &[Fr],
+        mask: &[Fr],
+        transcript: &mut Transcript<H>,
+    ) -> usize {
+        assert!(inputs.len().is_power_of_two());
+        let row_len = 1 << input_col_vars(inputs.len().trailing_zeros() as usize);
+        let rows = inputs.chunks(row_len).map(|row| row.to_vec()).collect();
+        self.commit_rows(rows, row_len, mask, transcript)
+    }
+
+    fn prove_input_claim(
+        &mut self,
+        idx: usize,
+        weights: &InputClaimWeights<Fr>,
+        transcript: &mut Transcript<H>,
+    ) {
+        let [u, a] = self.input_claim_weights(idx, weights);
+        self.prove_combined(idx, &u, &a, transcript);
+    }
+
+    fn input_commitment_size(&self, num_vars: usize) -> usize {
+        ((1 << (num_vars - input_col_vars(num_vars))) + 1) * HyraxPCS::g1_size()
+    }
+
+    fn input_claim_proof_size(&self, num_vars: usize, mask_len: usize) -> usize {
+        // the dot-product proof on the combined row and the mask
+        HyraxPCS::g1_size() + ((1 << input_col_vars(num_vars)) + mask_len + 2) * Fr::SIZE
+    }
+
+    fn read_input_commitment(
+        &mut self,
+        num_vars: usize,
+        mask_len: usize,
+        proof: &mut Proof,
+        transcript: &mut Transcript<H>,
+    ) -> Option<usize> {
+        let col_vars = input_col_vars(num_vars);
+        self.read_rows(
+            1 << (num_vars - col_vars),
+            1 << col_vars,
+            mask_len,
+            proof,
+            transcript,
+        )
+    }
+
+    fn verify_input_claim(
+        &mut self,
+        idx: usize,
+        weights: &InputClaimWeights<Fr>,
+        y: Fr,
+        proof: &mut Proof,
+        transcript: &mut Transcript<H>,
+    ) -> bool {
+        let [u, a] = self.input_claim_weights(idx, weights);
+        self.verify_combined(idx, &u, &a, y, proof, transcript)
+            .unwrap_or(false)
+    }
+}
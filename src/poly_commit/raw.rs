@@ -4,9 +4,11 @@
 use std::io::{Read, Write};
 
 use arith::{Field, FieldSerde, MultiLinearPoly};
+use rand::RngCore;
 
-use crate::GKRConfig;
+use crate::{FiatShamirHash, GKRConfig, PolynomialCommitment, Transcript};
 
+#[derive(Debug, Clone, Default)]
 pub struct RawOpening {}
 
 pub struct RawCommitment<C: GKRConfig> {
@@ -27,12 +29,12 @@ impl<C: GKRConfig> RawCommitment<C> {
     }
 
     #[inline]
-    pub fn deserialize_from<R: Read>(mut reader: R, poly_size: usize) -> Self {
+    pub fn deserialize_from<R: Read>(mut reader: R, poly_size: usize) -> Option<Self> {
         let poly_vals = (0..poly_size)
-            .map(|_| C::Field::deserialize_from(&mut reader))
-            .collect();
+            .map(|_| C::Field::try_deserialize_from(&mut reader))
+            .collect::<Option<_>>()?;
 
-        RawCommitment { poly_vals }
+        Some(RawCommitment { poly_vals })
     }
 }
 
@@ -47,3 +49,78 @@ impl<C: GKRConfig> RawCommitment<C> {
         y == MultiLinearPoly::<C::Field>::eval_multilinear(&self.poly_vals, x)
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RawParams {
+    pub num_vars: usize,
+}
+
+/// The RAW scheme: the commitment is the polynomial itself and openings are empty.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RawPCS;
+
+impl<C: GKRConfig> PolynomialCommitment<C> for RawPCS {
+    type Params = RawParams;
+    type Commitment = RawCommitment<C>;
+    type Opening = RawOpening;
+    type CommitmentAux = ();
+
+    fn setup(num_vars: usize, _rng: impl RngCore) -> Self::Params {
+        RawParams { num_vars }
+    }
+
+    fn commit(
+        params: &Self::Params,
+        poly_vals: &[C::Field],
+    ) -> (Self::Commitment, Self::CommitmentAux) {
+        assert_eq!(poly_vals.len(), 1 << params.num_vars);
+        (RawCommitment::new(poly_vals.to_vec()), ())
+    }
+
+    fn open<H: FiatShamirHash>(
+        _params: &Self::Params,
+        _poly_vals: &[C::Field],
+        _aux: &Self::CommitmentAux,
+        _x: &[C::ChallengeField],
+        _transcript: &mut Transcript<H>,
+    ) -> Self::Opening {
+        // the verifier evaluates the polynomial by itself
+        RawOpening {}
+    }
+
+    fn verify<H: FiatShamirHash>(
+        _params: &Self::Params,
+        commitment: &Self::Commitment,
+        x: &[C::ChallengeField],
+        y: C::Field,
+        _opening: &Self::Opening,
+        _transcript: &mut Transcript<H>,
+    ) -> bool {
+        commitment.verify(x, y)
+    }
+
+    fn commitment_size(params: &Self::Params) -> usize {
+        (1 << params.num_vars) * C::Field::SIZE
+    }
+
+    fn opening_size(_params: &Self::Params) -> usize {
+        0
+    }
+
+    fn serialize_commitment<W: Write>(commitment: &Self::Commitment, writer: W) {
+        commitment.serialize_into(writer);
+    }
+
+    fn deserialize_commitment<R: Read>(
+        params: &Self::Params,
+        reader: R,
+    ) -> Option<Self::Commitment> {
+        RawCommitment::deserialize_from(reader, 1 << params.num_vars)
+    }
+
+    fn serialize_opening<W: Write>(_opening: &Self::Opening, _writer: W) {}
+
+    fn deserialize_opening<R: Read>(_params: &Self::Params, _reader: R) -> Option<Self::Opening> {
+        Some(RawOpening {})
+    }
+}
@@ -1,6 +1,9 @@
 pub mod proof;
 pub use proof::*;
 
+pub mod structured_proof;
+pub use structured_proof::*;
+
 pub mod fiat_shamir;
 pub use fiat_shamir::*;
 
@@ -13,12 +16,18 @@ pub(crate) use sumcheck_helper::*;
 pub mod sumcheck;
 pub use sumcheck::*;
 
+pub mod zk;
+pub use zk::*;
+
 pub mod gkr;
 pub use gkr::*;
 
 pub mod gkr_square;
 pub use gkr_square::*;
 
+pub mod batch_gkr;
+pub use batch_gkr::*;
+
 pub mod linear_gkr;
 pub use linear_gkr::*;
 
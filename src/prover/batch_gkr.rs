@@ -0,0 +1,273 @@
+//! This module implements the data-parallel GKR IOP over the instances of a batch.
+
+use arith::{Field, MultiLinearPoly, SimdField};
+use ark_std::{end_timer, start_timer};
+
+use crate::{
+    eq_evals_at_primitive, sumcheck_prove_gkr_layer_with_inputs,
+    sumcheck_prove_gkr_square_layer_with_inputs, BatchCircuit, CircuitLayer, FiatShamirHash,
+    GKRConfig, GKRScheme, GkrScratchpad, Transcript, GATE_TYPE_POW1, GATE_TYPE_POW5,
+};
+
+/// The gates of a layer weighted by the claims on its outputs: the coefficient of a gate
+/// times alpha eq(rz0, o) + beta eq(rz1, o) at its output o
+struct GateWeights<C: GKRConfig> {
+    mul: Vec<C::ChallengeField>,
+    add: Vec<C::ChallengeField>,
+    uni: Vec<C::ChallengeField>,
+}
+
+impl<C: GKRConfig> GateWeights<C> {
+    fn new(
+        layer: &CircuitLayer<C>,
+        rz0: &[C::ChallengeField],
+        rz1: &[C::ChallengeField],
+        alpha: &C::ChallengeField,
+        beta: &C::ChallengeField,
+    ) -> Self {
+        let mut eq_evals_at_rz0 = vec![C::ChallengeField::zero(); 1 << rz0.len()];
+        let mut eq_evals_at_rz1 = vec![C::ChallengeField::zero(); 1 << rz1.len()];
+        eq_evals_at_primitive(rz0, alpha, &mut eq_evals_at_rz0);
+        eq_evals_at_primitive(rz1, beta, &mut eq_evals_at_rz1);
+        let weight = |o_id: usize, coef: &C::CircuitField| {
+            C::challenge_mul_circuit_field(&(eq_evals_at_rz0[o_id] + eq_evals_at_rz1[o_id]), coef)
+        };
+
+        Self {
+            mul: layer.mul.iter().map(|g| weight(g.o_id, &g.coef)).collect(),
+            add: layer.add.iter().map(|g| weight(g.o_id, &g.coef)).collect(),
+            uni: layer.uni.iter().map(|g| weight(g.o_id, &g.coef)).collect(),
+        }
+    }
+}
+
+/// The values of an input at the points 0, 1, ..., D - 1 of the line through v0 and v1
+#[inline]
+fn line_evals<F: Field, const D: usize>(v0: F, v1: F) -> [F; D] {
+    let mut evals = [v0; D];
+    let delta = v1 - v0;
+    for i in 1..D {
+        evals[i] = evals[i - 1] + delta;
+    }
+    evals
+}
+
+/// The weighted gates of the layer, the ones of the scheme, evaluated at the points of the
+/// line through the inputs `v0` and `v1` of two instances
+#[allow(clippy::needless_range_loop)]
+fn gate_evals_on_line<C: GKRConfig, const D: usize>(
+    layer: &CircuitLayer<C>,
+    gkr_scheme: &GKRScheme,
+    weights: &GateWeights<C>,
+    v0: &[C::Field],
+    v1: &[C::Field],
+) -> [C::Field; D] {
+    let mut evals = [C::Field::zero(); D];
+    let line = |i: usize| line_evals::<C::Field, D>(v0[i], v1[i]);
+    match gkr_scheme {
+        GKRScheme::Vanilla => {
+            for (g, w) in layer.mul.iter().zip(weights.mul.iter()) {
+                let (x, y) = (line(g.i_ids[0]), line(g.i_ids[1]));
+                for t in 0..D {
+                    evals[t] += (x[t] * y[t]).scale(w);
+                }
+            }
+            for (g, w) in layer.add.iter().zip(weights.add.iter()) {
+                let x = line(g.i_ids[0]);
+                for t in 0..D {
+                    evals[t] += x[t].scale(w);
+                }
+            }
+        }
+        GKRScheme::GkrSquare => {
+            for (g, w) in layer.uni.iter().zip(weights.uni.iter()) {
+                let x = line(g.i_ids[0]);
+                for t in 0..D {
+                    let v = match g.gate_type {
+                        GATE_TYPE_POW5 => x[t].square().square() * x[t],
+                        GATE_TYPE_POW1 => x[t],
+                        _ => panic!("Unsupported gate type"),
+                    };
+                    evals[t] += v.scale(w);
+                }
+            }
+        }
+    }
+    evals
+}
+
+/// The sumcheck over the instance variables of the sum over the instances k of eq(rk, k)
+/// times the weighted gates of the layer evaluated with the inputs of k. The round
+/// polynomials have degree D - 1, they are sent as their evaluations at 0, 1, ..., D - 1.
+///
+/// Returns the challenges rk', the inputs of the layer folded at rk' and eq(rk, rk').
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::needless_range_loop)]
+fn sumcheck_prove_instance_vars<C: GKRConfig, H: FiatShamirHash, const D: usize>(
+    layer: &CircuitLayer<C>,
+    gkr_scheme: &GKRScheme,
+    instance_vals: &[Vec<C::Field>],
+    rz0: &[C::ChallengeField],
+    rz1: &[C::ChallengeField],
+    alpha: &C::ChallengeField,
+    beta: &C::ChallengeField,
+    rk: &[C::ChallengeField],
+    transcript: &mut Transcript<H>,
+) -> (Vec<C::ChallengeField>, Vec<C::Field>, C::ChallengeField) {
+    let weights = GateWeights::new(layer, rz0, rz1, alpha, beta);
+    let mut eq_evals_at_rk = vec![C::ChallengeField::zero(); instance_vals.len()];
+    eq_evals_at_primitive(rk, &C::ChallengeField::one(), &mut eq_evals_at_rk);
+
+    let mut rk_next = vec![];
+    let mut folded_vals: Vec<Vec<C::Field>> = vec![];
+    for i_var in 0..rk.len() {
+        let vals: &[Vec<C::Field>] = if i_var == 0 {
+            instance_vals
+        } else {
+            &folded_vals
+        };
+        let half = vals.len() >> 1;
+
+        let mut evals = [C::Field::zero(); D];
+        for k in 0..half {
+            let gate_evals = gate_evals_on_line::<C, D>(
+                layer,
+                gkr_scheme,
+                &weights,
+                &vals[2 * k],
+                &vals[2 * k + 1],
+            );
+            let eq_evals = line_evals::<C::ChallengeField, D>(
+                eq_evals_at_rk[2 * k],
+                eq_evals_at_rk[2 * k + 1],
+            );
+            for t in 0..D {
+                evals[t] += gate_evals[t].scale(&eq_evals[t]);
+            }
+        }
+        evals.iter().for_each(|e| transcript.append_f::<C>(*e));
+        let r = transcript.challenge_f::<C>();
+        log::trace!("instance i_var={} evals: {:?} r: {:?}", i_var, evals, r);
+
+        folded_vals = (0..half)
+            .map(|k| {
+                vals[2 * k]
+                    .iter()
+                    .zip(vals[2 * k + 1].iter())
+                    .map(|(v0, v1)| *v0 + (*v1 - *v0).scale(&r))
+                    .collect()
+            })
+            .collect();
+        for k in 0..half {
+            eq_evals_at_rk[k] =
+                eq_evals_at_rk[2 * k] + (eq_evals_at_rk[2 * k + 1] - eq_evals_at_rk[2 * k]) * r;
+        }
+        rk_next.push(r);
+    }
+
+    let folded_vals = match folded_vals.pop() {
+        Some(vals) => vals,
+        // a single instance
+        None => instance_vals[0].clone(),
+    };
+    (rk_next, folded_vals, eq_evals_at_rk[0])
+}
+
+/// Prove the layers of all the instances of a batch. The claims on the input layer are
+/// returned as their points, the input variables followed by the instance variables: two
+/// claims for the vanilla GKR, one for GKR^2. The zero-knowledge mode does not support
+/// batches.
+pub fn batch_gkr_prove<C: GKRConfig, H: FiatShamirHash>(
+    batch: &BatchCircuit<C>,
+    gkr_scheme: &GKRScheme,
+    sp: &mut GkrScratchpad<C>,
+    transcript: &mut Transcript<H>,
+) -> (C::Field, Vec<Vec<C::ChallengeField>>) {
+    let timer = start_timer!(|| "batch gkr prove");
+    let circuit = &batch.circuit;
+    let layer_num = circuit.layers.len();
+
+    let mut rz0 = vec![];
+    let mut rz1 = vec![];
+    for _i in 0..circuit.layers.last().unwrap().output_var_num {
+        rz0.push(transcript.challenge_f::<C>());
+        rz1.push(C::ChallengeField::zero());
+    }
+    let mut rk = vec![];
+    for _i in 0..batch.instance_var_num() {
+        rk.push(transcript.challenge_f::<C>());
+    }
+
+    let mut alpha = C::ChallengeField::one();
+    let mut beta = C::ChallengeField::zero();
+
+    let claimed_v = MultiLinearPoly::<C::Field>::eval_multilinear(
+        &batch.output_vals.concat(),
+        &[rz0.as_slice(), rk.as_slice()].concat(),
+    );
+
+    for i in (0..layer_num).rev() {
+        let layer = &circuit.layers[i];
+        let instance_vals = &batch.layer_vals[i];
+        let (rk_next, vals, eq_rk) = match gkr_scheme {
+            // the gates have degree 2 in the instance variables, eq makes it 3
+            GKRScheme::Vanilla => sumcheck_prove_instance_vars::<C, H, 4>(
+                layer,
+                gkr_scheme,
+                instance_vals,
+                &rz0,
+                &rz1,
+                &alpha,
+                &beta,
+                &rk,
+                transcript,
+            ),
+            // the pow5 gates and eq
+            GKRScheme::GkrSquare => sumcheck_prove_instance_vars::<C, H, 7>(
+                layer,
+                gkr_scheme,
+                instance_vals,
+                &rz0,
+                &rz0,
+                &C::ChallengeField::one(),
+                &C::ChallengeField::zero(),
+                &rk,
+                transcript,
+            ),
+        };
+
+        // the sumcheck of a single instance on the folded inputs, for the claims scaled by
+        // eq(rk, rk')
+        match gkr_scheme {
+            GKRScheme::Vanilla => {
+                (rz0, rz1) = sumcheck_prove_gkr_layer_with_inputs(
+                    layer,
+                    &vals,
+                    &rz0,
+                    &rz1,
+                    &(alpha * eq_rk),
+                    &(beta * eq_rk),
+                    transcript,
+                    sp,
+                    None,
+                );
+                alpha = transcript.challenge_f::<C>();
+                beta = transcript.challenge_f::<C>();
+            }
+            GKRScheme::GkrSquare => {
+                rz0 = sumcheck_prove_gkr_square_layer_with_inputs(
+                    layer, &vals, &rz0, &eq_rk, transcript, sp,
+                );
+            }
+        }
+        rk = rk_next;
+        log::trace!("Layer {} of the batch proved", i);
+    }
+
+    let rzs = match gkr_scheme {
+        GKRScheme::Vanilla => vec![[rz0, rk.clone()].concat(), [rz1, rk].concat()],
+        GKRScheme::GkrSquare => vec![[rz0, rk].concat()],
+    };
+    end_timer!(timer);
+    (claimed_v, rzs)
+}
@@ -1,33 +1,37 @@
 use arith::{Field, FieldSerde};
 
-use crate::{GKRConfig, Proof, SHA256hasher};
+use crate::{FiatShamirHash, GKRConfig, Proof, SHA256hasher};
 
-pub struct Transcript {
-    pub hasher: SHA256hasher,
-    hash_start_idx: usize,
-    digest: [u8; Self::DIGEST_SIZE],
+const DIGEST_SIZE: usize = 32;
+
+/// Fiat-Shamir transcript over the hash `H`. The messages are appended to the proof and
+/// absorbed into the input of the next hash, and each challenge hashes the previous digest
+/// together with the messages absorbed since.
+#[derive(Clone)]
+pub struct Transcript<H: FiatShamirHash = SHA256hasher> {
+    pub hasher: H,
+    hash_input: Vec<u8>,
+    digest: [u8; DIGEST_SIZE],
     pub proof: Proof,
 }
 
-impl Default for Transcript {
+impl<H: FiatShamirHash> Default for Transcript<H> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl Transcript {
-    pub const DIGEST_SIZE: usize = 32;
+impl<H: FiatShamirHash> Transcript<H> {
+    pub const DIGEST_SIZE: usize = DIGEST_SIZE;
 
     #[inline]
     fn hash_to_digest(&mut self) {
-        let hash_end_idx = self.proof.bytes.len();
-        if hash_end_idx > self.hash_start_idx {
-            self.hasher.hash(
-                &mut self.digest,
-                &self.proof.bytes[self.hash_start_idx..],
-                hash_end_idx - self.hash_start_idx,
-            );
-            self.hash_start_idx = hash_end_idx;
+        if !self.hash_input.is_empty() {
+            // chain the new messages to the previous digest, so that the challenges depend
+            // on the whole transcript
+            let mut input = self.digest.to_vec();
+            input.append(&mut self.hash_input);
+            self.hasher.hash(&mut self.digest, &input, input.len());
         } else {
             self.hasher
                 .hash_inplace(&mut self.digest, Self::DIGEST_SIZE)
@@ -37,9 +41,9 @@ impl Transcript {
     #[inline]
     pub fn new() -> Self {
         Transcript {
-            hasher: SHA256hasher,
-            hash_start_idx: 0,
-            digest: [0u8; Self::DIGEST_SIZE],
+            hasher: H::default(),
+            hash_input: vec![],
+            digest: [0u8; DIGEST_SIZE],
             proof: Proof::default(),
         }
     }
@@ -49,18 +53,32 @@ impl Transcript {
         let cur_size = self.proof.bytes.len();
         self.proof.bytes.resize(cur_size + C::Field::SIZE, 0);
         f.serialize_into(&mut self.proof.bytes[cur_size..]);
+        self.absorb_f::<C>(&f);
     }
 
     #[inline]
     pub fn append_u8_slice(&mut self, buffer: &[u8]) {
         self.proof.bytes.extend_from_slice(buffer);
+        self.absorb_u8_slice(buffer);
+    }
+
+    /// Absorb a field element known to both parties, without adding it to the proof
+    #[inline]
+    pub fn absorb_f<C: GKRConfig>(&mut self, f: &C::Field) {
+        self.hasher.absorb_f::<C>(&mut self.hash_input, f);
+    }
+
+    /// Absorb bytes known to both parties, without adding them to the proof
+    #[inline]
+    pub fn absorb_u8_slice(&mut self, buffer: &[u8]) {
+        self.hasher.absorb_bytes(&mut self.hash_input, buffer);
     }
 
     #[inline]
     pub fn challenge_f<C: GKRConfig>(&mut self) -> C::ChallengeField {
         self.hash_to_digest();
         assert!(C::ChallengeField::SIZE <= Self::DIGEST_SIZE);
-        C::ChallengeField::from_uniform_bytes(&self.digest)
+        self.hasher.squeeze_f::<C>(&self.digest)
     }
 
     #[inline]
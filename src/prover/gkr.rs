@@ -3,14 +3,21 @@
 use arith::{Field, MultiLinearPoly};
 use ark_std::{end_timer, start_timer};
 
-use crate::{sumcheck_prove_gkr_layer, Circuit, GKRConfig, GkrScratchpad, Transcript};
+use crate::{
+    sumcheck_prove_gkr_layer, Circuit, FiatShamirHash, GKRConfig, GkrScratchpad, InputMask,
+    SumcheckZKMasks, Transcript, ZKCommitmentForGKR,
+};
 
+/// Prove the layers of the circuit. In the zero-knowledge mode, `zk` holds the hiding
+/// commitments and the masks of the inputs of all the layers, the mask of layer i being
+/// committed at index i for i > 0.
 // FIXME
 #[allow(clippy::type_complexity)]
-pub fn gkr_prove<C: GKRConfig>(
+pub fn gkr_prove<C: GKRConfig, H: FiatShamirHash>(
     circuit: &Circuit<C>,
     sp: &mut GkrScratchpad<C>,
-    transcript: &mut Transcript,
+    transcript: &mut Transcript<H>,
+    mut zk: Option<(&mut dyn ZKCommitmentForGKR<C, H>, &[InputMask<C>])>,
 ) -> (C::Field, Vec<C::ChallengeField>, Vec<C::ChallengeField>) {
     let timer = start_timer!(|| "gkr prove");
     let layer_num = circuit.layers.len();
@@ -31,6 +38,11 @@ pub fn gkr_prove<C: GKRConfig>(
     );
 
     for i in (0..layer_num).rev() {
+        let zk_masks = zk.as_mut().map(|(zk_com, masks)| SumcheckZKMasks {
+            zk_com: &mut **zk_com as &mut dyn ZKCommitmentForGKR<C, H>,
+            input_mask: &masks[i],
+            output_mask: (i + 1 < layer_num).then(|| (&masks[i + 1], i + 1)),
+        });
         (rz0, rz1) = sumcheck_prove_gkr_layer(
             &circuit.layers[i],
             &rz0,
@@ -39,6 +51,7 @@ pub fn gkr_prove<C: GKRConfig>(
             &beta,
             transcript,
             sp,
+            zk_masks,
         );
         alpha = transcript.challenge_f::<C>();
         beta = transcript.challenge_f::<C>();
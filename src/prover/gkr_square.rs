@@ -4,12 +4,14 @@
 use arith::MultiLinearPoly;
 use ark_std::{end_timer, start_timer};
 
-use crate::{sumcheck_prove_gkr_square_layer, Circuit, GKRConfig, GkrScratchpad, Transcript};
+use crate::{
+    sumcheck_prove_gkr_square_layer, Circuit, FiatShamirHash, GKRConfig, GkrScratchpad, Transcript,
+};
 
-pub fn gkr_square_prove<C: GKRConfig>(
+pub fn gkr_square_prove<C: GKRConfig, H: FiatShamirHash>(
     circuit: &Circuit<C>,
     sp: &mut GkrScratchpad<C>,
-    transcript: &mut Transcript,
+    transcript: &mut Transcript<H>,
 ) -> (C::Field, Vec<C::ChallengeField>) {
     let timer = start_timer!(|| "gkr^2 prove");
     let layer_num = circuit.layers.len();
@@ -1,17 +1,119 @@
 //! This module implements the whole GKR prover, including the IOP and PCS.
 
+use arith::MultiLinearPoly;
 use ark_std::{end_timer, start_timer};
 
 use crate::{
-    gkr_prove, gkr_square_prove, Circuit, Config, GKRConfig, GKRScheme, GkrScratchpad, Proof,
-    RawCommitment, Transcript,
+    batch_gkr_prove, gkr_prove, gkr_square_prove, input_claim_weights, new_pcs_for_gkr,
+    new_zk_commitment_for_gkr, random_input_masks, Anemoihasher, BatchCircuit, Circuit, Config,
+    FiatShamirHash, FiatShamirHashType, GKRConfig, GKRScheme, GkrScratchpad, Keccak256hasher,
+    MiMC5hasher, PCSForGKR, PoseidonM31hasher, Proof, SHA256hasher, Soundness, Transcript,
+    ZKCommitmentForGKR, ZKSupportError,
 };
 
+/// Start the transcript with the statement: the configuration, the parameters of a trusted
+/// setup, the circuit and the public inputs, so that the challenges depend on what is being
+/// proven.
+pub(crate) fn new_statement_transcript<C: GKRConfig, H: FiatShamirHash>(
+    circuit: &Circuit<C>,
+    config: &Config<C>,
+    pcs: &dyn PCSForGKR<C, H>,
+) -> Transcript<H> {
+    new_transcript_for_statement(config, pcs, &circuit.digest(), circuit.public_input_vals())
+}
+
+/// Same as `new_statement_transcript` for a batch, with the public inputs of all the
+/// instances
+pub(crate) fn new_batch_statement_transcript<C: GKRConfig, H: FiatShamirHash>(
+    batch: &BatchCircuit<C>,
+    config: &Config<C>,
+    pcs: &dyn PCSForGKR<C, H>,
+) -> Transcript<H> {
+    new_transcript_for_statement(config, pcs, &batch.digest(), &batch.public_input_vals())
+}
+
+fn new_transcript_for_statement<C: GKRConfig, H: FiatShamirHash>(
+    config: &Config<C>,
+    pcs: &dyn PCSForGKR<C, H>,
+    circuit_digest: &[u8],
+    public_inputs: &[C::Field],
+) -> Transcript<H> {
+    let mut transcript = Transcript::<H>::new();
+    transcript.absorb_u8_slice(&config.fingerprint());
+    if let Some(digest) = pcs.params_digest() {
+        transcript.absorb_u8_slice(&digest);
+    }
+    transcript.absorb_u8_slice(circuit_digest);
+    public_inputs
+        .iter()
+        .for_each(|v| transcript.absorb_f::<C>(v));
+    transcript
+}
+
+/// Log the soundness of the proof, with a warning if it is below the target of the
+/// configuration
+fn log_soundness<C: GKRConfig>(soundness: &Soundness, config: &Config<C>) {
+    log::info!("soundness: {:.1} bits", soundness.achieved_bits());
+    if soundness.achieved_bits() < config.security_bits as f64 {
+        log::warn!(
+            "the proof reaches {:.1} bits of security out of the {} targeted, the challenge \
+             field is too small for the circuit",
+            soundness.achieved_bits(),
+            config.security_bits
+        );
+    }
+}
+
+/// Open the commitment to the private inputs at the claims on the input layer. When the
+/// circuit has public inputs, the claim on the private inputs comes first, the verifier
+/// adds the public ones.
+fn open_input_claims<C: GKRConfig, H: FiatShamirHash>(
+    pcs: &mut dyn PCSForGKR<C, H>,
+    circuit: &Circuit<C>,
+    private_input_vals: &[C::Field],
+    rzs: &[Vec<C::ChallengeField>],
+    transcript: &mut Transcript<H>,
+) {
+    for rz in rzs.iter() {
+        let rz_private = circuit.private_input_point(rz);
+        if circuit.public_input_offset.is_some() {
+            let private_v =
+                MultiLinearPoly::<C::Field>::eval_multilinear(private_input_vals, &rz_private);
+            transcript.append_f::<C>(private_v);
+        }
+        pcs.open(private_input_vals, &rz_private, transcript);
+    }
+}
+
+/// Number of zero bits at the start of the bytes, from the most significant bit of each byte
 #[cfg(feature = "grinding")]
-pub(crate) fn grind<C: GKRConfig>(transcript: &mut Transcript, config: &Config<C>) {
-    use arith::{Field, FieldSerde};
+#[inline]
+fn leading_zero_bits(bytes: &[u8]) -> usize {
+    let mut bits = 0;
+    for b in bytes {
+        bits += b.leading_zeros() as usize;
+        if *b != 0 {
+            break;
+        }
+    }
+    bits
+}
 
-    let timer = start_timer!(|| format!("grind {} bits", config.grinding_bits));
+/// Hash of the transcript state with a grinding nonce
+#[cfg(feature = "grinding")]
+#[inline]
+fn grinding_hash<H: FiatShamirHash>(hasher: &H, seed: &[u8], nonce: u64) -> [u8; 32] {
+    let mut input = seed.to_vec();
+    hasher.absorb_bytes(&mut input, &nonce.to_le_bytes());
+    let mut output = [0u8; 32];
+    hasher.hash(&mut output, &input, input.len());
+    output
+}
+
+/// A seed drawn from the transcript for the grinding nonce
+#[cfg(feature = "grinding")]
+fn grinding_seed<C: GKRConfig, H: FiatShamirHash>(transcript: &mut Transcript<H>) -> Vec<u8> {
+    use arith::{Field, FieldSerde};
 
     let mut hash_bytes = vec![];
 
@@ -25,14 +127,44 @@ pub(crate) fn grind<C: GKRConfig>(transcript: &mut Transcript, config: &Config<C
 
     assert!(hash_bytes.len() >= 32, "hash len: {}", hash_bytes.len());
     hash_bytes.truncate(32);
+    hash_bytes
+}
 
-    for _ in 0..(1 << config.grinding_bits) {
-        transcript.hasher.hash_inplace(&mut hash_bytes, 32);
-    }
-    transcript.append_u8_slice(&hash_bytes[..32]);
+/// Search for a nonce whose hash with the transcript state starts with `grinding_bits`
+/// zero bits, and append it to the proof. It takes 2^grinding_bits hashes in expectation.
+#[cfg(feature = "grinding")]
+pub(crate) fn grind<C: GKRConfig, H: FiatShamirHash>(
+    transcript: &mut Transcript<H>,
+    grinding_bits: usize,
+) {
+    let timer = start_timer!(|| format!("grind {} bits", grinding_bits));
+
+    let seed = grinding_seed::<C, H>(transcript);
+    let nonce = (0..u64::MAX)
+        .find(|nonce| {
+            leading_zero_bits(&grinding_hash(&transcript.hasher, &seed, *nonce)) >= grinding_bits
+        })
+        .unwrap();
+    transcript.append_u8_slice(&nonce.to_le_bytes());
     end_timer!(timer);
 }
 
+/// Read the grinding nonce from the proof and check it with a single hash
+#[cfg(feature = "grinding")]
+pub(crate) fn verify_grinding<C: GKRConfig, H: FiatShamirHash>(
+    transcript: &mut Transcript<H>,
+    proof: &mut Proof,
+    grinding_bits: usize,
+) -> bool {
+    let seed = grinding_seed::<C, H>(transcript);
+    let Some(nonce_bytes) = proof.get_next_bytes_and_step(8) else {
+        return false;
+    };
+    let nonce = u64::from_le_bytes(nonce_bytes.try_into().unwrap());
+    transcript.append_u8_slice(nonce_bytes);
+    leading_zero_bits(&grinding_hash(&transcript.hasher, &seed, nonce)) >= grinding_bits
+}
+
 pub struct Prover<C: GKRConfig> {
     config: Config<C>,
     sp: GkrScratchpad<C>,
@@ -50,11 +182,6 @@ impl<C: GKRConfig> Default for Prover<C> {
 impl<C: GKRConfig> Prover<C> {
     pub fn new(config: &Config<C>) -> Self {
         // assert_eq!(config.field_type, crate::config::FieldType::M31);
-        assert_eq!(config.fs_hash, crate::config::FiatShamirHashType::SHA256);
-        assert_eq!(
-            config.polynomial_commitment_type,
-            crate::config::PolynomialCommitmentType::Raw
-        );
         Prover {
             config: config.clone(),
             sp: GkrScratchpad::default(),
@@ -76,39 +203,186 @@ impl<C: GKRConfig> Prover<C> {
         self.sp = GkrScratchpad::<C>::new(max_num_input_var, max_num_output_var);
     }
 
+    /// Prove the circuit with the transcript selected by `Config::fs_hash`. Panics if the
+    /// zero-knowledge mode is asked for a circuit or configuration it does not support, see
+    /// `try_prove`.
     pub fn prove(&mut self, c: &Circuit<C>) -> (C::Field, Proof) {
+        self.try_prove(c).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Prove the circuit with the transcript selected by `Config::fs_hash`, or return why the
+    /// zero-knowledge mode is not supported for the circuit and configuration
+    pub fn try_prove(&mut self, c: &Circuit<C>) -> Result<(C::Field, Proof), ZKSupportError> {
+        match self.config.fs_hash {
+            FiatShamirHashType::SHA256 => self.prove_with_hash::<SHA256hasher>(c),
+            FiatShamirHashType::Keccak256 => self.prove_with_hash::<Keccak256hasher>(c),
+            FiatShamirHashType::Poseidon => self.prove_with_hash::<PoseidonM31hasher>(c),
+            FiatShamirHashType::MIMC7 => self.prove_with_hash::<MiMC5hasher>(c),
+            FiatShamirHashType::Animoe => self.prove_with_hash::<Anemoihasher>(c),
+        }
+    }
+
+    /// Prove all the instances of `batch` with a single proof, see `BatchCircuit`. The
+    /// memory is prepared for the circuit of one instance `batch.circuit`. Panics in the
+    /// zero-knowledge mode, which does not support batches.
+    pub fn prove_batch(&mut self, batch: &BatchCircuit<C>) -> (C::Field, Proof) {
+        self.try_prove_batch(batch)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Prove all the instances of `batch` with a single proof, or fail in the zero-knowledge
+    /// mode
+    pub fn try_prove_batch(
+        &mut self,
+        batch: &BatchCircuit<C>,
+    ) -> Result<(C::Field, Proof), ZKSupportError> {
+        if self.config.zk {
+            return Err(ZKSupportError::UnsupportedBatch);
+        }
+        Ok(match self.config.fs_hash {
+            FiatShamirHashType::SHA256 => self.prove_batch_with_hash::<SHA256hasher>(batch),
+            FiatShamirHashType::Keccak256 => self.prove_batch_with_hash::<Keccak256hasher>(batch),
+            FiatShamirHashType::Poseidon => self.prove_batch_with_hash::<PoseidonM31hasher>(batch),
+            FiatShamirHashType::MIMC7 => self.prove_batch_with_hash::<MiMC5hasher>(batch),
+            FiatShamirHashType::Animoe => self.prove_batch_with_hash::<Anemoihasher>(batch),
+        })
+    }
+
+    fn prove_batch_with_hash<H: FiatShamirHash>(
+        &mut self,
+        batch: &BatchCircuit<C>,
+    ) -> (C::Field, Proof) {
+        let timer = start_timer!(|| "prove batch");
+
+        // the private inputs of all the instances are committed together
+        let mut pcs = new_pcs_for_gkr::<C, H>(&self.config, batch.log_private_input_size());
+        let input_vals = batch.private_input_vals();
+        let soundness = Soundness::new_batch(batch, &self.config);
+        log_soundness(&soundness, &self.config);
+
+        let mut transcript =
+            new_batch_statement_transcript::<C, H>(batch, &self.config, pcs.as_ref());
+        pcs.commit(&input_vals, &mut transcript);
+
+        #[cfg(feature = "grinding")]
+        if self.config.grinding_bits > 0 {
+            grind::<C, H>(&mut transcript, self.config.grinding_bits);
+        }
+
+        // the random coefficients are shared by the instances, which are evaluated again
+        let filled_batch = batch.circuit.has_rand_coef().then(|| {
+            let mut batch = batch.clone();
+            batch.circuit.fill_rand_coef(&mut transcript);
+            batch.evaluate();
+            batch
+        });
+        let batch = filled_batch.as_ref().unwrap_or(batch);
+
+        let (claimed_v, rzs) = batch_gkr_prove(
+            batch,
+            &self.config.gkr_scheme,
+            &mut self.sp,
+            &mut transcript,
+        );
+        open_input_claims(
+            pcs.as_mut(),
+            &batch.circuit,
+            &input_vals,
+            &rzs,
+            &mut transcript,
+        );
+
+        end_timer!(timer);
+        let mut proof = transcript.proof;
+        proof.soundness = Some(soundness);
+        (claimed_v, proof)
+    }
+
+    fn prove_with_hash<H: FiatShamirHash>(
+        &mut self,
+        c: &Circuit<C>,
+    ) -> Result<(C::Field, Proof), ZKSupportError> {
         let timer = start_timer!(|| "prove");
         // std::thread::sleep(std::time::Duration::from_secs(1)); // TODO
 
-        // PC commit
-        let commitment = RawCommitment::<C>::new(c.layers[0].input_vals.evals.clone());
+        let mut pcs = new_pcs_for_gkr::<C, H>(&self.config, c.log_private_input_size());
+        // only the private inputs are committed, the verifier evaluates the public ones
+        let input_vals = c.private_input_vals();
+        let soundness = Soundness::new(c, &self.config);
+        log_soundness(&soundness, &self.config);
 
-        let mut buffer = vec![];
-        commitment.serialize_into(&mut buffer);
-        let mut transcript = Transcript::new();
-        transcript.append_u8_slice(&buffer);
+        // PC commit
+        let mut transcript = new_statement_transcript::<C, H>(c, &self.config, pcs.as_ref());
+        let mut zk_com = None;
+        let mut input_masks = vec![];
+        if self.config.zk {
+            // the private inputs are committed together with the mask of the input layer,
+            // followed by the masks of the other layers
+            let mut com = new_zk_commitment_for_gkr::<C, H>(c, &self.config)?;
+            input_masks = random_input_masks::<C>(c.layers.len());
+            com.commit_input(input_vals, &input_masks[0], &mut transcript);
+            input_masks[1..].iter().for_each(|mask| {
+                com.commit(mask, &mut transcript);
+            });
+            zk_com = Some(com);
+        } else {
+            pcs.commit(input_vals, &mut transcript);
+        }
 
+        // the nonce is only in the proof when the header asks for proof of work
         #[cfg(feature = "grinding")]
-        grind::<C>(&mut transcript, &self.config);
+        if self.config.grinding_bits > 0 {
+            grind::<C, H>(&mut transcript, self.config.grinding_bits);
+        }
 
-        let claimed_v: C::Field;
-        let mut _rz0s = vec![];
-        let mut _rz1s = vec![];
+        // the random coefficients are only known once the witness is committed, the layers
+        // are evaluated again with them
+        let filled_circuit = c.has_rand_coef().then(|| {
+            let mut circuit = c.clone();
+            circuit.fill_rand_coef(&mut transcript);
+            circuit.evaluate();
+            circuit
+        });
+        let c = filled_circuit.as_ref().unwrap_or(c);
 
+        // the claims on the input layer
+        let claimed_v;
+        let mut rzs = vec![];
         if self.config.gkr_scheme == GKRScheme::GkrSquare {
-            (claimed_v, _rz0s) = gkr_square_prove(c, &mut self.sp, &mut transcript);
+            let rz0s;
+            (claimed_v, rz0s) = gkr_square_prove(c, &mut self.sp, &mut transcript);
+            rzs.push(rz0s);
         } else {
-            (claimed_v, _rz0s, _rz1s) = gkr_prove(c, &mut self.sp, &mut transcript);
+            let (rz0s, rz1s);
+            (claimed_v, rz0s, rz1s) = gkr_prove(
+                c,
+                &mut self.sp,
+                &mut transcript,
+                zk_com.as_mut().map(|com| {
+                    (
+                        com.as_mut() as &mut dyn ZKCommitmentForGKR<C, H>,
+                        input_masks.as_slice(),
+                    )
+                }),
+            );
+            rzs.push(rz0s);
+            rzs.push(rz1s);
         }
 
         // open
-        match self.config.polynomial_commitment_type {
-            crate::config::PolynomialCommitmentType::Raw => {
-                // no need to update transcript
+        match zk_com.as_mut() {
+            Some(com) => {
+                // the claims V'(rz) are proven without revealing the inputs
+                for rz in rzs.iter() {
+                    com.prove_input_claim(0, &input_claim_weights(c, rz), &mut transcript);
+                }
             }
-            _ => todo!(),
+            None => open_input_claims(pcs.as_mut(), c, input_vals, &rzs, &mut transcript),
         }
+
         end_timer!(timer);
-        (claimed_v, transcript.proof)
+        let mut proof = transcript.proof;
+        proof.soundness = Some(soundness);
+        Ok((claimed_v, proof))
     }
 }
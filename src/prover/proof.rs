@@ -1,12 +1,214 @@
-use std::io::{Read, Write};
+use std::{
+    fmt,
+    io::{Read, Write},
+};
 
 use arith::{Field, FieldSerde};
 
+use crate::{
+    Circuit, Config, FiatShamirHashType, FieldType, GKRConfig, GKRScheme, PolynomialCommitmentType,
+    Soundness, StructuredProof,
+};
+
+/// Magic bytes at the start of an encoded proof
+pub const PROOF_MAGIC: [u8; 4] = *b"EXPR";
+
+/// Version of the proof encoding, bumped whenever the layout of the proof changes
+pub const PROOF_VERSION: u16 = 3;
+
+/// Errors when decoding or parsing a proof
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProofFormatError {
+    /// the bytes do not start with `PROOF_MAGIC`
+    BadMagic,
+    /// the proof was encoded with another version of the format
+    UnsupportedVersion(u16),
+    /// a code of the header is not known, with the name of the header field
+    UnknownTag(&'static str, u8),
+    /// the header does not match the configuration, with the name of the header field
+    ConfigMismatch(&'static str),
+    /// the proof ends in the middle of the named section
+    Truncated(&'static str),
+    /// the named section holds bytes that are not the canonical encoding of a field element
+    NonCanonical(&'static str),
+    /// the number of bytes left after the last section
+    TrailingBytes(usize),
+    /// the proof cannot be parsed for this configuration
+    Unsupported(&'static str),
+    /// the sections of the proof are not the ones of the circuit
+    CircuitMismatch,
+}
+
+impl fmt::Display for ProofFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofFormatError::BadMagic => write!(f, "not an expander proof"),
+            ProofFormatError::UnsupportedVersion(v) => {
+                write!(f, "unsupported proof version {}", v)
+            }
+            ProofFormatError::UnknownTag(name, tag) => write!(f, "unknown {} code {}", name, tag),
+            ProofFormatError::ConfigMismatch(name) => {
+                write!(
+                    f,
+                    "the {} of the proof does not match the configuration",
+                    name
+                )
+            }
+            ProofFormatError::Truncated(section) => write!(f, "the proof ends in the {}", section),
+            ProofFormatError::NonCanonical(section) => {
+                write!(f, "invalid field element in the {}", section)
+            }
+            ProofFormatError::TrailingBytes(n) => write!(f, "{} bytes after the proof", n),
+            ProofFormatError::Unsupported(what) => write!(f, "{} is not supported", what),
+            ProofFormatError::CircuitMismatch => {
+                write!(f, "the sections of the proof do not match the circuit")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProofFormatError {}
+
+/// Header of an encoded proof: the version of the format, the proof system the proof
+/// was made with and its security parameters, so that a proof can be inspected without
+/// knowing its configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofHeader {
+    pub version: u16,
+    pub field_type: FieldType,
+    pub gkr_scheme: GKRScheme,
+    pub polynomial_commitment_type: PolynomialCommitmentType,
+    pub fs_hash: FiatShamirHashType,
+    pub zk: bool,
+    /// Bits of the challenge field, see `Config::field_size`
+    pub field_size: usize,
+    /// The targeted bits of security, see `Config::security_bits`
+    pub security_bits: usize,
+    /// Bits of proof of work required from the prover, zero without the grinding feature
+    pub grinding_bits: usize,
+}
+
+impl ProofHeader {
+    /// The magic, the version in little endian, one byte per code and the security
+    /// parameters as u16 in little endian
+    pub const SIZE: usize = 4 + 2 + 5 + 3 * 2;
+
+    /// The header of a proof made with `config`
+    pub fn new<C: GKRConfig>(config: &Config<C>) -> Self {
+        Self {
+            version: PROOF_VERSION,
+            field_type: C::FIELD_TYPE,
+            gkr_scheme: config.gkr_scheme.clone(),
+            polynomial_commitment_type: config.polynomial_commitment_type.clone(),
+            fs_hash: config.fs_hash.clone(),
+            zk: config.zk,
+            field_size: config.field_size,
+            security_bits: config.security_bits,
+            #[cfg(feature = "grinding")]
+            grinding_bits: config.grinding_bits,
+            #[cfg(not(feature = "grinding"))]
+            grinding_bits: 0,
+        }
+    }
+
+    /// Check that the proof was made with the proof system of `config`
+    pub fn check<C: GKRConfig>(&self, config: &Config<C>) -> Result<(), ProofFormatError> {
+        let expected = Self::new(config);
+        if self.field_type != expected.field_type {
+            return Err(ProofFormatError::ConfigMismatch("field"));
+        }
+        if self.gkr_scheme != expected.gkr_scheme {
+            return Err(ProofFormatError::ConfigMismatch("scheme"));
+        }
+        if self.polynomial_commitment_type != expected.polynomial_commitment_type {
+            return Err(ProofFormatError::ConfigMismatch("polynomial commitment"));
+        }
+        if self.fs_hash != expected.fs_hash {
+            return Err(ProofFormatError::ConfigMismatch("hash"));
+        }
+        if self.zk != expected.zk {
+            return Err(ProofFormatError::ConfigMismatch("zero-knowledge mode"));
+        }
+        if self.field_size != expected.field_size {
+            return Err(ProofFormatError::ConfigMismatch("field size"));
+        }
+        if self.security_bits != expected.security_bits {
+            return Err(ProofFormatError::ConfigMismatch("security level"));
+        }
+        if self.grinding_bits != expected.grinding_bits {
+            return Err(ProofFormatError::ConfigMismatch("grinding bits"));
+        }
+        Ok(())
+    }
+
+    pub fn serialize_into<W: Write>(&self, mut writer: W) {
+        writer.write_all(&PROOF_MAGIC).unwrap();
+        writer.write_all(&self.version.to_le_bytes()).unwrap();
+        writer
+            .write_all(&[
+                self.field_type.tag(),
+                self.gkr_scheme.tag(),
+                self.polynomial_commitment_type.tag(),
+                self.fs_hash.tag(),
+                self.zk as u8,
+            ])
+            .unwrap();
+        for (name, value) in [
+            ("field size", self.field_size),
+            ("security level", self.security_bits),
+            ("grinding bits", self.grinding_bits),
+        ] {
+            let value = u16::try_from(value)
+                .unwrap_or_else(|_| panic!("the {} does not fit in the header", name));
+            writer.write_all(&value.to_le_bytes()).unwrap();
+        }
+    }
+
+    pub fn deserialize_from<R: Read>(mut reader: R) -> Result<Self, ProofFormatError> {
+        let mut bytes = [0u8; Self::SIZE];
+        reader
+            .read_exact(&mut bytes)
+            .map_err(|_| ProofFormatError::Truncated("header"))?;
+        if bytes[..4] != PROOF_MAGIC {
+            return Err(ProofFormatError::BadMagic);
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != PROOF_VERSION {
+            return Err(ProofFormatError::UnsupportedVersion(version));
+        }
+        let zk = match bytes[10] {
+            0 => false,
+            1 => true,
+            tag => return Err(ProofFormatError::UnknownTag("zero-knowledge mode", tag)),
+        };
+        let param = |i: usize| u16::from_le_bytes([bytes[11 + 2 * i], bytes[12 + 2 * i]]) as usize;
+        Ok(Self {
+            version,
+            field_type: FieldType::from_tag(bytes[6])
+                .ok_or(ProofFormatError::UnknownTag("field", bytes[6]))?,
+            gkr_scheme: GKRScheme::from_tag(bytes[7])
+                .ok_or(ProofFormatError::UnknownTag("scheme", bytes[7]))?,
+            polynomial_commitment_type: PolynomialCommitmentType::from_tag(bytes[8]).ok_or(
+                ProofFormatError::UnknownTag("polynomial commitment", bytes[8]),
+            )?,
+            fs_hash: FiatShamirHashType::from_tag(bytes[9])
+                .ok_or(ProofFormatError::UnknownTag("hash", bytes[9]))?,
+            zk,
+            field_size: param(0),
+            security_bits: param(1),
+            grinding_bits: param(2),
+        })
+    }
+}
+
 /// Proof. In the serialized mode.
 #[derive(Debug, Clone, Default)]
 pub struct Proof {
     idx: usize,
     pub bytes: Vec<u8>,
+    /// The round-by-round soundness of the proof, set by the prover and not serialized.
+    /// The verifier derives it from the circuit and the configuration.
+    pub soundness: Option<Soundness>,
 }
 
 impl Proof {
@@ -20,11 +222,63 @@ impl Proof {
         self.idx += size;
     }
 
+    /// The next `size` bytes, or None if the proof ends before them
+    #[inline(always)]
+    pub fn get_next_bytes_and_step(&mut self, size: usize) -> Option<&[u8]> {
+        let start = self.idx;
+        let end = start.checked_add(size)?;
+        if end > self.bytes.len() {
+            return None;
+        }
+        self.step(size);
+        Some(&self.bytes[start..end])
+    }
+
+    /// The next field element, or None if the proof ends before it or its bytes are not a
+    /// canonical encoding
     #[inline(always)]
-    pub fn get_next_and_step<F: Field + FieldSerde>(&mut self) -> F {
-        let ret = F::deserialize_from(&self.bytes[self.idx..]);
+    pub fn get_next_and_step<F: Field + FieldSerde>(&mut self) -> Option<F> {
+        let ret = F::try_deserialize_from(self.bytes.get(self.idx..)?)?;
         self.step(F::SIZE);
-        ret
+        Some(ret)
+    }
+
+    /// Canonical encoding of a proof made for `circuit` with `config`, the one of its typed
+    /// form, see `StructuredProof::serialize_into`. Fails if the proof does not follow the
+    /// layout of the circuit.
+    pub fn serialize_with_header<C: GKRConfig, W: Write>(
+        &self,
+        circuit: &Circuit<C>,
+        config: &Config<C>,
+        writer: W,
+    ) -> Result<(), ProofFormatError> {
+        StructuredProof::parse(self, circuit, config)?.serialize_into(writer);
+        Ok(())
+    }
+
+    /// Decode a proof for `circuit` in the canonical encoding, checking its header against
+    /// `config`. The sections must be the ones of the circuit, so that a proof has a single
+    /// encoding.
+    pub fn deserialize_with_header<C: GKRConfig, R: Read>(
+        circuit: &Circuit<C>,
+        config: &Config<C>,
+        mut reader: R,
+    ) -> Result<Self, ProofFormatError> {
+        let header = ProofHeader::deserialize_from(&mut reader)?;
+        header.check(config)?;
+        let structured = StructuredProof::<C>::deserialize_sections(header, reader)?;
+        let proof = structured.to_proof();
+
+        // the bytes split along the circuit give back the same sections
+        let mut encoding = vec![];
+        structured.serialize_into(&mut encoding);
+        let mut expected = vec![];
+        StructuredProof::parse_with_header(structured.header, &proof, circuit, config)?
+            .serialize_into(&mut expected);
+        if encoding != expected {
+            return Err(ProofFormatError::CircuitMismatch);
+        }
+        Ok(proof)
     }
 }
 
@@ -48,6 +302,22 @@ impl FieldSerde for Proof {
         Self {
             idx: 0,
             bytes: proof,
+            soundness: None,
+        }
+    }
+
+    #[inline(always)]
+    fn try_deserialize_from<R: Read>(mut reader: R) -> Option<Self> {
+        // the length is not trusted, the bytes are read up to it
+        let proof_len = u64::try_deserialize_from(&mut reader)?;
+        let mut proof = vec![];
+        match reader.take(proof_len).read_to_end(&mut proof) {
+            Ok(n) if n as u64 == proof_len => Some(Self {
+                idx: 0,
+                bytes: proof,
+                soundness: None,
+            }),
+            _ => None,
         }
     }
 
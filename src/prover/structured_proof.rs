@@ -0,0 +1,536 @@
+//! Typed proof. The bytes of a `Proof` are the messages of the prover in the order the
+//! verifier reads them. `StructuredProof` splits them into the commitment, the sumcheck
+//! messages of every layer and the openings, from the shape of the circuit and the proof
+//! header, and it is what gets encoded: every section carries its own length, so that the
+//! encoding is parsed without the circuit.
+
+use std::io::{Read, Write};
+
+use arith::{Field, FieldSerde};
+
+use crate::{
+    libra_mask_len, new_pcs_for_gkr, new_zk_commitment_for_gkr, sumcheck_degree, Circuit, Config,
+    GKRConfig, GKRScheme, Proof, ProofFormatError, ProofHeader, SHA256hasher, INPUT_MASK_LEN,
+};
+
+/// Number of evaluations sent per sumcheck round
+fn round_evals_num(gkr_scheme: &GKRScheme, zk: bool) -> usize {
+    sumcheck_degree(gkr_scheme, zk) + 1
+}
+
+/// The messages of the masks of a layer in the zero-knowledge mode, see `prover::zk`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerZKProof<F> {
+    /// The serialized commitment to the mask g of the sumcheck, sent before the rounds
+    pub mask_commitment: Vec<u8>,
+    /// The sum of g over the hypercube
+    pub mask_sum: F,
+    /// The evaluations of the round polynomial of w at 0, 1, 2, 3, sent after vy_claim
+    pub w_round: Vec<F>,
+    /// The evaluations of the mask of the outputs at rz0 and rz1 with their serialized
+    /// inner product proofs, empty for the output layer
+    pub output_mask_evals: Vec<(F, Vec<u8>)>,
+    /// The evaluation of g at the challenges of the sumcheck
+    pub mask_eval: F,
+    /// The serialized inner product proof of `mask_eval`
+    pub mask_eval_proof: Vec<u8>,
+}
+
+/// The sumcheck of a layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerProof<F> {
+    /// The evaluations of the round polynomials at 0, 1, ..., one entry per round
+    pub rounds: Vec<Vec<F>>,
+    /// The claim on the input layer at rx, sent after the rounds over x
+    pub vx_claim: F,
+    /// The claim on the input layer at ry, None for GKR^2
+    pub vy_claim: Option<F>,
+    /// The messages of the masks, in the zero-knowledge mode
+    pub zk: Option<LayerZKProof<F>>,
+}
+
+/// The opening of the input commitment for a claim on the input layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpeningProof<F> {
+    /// The claim on the private inputs, sent when the circuit has public inputs outside of
+    /// the zero-knowledge mode
+    pub private_v: Option<F>,
+    /// The serialized opening of the polynomial commitment, or the inner product proof of
+    /// the claim in the zero-knowledge mode
+    pub opening: Vec<u8>,
+}
+
+/// A proof split into its messages
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructuredProof<C: GKRConfig> {
+    pub header: ProofHeader,
+    /// The serialized commitment to the private inputs. In the zero-knowledge mode, the
+    /// hiding commitments to the private inputs with the mask of the input layer and to the
+    /// masks of the other layers, one after the other.
+    pub commitment: Vec<u8>,
+    /// The grinding nonce, present when the header asks for grinding bits
+    pub grinding_nonce: Option<u64>,
+    /// The layers in the order they are proven, the output layer first
+    pub layers: Vec<LayerProof<C::Field>>,
+    /// The openings for the claims on the input layer, two in the vanilla scheme
+    pub openings: Vec<OpeningProof<C::Field>>,
+}
+
+struct SectionReader<'a> {
+    bytes: &'a [u8],
+    idx: usize,
+}
+
+impl<'a> SectionReader<'a> {
+    fn take(&mut self, size: usize, section: &'static str) -> Result<&'a [u8], ProofFormatError> {
+        if self.bytes.len() - self.idx < size {
+            return Err(ProofFormatError::Truncated(section));
+        }
+        self.idx += size;
+        Ok(&self.bytes[self.idx - size..self.idx])
+    }
+
+    fn take_f<F: Field + FieldSerde>(
+        &mut self,
+        section: &'static str,
+    ) -> Result<F, ProofFormatError> {
+        F::try_deserialize_from(self.take(F::SIZE, section)?)
+            .ok_or(ProofFormatError::NonCanonical(section))
+    }
+}
+
+/// Writes the sections of the encoding, the lengths as u64 in little endian
+struct EncodingWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> EncodingWriter<W> {
+    fn len(&mut self, len: usize) {
+        (len as u64).serialize_into(&mut self.writer);
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.len(bytes.len());
+        self.writer.write_all(bytes).unwrap();
+    }
+
+    fn f<F: FieldSerde>(&mut self, f: &F) {
+        f.serialize_into(&mut self.writer);
+    }
+}
+
+/// Reads the sections of the encoding. The lengths are not trusted: nothing is allocated
+/// before the bytes it holds are read.
+struct EncodingReader<R: Read> {
+    reader: R,
+}
+
+impl<R: Read> EncodingReader<R> {
+    fn exact<const N: usize>(
+        &mut self,
+        section: &'static str,
+    ) -> Result<[u8; N], ProofFormatError> {
+        let mut bytes = [0u8; N];
+        self.reader
+            .read_exact(&mut bytes)
+            .map_err(|_| ProofFormatError::Truncated(section))?;
+        Ok(bytes)
+    }
+
+    fn len(&mut self, section: &'static str) -> Result<u64, ProofFormatError> {
+        Ok(u64::from_le_bytes(self.exact(section)?))
+    }
+
+    fn bytes(&mut self, section: &'static str) -> Result<Vec<u8>, ProofFormatError> {
+        let len = self.len(section)?;
+        let mut bytes = vec![];
+        match (&mut self.reader).take(len).read_to_end(&mut bytes) {
+            Ok(n) if n as u64 == len => Ok(bytes),
+            _ => Err(ProofFormatError::Truncated(section)),
+        }
+    }
+
+    fn f<F: Field + FieldSerde>(&mut self, section: &'static str) -> Result<F, ProofFormatError> {
+        let mut bytes = vec![0u8; F::SIZE];
+        self.reader
+            .read_exact(&mut bytes)
+            .map_err(|_| ProofFormatError::Truncated(section))?;
+        F::try_deserialize_from(&bytes[..]).ok_or(ProofFormatError::NonCanonical(section))
+    }
+
+    fn fs<F: Field + FieldSerde>(
+        &mut self,
+        num: usize,
+        section: &'static str,
+    ) -> Result<Vec<F>, ProofFormatError> {
+        (0..num).map(|_| self.f(section)).collect()
+    }
+
+    fn flag(&mut self, section: &'static str) -> Result<bool, ProofFormatError> {
+        match self.exact::<1>(section)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(ProofFormatError::UnknownTag(section, tag)),
+        }
+    }
+}
+
+impl<C: GKRConfig> StructuredProof<C> {
+    /// Split the proof made with `config` for `circuit`, when its header is not at hand.
+    /// The header is derived from the circuit and the configuration.
+    pub fn parse(
+        proof: &Proof,
+        circuit: &Circuit<C>,
+        config: &Config<C>,
+    ) -> Result<Self, ProofFormatError> {
+        let header = ProofHeader::new(config);
+        Self::parse_with_header(header, proof, circuit, config)
+    }
+
+    /// Split the proof with `header` made with `config` for `circuit`. The layout follows
+    /// the header, once it is checked against the circuit and the configuration. All the
+    /// bytes of the proof must belong to a section.
+    pub fn parse_with_header(
+        header: ProofHeader,
+        proof: &Proof,
+        circuit: &Circuit<C>,
+        config: &Config<C>,
+    ) -> Result<Self, ProofFormatError> {
+        header.check(config)?;
+
+        // the sizes of the commitments and the openings do not depend on the hash
+        let pcs = new_pcs_for_gkr::<C, SHA256hasher>(config, circuit.log_private_input_size());
+        let zk_com = match header.zk {
+            true => Some(
+                new_zk_commitment_for_gkr::<C, SHA256hasher>(circuit, config)
+                    .map_err(|_| ProofFormatError::Unsupported("zero-knowledge mode"))?,
+            ),
+            false => None,
+        };
+        let evals_num = round_evals_num(&header.gkr_scheme, header.zk);
+        let mut reader = SectionReader {
+            bytes: &proof.bytes,
+            idx: 0,
+        };
+
+        let commitment = match &zk_com {
+            Some(com) => reader.take(
+                com.input_commitment_size(circuit.log_private_input_size())
+                    + (circuit.layers.len() - 1) * com.commitment_size(),
+                "commitment",
+            ),
+            None => reader.take(pcs.commitment_size(), "commitment"),
+        }?
+        .to_vec();
+
+        let grinding_nonce = match header.grinding_bits {
+            0 => None,
+            _ => Some(u64::from_le_bytes(
+                reader.take(8, "grinding nonce")?.try_into().unwrap(),
+            )),
+        };
+
+        let mut layers = vec![];
+        for (i, layer) in circuit.layers.iter().enumerate().rev() {
+            let var_num = layer.input_var_num;
+            let mask = match &zk_com {
+                Some(com) => Some((
+                    reader
+                        .take(com.commitment_size(), "mask commitment")?
+                        .to_vec(),
+                    reader.take_f("mask sum")?,
+                )),
+                None => None,
+            };
+            let mut rounds = vec![];
+            let mut vx_claim = None;
+            let rounds_num = match header.gkr_scheme {
+                GKRScheme::Vanilla => 2 * var_num,
+                GKRScheme::GkrSquare => var_num,
+            };
+            for i_var in 0..rounds_num {
+                let evals = (0..evals_num)
+                    .map(|_| reader.take_f("sumcheck round"))
+                    .collect::<Result<Vec<_>, _>>()?;
+                rounds.push(evals);
+                if i_var == var_num - 1 {
+                    vx_claim = Some(reader.take_f("input claim")?);
+                }
+            }
+            let vy_claim = match header.gkr_scheme {
+                GKRScheme::Vanilla => Some(reader.take_f("input claim")?),
+                GKRScheme::GkrSquare => None,
+            };
+            let zk = match (&zk_com, mask) {
+                (Some(com), Some((mask_commitment, mask_sum))) => {
+                    let w_round = (0..evals_num)
+                        .map(|_| reader.take_f("sumcheck round"))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    // the outputs of the output layer are not masked
+                    let output_mask_num = if i + 1 < circuit.layers.len() { 2 } else { 0 };
+                    let output_mask_evals = (0..output_mask_num)
+                        .map(|_| {
+                            Ok::<_, ProofFormatError>((
+                                reader.take_f("mask evaluation")?,
+                                reader
+                                    .take(
+                                        com.inner_product_proof_size(INPUT_MASK_LEN),
+                                        "inner product proof",
+                                    )?
+                                    .to_vec(),
+                            ))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let mask_eval = reader.take_f("mask evaluation")?;
+                    let mask_eval_proof = reader
+                        .take(
+                            com.inner_product_proof_size(libra_mask_len(var_num * 2 + 1)),
+                            "inner product proof",
+                        )?
+                        .to_vec();
+                    Some(LayerZKProof {
+                        mask_commitment,
+                        mask_sum,
+                        w_round,
+                        output_mask_evals,
+                        mask_eval,
+                        mask_eval_proof,
+                    })
+                }
+                _ => None,
+            };
+            layers.push(LayerProof {
+                rounds,
+                vx_claim: vx_claim.unwrap_or(C::Field::zero()),
+                vy_claim,
+                zk,
+            });
+        }
+
+        let claims_num = match header.gkr_scheme {
+            GKRScheme::Vanilla => 2,
+            GKRScheme::GkrSquare => 1,
+        };
+        let mut openings = vec![];
+        for _ in 0..claims_num {
+            let opening = match &zk_com {
+                // the claim is proven on the private inputs with the mask of the input layer
+                Some(com) => OpeningProof {
+                    private_v: None,
+                    opening: reader
+                        .take(
+                            com.input_claim_proof_size(
+                                circuit.log_private_input_size(),
+                                INPUT_MASK_LEN,
+                            ),
+                            "inner product proof",
+                        )?
+                        .to_vec(),
+                },
+                None => OpeningProof {
+                    private_v: match circuit.public_input_offset {
+                        Some(_) => Some(reader.take_f("private input claim")?),
+                        None => None,
+                    },
+                    opening: reader.take(pcs.opening_size(), "opening")?.to_vec(),
+                },
+            };
+            openings.push(opening);
+        }
+
+        if reader.idx != proof.bytes.len() {
+            return Err(ProofFormatError::TrailingBytes(
+                proof.bytes.len() - reader.idx,
+            ));
+        }
+
+        Ok(Self {
+            header,
+            commitment,
+            grinding_nonce,
+            layers,
+            openings,
+        })
+    }
+
+    /// The bytes of the proof, in the order the verifier reads them
+    pub fn to_proof(&self) -> Proof {
+        let mut bytes = self.commitment.clone();
+        if let Some(nonce) = self.grinding_nonce {
+            bytes.extend_from_slice(&nonce.to_le_bytes());
+        }
+        for layer in self.layers.iter() {
+            if let Some(zk) = &layer.zk {
+                bytes.extend_from_slice(&zk.mask_commitment);
+                zk.mask_sum.serialize_into(&mut bytes);
+            }
+            // the claim at rx follows the rounds over x, which are the first half in the
+            // vanilla scheme and all the rounds in GKR^2
+            let var_num = match layer.vy_claim {
+                Some(_) => layer.rounds.len() / 2,
+                None => layer.rounds.len(),
+            };
+            for (i_var, evals) in layer.rounds.iter().enumerate() {
+                evals.iter().for_each(|e| e.serialize_into(&mut bytes));
+                if i_var + 1 == var_num {
+                    layer.vx_claim.serialize_into(&mut bytes);
+                }
+            }
+            if let Some(vy_claim) = layer.vy_claim {
+                vy_claim.serialize_into(&mut bytes);
+            }
+            if let Some(zk) = &layer.zk {
+                zk.w_round.iter().for_each(|e| e.serialize_into(&mut bytes));
+                for (eval, eval_proof) in zk.output_mask_evals.iter() {
+                    eval.serialize_into(&mut bytes);
+                    bytes.extend_from_slice(eval_proof);
+                }
+                zk.mask_eval.serialize_into(&mut bytes);
+                bytes.extend_from_slice(&zk.mask_eval_proof);
+            }
+        }
+        for opening in self.openings.iter() {
+            if let Some(private_v) = opening.private_v {
+                private_v.serialize_into(&mut bytes);
+            }
+            bytes.extend_from_slice(&opening.opening);
+        }
+
+        let mut proof = Proof::default();
+        proof.append_u8_slice(&bytes, bytes.len());
+        proof
+    }
+
+    /// The canonical encoding of the proof: the header, then the sections in the order of
+    /// the proof, the lengths of the byte strings and the numbers of entries as u64 in little
+    /// endian. The round polynomials have as many evaluations as the scheme of the header
+    /// sends, the vy claims are there for the vanilla scheme, and the masks in the
+    /// zero-knowledge mode.
+    pub fn serialize_into<W: Write>(&self, writer: W) {
+        let mut writer = EncodingWriter { writer };
+        self.header.serialize_into(&mut writer.writer);
+        writer.bytes(&self.commitment);
+        if let Some(nonce) = self.grinding_nonce {
+            writer.writer.write_all(&nonce.to_le_bytes()).unwrap();
+        }
+        writer.len(self.layers.len());
+        for layer in self.layers.iter() {
+            writer.len(layer.rounds.len());
+            layer.rounds.iter().flatten().for_each(|e| writer.f(e));
+            writer.f(&layer.vx_claim);
+            if let Some(vy_claim) = &layer.vy_claim {
+                writer.f(vy_claim);
+            }
+            if let Some(zk) = &layer.zk {
+                writer.bytes(&zk.mask_commitment);
+                writer.f(&zk.mask_sum);
+                zk.w_round.iter().for_each(|e| writer.f(e));
+                writer.len(zk.output_mask_evals.len());
+                for (eval, eval_proof) in zk.output_mask_evals.iter() {
+                    writer.f(eval);
+                    writer.bytes(eval_proof);
+                }
+                writer.f(&zk.mask_eval);
+                writer.bytes(&zk.mask_eval_proof);
+            }
+        }
+        writer.len(self.openings.len());
+        for opening in self.openings.iter() {
+            writer
+                .writer
+                .write_all(&[opening.private_v.is_some() as u8])
+                .unwrap();
+            if let Some(private_v) = &opening.private_v {
+                writer.f(private_v);
+            }
+            writer.bytes(&opening.opening);
+        }
+    }
+
+    /// Decode a proof in the canonical encoding, without the circuit. The header must be
+    /// for the field of `C`, it is not checked against a configuration.
+    pub fn deserialize_from<R: Read>(mut reader: R) -> Result<Self, ProofFormatError> {
+        let header = ProofHeader::deserialize_from(&mut reader)?;
+        Self::deserialize_sections(header, reader)
+    }
+
+    /// Decode the sections of the canonical encoding that follow `header`
+    pub(crate) fn deserialize_sections<R: Read>(
+        header: ProofHeader,
+        reader: R,
+    ) -> Result<Self, ProofFormatError> {
+        let mut reader = EncodingReader { reader };
+        if header.field_type != C::FIELD_TYPE {
+            return Err(ProofFormatError::ConfigMismatch("field"));
+        }
+        let evals_num = round_evals_num(&header.gkr_scheme, header.zk);
+
+        let commitment = reader.bytes("commitment")?;
+        let grinding_nonce = match header.grinding_bits {
+            0 => None,
+            _ => Some(u64::from_le_bytes(reader.exact("grinding nonce")?)),
+        };
+
+        let mut layers = vec![];
+        for _ in 0..reader.len("layers")? {
+            let mut rounds = vec![];
+            for _ in 0..reader.len("sumcheck rounds")? {
+                rounds.push(reader.fs(evals_num, "sumcheck round")?);
+            }
+            let vx_claim = reader.f("input claim")?;
+            let vy_claim = match header.gkr_scheme {
+                GKRScheme::Vanilla => Some(reader.f("input claim")?),
+                GKRScheme::GkrSquare => None,
+            };
+            let zk = match header.zk {
+                true => {
+                    let mask_commitment = reader.bytes("mask commitment")?;
+                    let mask_sum = reader.f("mask sum")?;
+                    let w_round = reader.fs(evals_num, "sumcheck round")?;
+                    let mut output_mask_evals = vec![];
+                    for _ in 0..reader.len("mask evaluations")? {
+                        output_mask_evals.push((
+                            reader.f("mask evaluation")?,
+                            reader.bytes("inner product proof")?,
+                        ));
+                    }
+                    Some(LayerZKProof {
+                        mask_commitment,
+                        mask_sum,
+                        w_round,
+                        output_mask_evals,
+                        mask_eval: reader.f("mask evaluation")?,
+                        mask_eval_proof: reader.bytes("inner product proof")?,
+                    })
+                }
+                false => None,
+            };
+            layers.push(LayerProof {
+                rounds,
+                vx_claim,
+                vy_claim,
+                zk,
+            });
+        }
+
+        let mut openings = vec![];
+        for _ in 0..reader.len("openings")? {
+            let private_v = match reader.flag("private input claim")? {
+                true => Some(reader.f("private input claim")?),
+                false => None,
+            };
+            openings.push(OpeningProof {
+                private_v,
+                opening: reader.bytes("opening")?,
+            });
+        }
+
+        Ok(Self {
+            header,
+            commitment,
+            grinding_nonce,
+            layers,
+            openings,
+        })
+    }
+}
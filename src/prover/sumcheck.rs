@@ -1,50 +1,104 @@
+use arith::Field;
+
 use crate::{
-    CircuitLayer, GKRConfig, GkrScratchpad, SumcheckGkrHelper, SumcheckGkrSquareHelper, Transcript,
+    CircuitLayer, FiatShamirHash, GKRConfig, GkrScratchpad, SumcheckGkrHelper,
+    SumcheckGkrSquareHelper, SumcheckGkrZKHelper, SumcheckZKMasks, Transcript,
 };
 
 // FIXME
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::type_complexity)]
-pub fn sumcheck_prove_gkr_layer<C: GKRConfig>(
+pub fn sumcheck_prove_gkr_layer<C: GKRConfig, H: FiatShamirHash>(
+    layer: &CircuitLayer<C>,
+    rz0: &[C::ChallengeField],
+    rz1: &[C::ChallengeField],
+    alpha: &C::ChallengeField,
+    beta: &C::ChallengeField,
+    transcript: &mut Transcript<H>,
+    sp: &mut GkrScratchpad<C>,
+    zk_masks: Option<SumcheckZKMasks<C, H>>,
+) -> (Vec<C::ChallengeField>, Vec<C::ChallengeField>) {
+    sumcheck_prove_gkr_layer_with_inputs(
+        layer,
+        &layer.input_vals.evals,
+        rz0,
+        rz1,
+        alpha,
+        beta,
+        transcript,
+        sp,
+        zk_masks,
+    )
+}
+
+/// Same as `sumcheck_prove_gkr_layer`, on the inputs `input_vals` instead of the input
+/// values of the layer
+// FIXME
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+pub(crate) fn sumcheck_prove_gkr_layer_with_inputs<C: GKRConfig, H: FiatShamirHash>(
     layer: &CircuitLayer<C>,
+    input_vals: &[C::Field],
     rz0: &[C::ChallengeField],
     rz1: &[C::ChallengeField],
     alpha: &C::ChallengeField,
     beta: &C::ChallengeField,
-    transcript: &mut Transcript,
+    transcript: &mut Transcript<H>,
     sp: &mut GkrScratchpad<C>,
+    zk_masks: Option<SumcheckZKMasks<C, H>>,
 ) -> (Vec<C::ChallengeField>, Vec<C::ChallengeField>) {
-    let mut helper = SumcheckGkrHelper::new(layer, rz0, rz1, alpha, beta, sp);
+    let mut zk_helper = zk_masks
+        .map(|masks| SumcheckGkrZKHelper::new(layer, rz0, rz1, alpha, beta, masks, transcript));
+    let mut helper = SumcheckGkrHelper::new(layer, input_vals, rz0, rz1, alpha, beta, sp);
 
+    let mut vx_claim = C::Field::zero();
     for i_var in 0..layer.input_var_num * 2 {
         if i_var == 0 {
             helper.prepare_g_x_vals()
         }
 
         if i_var == layer.input_var_num {
-            let vx_claim = helper.vx_claim();
             helper.prepare_h_y_vals(vx_claim)
         }
 
         let evals = helper.poly_evals_at(i_var, 2);
 
-        transcript.append_f::<C>(evals[0]);
-        transcript.append_f::<C>(evals[1]);
-        transcript.append_f::<C>(evals[2]);
+        match zk_helper.as_mut() {
+            None => evals.iter().for_each(|e| transcript.append_f::<C>(*e)),
+            Some(zk_helper) => zk_helper
+                .poly_evals_at(i_var, evals, &helper)
+                .iter()
+                .for_each(|e| transcript.append_f::<C>(*e)),
+        }
 
         let r = transcript.challenge_f::<C>();
 
         log::trace!("i_var={} evals: {:?} r: {:?}", i_var, evals, r);
 
         helper.receive_challenge(i_var, r);
+        if let Some(zk_helper) = zk_helper.as_mut() {
+            zk_helper.receive_challenge(i_var, r);
+        }
         if i_var == layer.input_var_num - 1 {
-            log::trace!("vx claim: {:?}", helper.vx_claim());
-            transcript.append_f::<C>(helper.vx_claim());
+            vx_claim = helper.vx_claim();
+            if let Some(zk_helper) = zk_helper.as_ref() {
+                vx_claim += zk_helper.input_mask_eval(&helper.rx);
+            }
+            log::trace!("vx claim: {:?}", vx_claim);
+            transcript.append_f::<C>(vx_claim);
         }
     }
 
-    log::trace!("claimed vy = {:?}", helper.vy_claim());
-    transcript.append_f::<C>(helper.vy_claim());
+    let mut vy_claim = helper.vy_claim();
+    if let Some(zk_helper) = zk_helper.as_ref() {
+        vy_claim += zk_helper.input_mask_eval(&helper.ry);
+    }
+    log::trace!("claimed vy = {:?}", vy_claim);
+    transcript.append_f::<C>(vy_claim);
+
+    if let Some(zk_helper) = zk_helper {
+        zk_helper.finalize(helper.hg_claim() * vy_claim, transcript);
+    }
 
     let rz0 = helper.rx.clone();
     let rz1 = helper.ry.clone();
@@ -55,14 +109,35 @@ pub fn sumcheck_prove_gkr_layer<C: GKRConfig>(
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::type_complexity)]
 #[allow(clippy::needless_range_loop)] // todo: remove
-pub fn sumcheck_prove_gkr_square_layer<C: GKRConfig>(
+pub fn sumcheck_prove_gkr_square_layer<C: GKRConfig, H: FiatShamirHash>(
     layer: &CircuitLayer<C>,
     rz0: &[C::ChallengeField],
-    transcript: &mut Transcript,
+    transcript: &mut Transcript<H>,
+    sp: &mut GkrScratchpad<C>,
+) -> Vec<C::ChallengeField> {
+    sumcheck_prove_gkr_square_layer_with_inputs(
+        layer,
+        &layer.input_vals.evals,
+        rz0,
+        &C::ChallengeField::one(),
+        transcript,
+        sp,
+    )
+}
+
+/// Same as `sumcheck_prove_gkr_square_layer`, on the inputs `input_vals` instead of the
+/// input values of the layer and for the claim at rz0 scaled by `scale`
+#[allow(clippy::needless_range_loop)] // todo: remove
+pub(crate) fn sumcheck_prove_gkr_square_layer_with_inputs<C: GKRConfig, H: FiatShamirHash>(
+    layer: &CircuitLayer<C>,
+    input_vals: &[C::Field],
+    rz0: &[C::ChallengeField],
+    scale: &C::ChallengeField,
+    transcript: &mut Transcript<H>,
     sp: &mut GkrScratchpad<C>,
 ) -> Vec<C::ChallengeField> {
     const D: usize = 7;
-    let mut helper = SumcheckGkrSquareHelper::new(layer, rz0, sp);
+    let mut helper = SumcheckGkrSquareHelper::new(layer, input_vals, rz0, scale, sp);
 
     for i_var in 0..layer.input_var_num {
         if i_var == 0 {
@@ -80,10 +155,6 @@ pub fn sumcheck_prove_gkr_square_layer<C: GKRConfig>(
         log::trace!("i_var={} evals: {:?} r: {:?}", i_var, evals, r);
 
         helper.receive_challenge(i_var, r);
-        if i_var == layer.input_var_num - 1 {
-            log::trace!("vx claim: {:?}", helper.vx_claim());
-            transcript.append_f::<C>(helper.vx_claim());
-        }
     }
 
     log::trace!("claimed vx = {:?}", helper.vx_claim());
@@ -9,6 +9,14 @@ fn _eq<F: Field>(x: &F, y: &F) -> F {
     xy + xy - x - y + F::from(1)
 }
 
+/// eq(r0, r1) between two points with the same number of variables
+pub(crate) fn eq_eval_between<F: Field>(r0: &[F], r1: &[F]) -> F {
+    assert_eq!(r0.len(), r1.len());
+    r0.iter()
+        .zip(r1.iter())
+        .fold(F::one(), |acc, (x, y)| acc * _eq(x, y))
+}
+
 pub(crate) fn eq_evals_at_primitive<F: Field>(r: &[F], mul_factor: &F, eq_evals: &mut [F]) {
     eq_evals[0] = *mul_factor;
     let mut cur_eval_num = 1;
@@ -154,6 +162,7 @@ pub(crate) struct SumcheckGkrHelper<'a, C: GKRConfig> {
     pub(crate) ry: Vec<C::ChallengeField>,
 
     layer: &'a CircuitLayer<C>,
+    input_vals: &'a [C::Field],
     sp: &'a mut GkrScratchpad<C>,
     rz0: &'a [C::ChallengeField],
     rz1: &'a [C::ChallengeField],
@@ -168,8 +177,11 @@ pub(crate) struct SumcheckGkrHelper<'a, C: GKRConfig> {
 }
 
 impl<'a, C: GKRConfig> SumcheckGkrHelper<'a, C> {
+    /// The sumcheck of `layer` on the inputs `input_vals`, the input values of the layer
+    /// itself unless its values are folded from the instances of a batch
     pub(crate) fn new(
         layer: &'a CircuitLayer<C>,
+        input_vals: &'a [C::Field],
         rz0: &'a [C::ChallengeField],
         rz1: &'a [C::ChallengeField],
         alpha: &'a C::ChallengeField,
@@ -181,6 +193,7 @@ impl<'a, C: GKRConfig> SumcheckGkrHelper<'a, C> {
             ry: vec![],
 
             layer,
+            input_vals,
             sp,
             rz0,
             rz1,
@@ -202,7 +215,7 @@ impl<'a, C: GKRConfig> SumcheckGkrHelper<'a, C> {
                 degree,
                 &mut self.sp.v_evals,
                 &mut self.sp.hg_evals_5,
-                &self.layer.input_vals.evals,
+                self.input_vals,
                 &self.sp.gate_exists_5,
             )
         } else {
@@ -211,7 +224,7 @@ impl<'a, C: GKRConfig> SumcheckGkrHelper<'a, C> {
                 degree,
                 &mut self.sp.v_evals,
                 &mut self.sp.hg_evals_5,
-                &self.layer.input_vals.evals,
+                self.input_vals,
                 &self.sp.gate_exists_5,
             )
         }
@@ -224,7 +237,7 @@ impl<'a, C: GKRConfig> SumcheckGkrHelper<'a, C> {
                 r,
                 &mut self.sp.v_evals,
                 &mut self.sp.hg_evals_5,
-                &self.layer.input_vals.evals,
+                self.input_vals,
                 &mut self.sp.gate_exists_5,
             );
             log::trace!("v_eval[0]:= {:?}", self.sp.v_evals[0]);
@@ -235,7 +248,7 @@ impl<'a, C: GKRConfig> SumcheckGkrHelper<'a, C> {
                 r,
                 &mut self.sp.v_evals,
                 &mut self.sp.hg_evals_5,
-                &self.layer.input_vals.evals,
+                self.input_vals,
                 &mut self.sp.gate_exists_5,
             );
             self.ry.push(r);
@@ -250,21 +263,35 @@ impl<'a, C: GKRConfig> SumcheckGkrHelper<'a, C> {
         self.sp.v_evals[0]
     }
 
+    /// The two entries of the hg table before the challenge on the last variable of x or y
+    pub(crate) fn hg_evals_at_last_var(&self) -> [C::Field; 2] {
+        if !self.sp.gate_exists_5[0] && !self.sp.gate_exists_5[1] {
+            [C::Field::zero(); 2]
+        } else {
+            [self.sp.hg_evals_5[0], self.sp.hg_evals_5[1]]
+        }
+    }
+
+    /// hg evaluated at the challenges, after the last variable of x or y
+    pub(crate) fn hg_claim(&self) -> C::Field {
+        self.sp.hg_evals_5[0]
+    }
+
     pub(crate) fn prepare_g_x_vals(&mut self) {
         let mul = &self.layer.mul;
         let add = &self.layer.add;
-        let vals = &self.layer.input_vals;
+        let vals = self.input_vals;
         let eq_evals_at_rz0 = &mut self.sp.eq_evals_at_rz0;
         let eq_evals_at_rz1 = &mut self.sp.eq_evals_at_rz1;
         let gate_exists = &mut self.sp.gate_exists_5;
         let hg_vals = &mut self.sp.hg_evals_5;
-        // hg_vals[0..vals.evals.len()].fill(F::zero()); // FIXED: consider memset unsafe?
+        // hg_vals[0..vals.len()].fill(F::zero()); // FIXED: consider memset unsafe?
         unsafe {
-            std::ptr::write_bytes(hg_vals.as_mut_ptr(), 0, vals.evals.len());
+            std::ptr::write_bytes(hg_vals.as_mut_ptr(), 0, vals.len());
         }
-        // gate_exists[0..vals.evals.len()].fill(false); // FIXED: consider memset unsafe?
+        // gate_exists[0..vals.len()].fill(false); // FIXED: consider memset unsafe?
         unsafe {
-            std::ptr::write_bytes(gate_exists.as_mut_ptr(), 0, vals.evals.len());
+            std::ptr::write_bytes(gate_exists.as_mut_ptr(), 0, vals.len());
         }
         eq_eval_at(
             self.rz0,
@@ -285,7 +312,7 @@ impl<'a, C: GKRConfig> SumcheckGkrHelper<'a, C> {
         }
 
         for g in mul.iter() {
-            hg_vals[g.i_ids[0]] += vals.evals[g.i_ids[1]].scale(&C::challenge_mul_circuit_field(
+            hg_vals[g.i_ids[0]] += vals[g.i_ids[1]].scale(&C::challenge_mul_circuit_field(
                 &eq_evals_at_rz0[g.o_id],
                 &g.coef,
             ));
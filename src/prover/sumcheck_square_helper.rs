@@ -1,6 +1,6 @@
 use arith::{Field, SimdField};
 
-use crate::{CircuitLayer, GKRConfig, GkrScratchpad};
+use crate::{CircuitLayer, GKRConfig, GkrScratchpad, GATE_TYPE_POW1, GATE_TYPE_POW5};
 
 use crate::sumcheck_helper::eq_eval_at;
 
@@ -142,8 +142,10 @@ pub(crate) struct SumcheckGkrSquareHelper<'a, C: GKRConfig, const D: usize> {
     pub(crate) rx: Vec<C::ChallengeField>,
 
     layer: &'a CircuitLayer<C>,
+    input_vals: &'a [C::Field],
     sp: &'a mut GkrScratchpad<C>,
     rz0: &'a [C::ChallengeField],
+    scale: C::ChallengeField,
 
     input_var_num: usize,
     output_var_num: usize,
@@ -152,17 +154,23 @@ pub(crate) struct SumcheckGkrSquareHelper<'a, C: GKRConfig, const D: usize> {
 }
 
 impl<'a, C: GKRConfig, const D: usize> SumcheckGkrSquareHelper<'a, C, D> {
+    /// The sumcheck of `layer` on the inputs `input_vals` for the claim at rz0 scaled by
+    /// `scale`, see `SumcheckGkrHelper::new`
     pub(crate) fn new(
         layer: &'a CircuitLayer<C>,
+        input_vals: &'a [C::Field],
         rz0: &'a [C::ChallengeField],
+        scale: &C::ChallengeField,
         sp: &'a mut GkrScratchpad<C>,
     ) -> Self {
         SumcheckGkrSquareHelper {
             rx: vec![],
 
             layer,
+            input_vals,
             sp,
             rz0,
+            scale: *scale,
 
             input_var_num: layer.input_var_num,
             output_var_num: layer.output_var_num,
@@ -177,7 +185,7 @@ impl<'a, C: GKRConfig, const D: usize> SumcheckGkrSquareHelper<'a, C, D> {
             &mut self.sp.v_evals,
             &mut self.sp.hg_evals_5,
             &mut self.sp.hg_evals_1,
-            &self.layer.input_vals.evals,
+            self.input_vals,
             &self.sp.gate_exists_5,
             &self.sp.gate_exists_1,
         )
@@ -190,7 +198,7 @@ impl<'a, C: GKRConfig, const D: usize> SumcheckGkrSquareHelper<'a, C, D> {
             &mut self.sp.v_evals,
             &mut self.sp.hg_evals_5,
             &mut self.sp.hg_evals_1,
-            &self.layer.input_vals.evals,
+            self.input_vals,
             &mut self.sp.gate_exists_5,
             &mut self.sp.gate_exists_1,
         );
@@ -204,25 +212,25 @@ impl<'a, C: GKRConfig, const D: usize> SumcheckGkrSquareHelper<'a, C, D> {
 
     pub(crate) fn prepare_g_x_vals(&mut self) {
         let uni = &self.layer.uni; // univariate things like square, pow5, etc.
-        let vals = &self.layer.input_vals;
+        let vals = self.input_vals;
         let eq_evals_at_rz0 = &mut self.sp.eq_evals_at_rz0;
         let gate_exists_5 = &mut self.sp.gate_exists_5;
         let gate_exists_1 = &mut self.sp.gate_exists_1;
         let hg_evals_5 = &mut self.sp.hg_evals_5;
         let hg_evals_1 = &mut self.sp.hg_evals_1;
-        // hg_vals[0..vals.evals.len()].fill(F::zero()); // FIXED: consider memset unsafe?
+        // hg_vals[0..vals.len()].fill(F::zero()); // FIXED: consider memset unsafe?
         unsafe {
-            std::ptr::write_bytes(hg_evals_5.as_mut_ptr(), 0, vals.evals.len());
-            std::ptr::write_bytes(hg_evals_1.as_mut_ptr(), 0, vals.evals.len());
+            std::ptr::write_bytes(hg_evals_5.as_mut_ptr(), 0, vals.len());
+            std::ptr::write_bytes(hg_evals_1.as_mut_ptr(), 0, vals.len());
         }
-        // gate_exists[0..vals.evals.len()].fill(false); // FIXED: consider memset unsafe?
+        // gate_exists[0..vals.len()].fill(false); // FIXED: consider memset unsafe?
         unsafe {
-            std::ptr::write_bytes(gate_exists_5.as_mut_ptr(), 0, vals.evals.len());
-            std::ptr::write_bytes(gate_exists_1.as_mut_ptr(), 0, vals.evals.len());
+            std::ptr::write_bytes(gate_exists_5.as_mut_ptr(), 0, vals.len());
+            std::ptr::write_bytes(gate_exists_1.as_mut_ptr(), 0, vals.len());
         }
         eq_eval_at(
             self.rz0,
-            &C::ChallengeField::one(),
+            &self.scale,
             eq_evals_at_rz0,
             &mut self.sp.eq_evals_first_half,
             &mut self.sp.eq_evals_second_half,
@@ -230,14 +238,14 @@ impl<'a, C: GKRConfig, const D: usize> SumcheckGkrSquareHelper<'a, C, D> {
 
         for g in uni.iter() {
             match g.gate_type {
-                12345 => {
+                GATE_TYPE_POW5 => {
                     hg_evals_5[g.i_ids[0]] += C::Field::from(C::challenge_mul_circuit_field(
                         &eq_evals_at_rz0[g.o_id],
                         &g.coef,
                     ));
                     gate_exists_5[g.i_ids[0]] = true;
                 }
-                12346 => {
+                GATE_TYPE_POW1 => {
                     hg_evals_1[g.i_ids[0]] += C::Field::from(C::challenge_mul_circuit_field(
                         &eq_evals_at_rz0[g.o_id],
                         &g.coef,
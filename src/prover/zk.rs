@@ -0,0 +1,415 @@
+//! Masking of the GKR layers for the zero-knowledge mode, following Libra.
+//!
+//! The MLE of the inputs of each layer is replaced by
+//! V'(z) = V(z) + Z(z) sum_w R(z_0, w) with Z(z) = prod_j z_j (1 - z_j), which agrees with V
+//! on the boolean hypercube, and R(z_0, w) = c_00 + c_10 z_0 + c_01 w + c_11 z_0 w is a
+//! random polynomial committed before the proof. The sumcheck of a layer then runs over
+//! (x, y, w) on
+//!     H(x, y, w) = F(x, y) (1 - w) + T(w) eq(x, 0) eq(y, 0) + rho g(x, y, w)
+//! where F is the usual sumcheck polynomial over V', T(w) carries the mask of the outputs
+//! of the layer, T(w) = alpha Z(rz0) R(rz0_0, w) + beta Z(rz1) R(rz1_0, w), and
+//! g = a_0 + sum_k g_k(var_k) is a random polynomial with g_k of degree 3 without constant
+//! term, committed by the prover before the verifier draws rho. All the round polynomials
+//! have degree 3 and are sent as their evaluations at 0, 1, 2, 3. At the end of the
+//! sumcheck, the evaluations of g and of the output mask are proven against their
+//! commitments instead of being opened.
+//!
+//! The masks only act on the last round of the x and y variables, as Z vanishes as soon as
+//! one of the remaining variables is boolean. The layers need at least 2 input variables
+//! so that R(z_0, .) is fixed by then.
+//!
+//! The private inputs are committed by rows of 2^ceil(n/2) entries as in Hyrax, so the mode
+//! goes with the Hyrax polynomial commitment, and the claims on the inputs are proven with
+//! messages of the size of a row.
+
+use std::fmt;
+
+use arith::{Field, SimdField};
+use rand::thread_rng;
+
+use crate::{
+    Circuit, CircuitLayer, Config, FiatShamirHash, GKRConfig, GKRScheme, PolynomialCommitmentType,
+    SumcheckGkrHelper, Transcript, ZKCommitmentForGKR,
+};
+
+/// Number of coefficients of the mask R of the inputs of a layer
+pub const INPUT_MASK_LEN: usize = 4;
+
+/// The mask R of the inputs of a layer, as (c_00, c_10, c_01, c_11)
+pub type InputMask<C> = [<C as GKRConfig>::Field; INPUT_MASK_LEN];
+
+/// Number of coefficients of the mask g of a sumcheck over `num_vars` variables
+#[inline]
+pub fn libra_mask_len(num_vars: usize) -> usize {
+    1 + 3 * num_vars
+}
+
+/// Number of variables indexing the entries of a row of the committed inputs, the low bits
+/// of the index, as for the Hyrax commitment
+#[inline]
+pub fn input_col_vars(num_vars: usize) -> usize {
+    num_vars.div_ceil(2)
+}
+
+/// Number of entries of the largest vector committed in the zero-knowledge mode: a row of
+/// the private inputs together with the mask of the input layer, or the mask g of the
+/// largest layer.
+pub fn zk_commitment_len<C: GKRConfig>(circuit: &Circuit<C>) -> usize {
+    let max_input_var_num = circuit
+        .layers
+        .iter()
+        .map(|layer| layer.input_var_num)
+        .max()
+        .unwrap();
+    usize::max(
+        (1 << input_col_vars(circuit.log_private_input_size())) + INPUT_MASK_LEN,
+        libra_mask_len(max_input_var_num * 2 + 1),
+    )
+}
+
+/// Why a circuit cannot be proven in zero knowledge with a configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZKSupportError {
+    /// the masks are only defined for the vanilla GKR
+    UnsupportedScheme,
+    /// the inputs are committed as for Hyrax, which must be the polynomial commitment
+    UnsupportedPolynomialCommitment,
+    /// the field has no hiding commitment
+    UnsupportedCommitment,
+    /// a layer has less than 2 input variables
+    TooFewInputVars,
+    /// the instances of a batch are proven together without masks
+    UnsupportedBatch,
+}
+
+impl fmt::Display for ZKSupportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZKSupportError::UnsupportedScheme => {
+                write!(
+                    f,
+                    "zero-knowledge mode is only supported for the vanilla GKR"
+                )
+            }
+            ZKSupportError::UnsupportedPolynomialCommitment => {
+                write!(
+                    f,
+                    "zero-knowledge mode needs the Hyrax polynomial commitment"
+                )
+            }
+            ZKSupportError::UnsupportedCommitment => {
+                write!(f, "zero-knowledge mode is not supported for the field")
+            }
+            ZKSupportError::TooFewInputVars => {
+                write!(
+                    f,
+                    "zero-knowledge mode needs at least 2 input variables per layer"
+                )
+            }
+            ZKSupportError::UnsupportedBatch => {
+                write!(f, "zero-knowledge mode is not supported for batches")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ZKSupportError {}
+
+/// Check that the zero-knowledge mode is supported for the circuit and configuration, the
+/// field is checked when its commitment is instantiated
+pub fn check_zk_support<C: GKRConfig>(
+    circuit: &Circuit<C>,
+    config: &Config<C>,
+) -> Result<(), ZKSupportError> {
+    if config.gkr_scheme != GKRScheme::Vanilla {
+        return Err(ZKSupportError::UnsupportedScheme);
+    }
+    if config.polynomial_commitment_type != PolynomialCommitmentType::Hyrax {
+        return Err(ZKSupportError::UnsupportedPolynomialCommitment);
+    }
+    if circuit.layers.iter().any(|layer| layer.input_var_num < 2) {
+        return Err(ZKSupportError::TooFewInputVars);
+    }
+    Ok(())
+}
+
+/// Sample the masks of the inputs of all the layers
+pub fn random_input_masks<C: GKRConfig>(layer_num: usize) -> Vec<InputMask<C>> {
+    let mut rng = thread_rng();
+    (0..layer_num)
+        .map(|_| [(); INPUT_MASK_LEN].map(|_| C::Field::random_unsafe(&mut rng)))
+        .collect()
+}
+
+#[inline]
+pub(crate) fn inner_product<C: GKRConfig>(
+    vals: &[C::Field],
+    weights: &[C::ChallengeField],
+) -> C::Field {
+    assert_eq!(vals.len(), weights.len());
+    vals.iter()
+        .zip(weights.iter())
+        .fold(C::Field::zero(), |acc, (v, w)| acc + v.scale(w))
+}
+
+/// Z(r) = prod_j r_j (1 - r_j)
+#[inline]
+pub(crate) fn vanishing_eval<F: Field>(r: &[F]) -> F {
+    r.iter().fold(F::one(), |acc, r| acc * r * (F::one() - r))
+}
+
+/// eq(r, 0) = prod_j (1 - r_j)
+#[inline]
+pub(crate) fn eq_zero_eval<F: Field>(r: &[F]) -> F {
+    r.iter().fold(F::one(), |acc, r| acc * (F::one() - r))
+}
+
+/// The weights of (c_00, c_10, c_01, c_11) in R(z_0, w)
+#[inline]
+pub(crate) fn mask_weights<F: Field>(z0: F, w: F) -> Vec<F> {
+    vec![F::one(), z0, w, z0 * w]
+}
+
+/// The weights of (c_00, c_10, c_01, c_11) in Z(r) sum_w R(r_0, w), the difference between
+/// the masked and the original MLE at r
+#[inline]
+pub(crate) fn input_mask_weights<F: Field>(r: &[F]) -> Vec<F> {
+    let z = vanishing_eval(r);
+    vec![z.double(), (z * r[0]).double(), z, z * r[0]]
+}
+
+/// The weights of the coefficients of g in g(r)
+pub(crate) fn libra_mask_weights<F: Field>(r: &[F]) -> Vec<F> {
+    let mut weights = Vec::with_capacity(libra_mask_len(r.len()));
+    weights.push(F::one());
+    for r in r {
+        let r2 = r.square();
+        weights.extend_from_slice(&[*r, r2, r2 * r]);
+    }
+    weights
+}
+
+/// The weights of the committed private inputs and mask of the input layer for a claim on
+/// them: `scale` eq(`rz_private`, .) on the inputs and `mask` on the mask
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputClaimWeights<F> {
+    pub rz_private: Vec<F>,
+    pub scale: F,
+    pub mask: Vec<F>,
+}
+
+/// The weights of the committed private inputs and mask of the input layer for the claim
+/// V'(rz) - the MLE of the public inputs at rz
+pub(crate) fn input_claim_weights<C: GKRConfig>(
+    circuit: &Circuit<C>,
+    rz: &[C::ChallengeField],
+) -> InputClaimWeights<C::ChallengeField> {
+    let (rz_private, rz_public) = rz.split_at(circuit.log_private_input_size());
+    InputClaimWeights {
+        rz_private: rz_private.to_vec(),
+        scale: eq_zero_eval(rz_public),
+        mask: input_mask_weights(rz),
+    }
+}
+
+#[inline]
+fn pow_of_2<F: Field>(exponent: usize) -> F {
+    (0..exponent).fold(F::one(), |acc, _| acc.double())
+}
+
+/// The masks of the sumcheck of a layer, on the prover side.
+pub struct SumcheckZKMasks<'a, C: GKRConfig, H: FiatShamirHash> {
+    pub zk_com: &'a mut dyn ZKCommitmentForGKR<C, H>,
+    /// the mask of the inputs of the layer
+    pub input_mask: &'a InputMask<C>,
+    /// the mask of the outputs of the layer with the index of its commitment,
+    /// None for the output layer of the circuit which is not masked
+    pub output_mask: Option<(&'a InputMask<C>, usize)>,
+}
+
+pub(crate) struct SumcheckGkrZKHelper<'a, C: GKRConfig, H: FiatShamirHash> {
+    masks: SumcheckZKMasks<'a, C, H>,
+    input_var_num: usize,
+    num_vars: usize,
+
+    /// the coefficients of g and the index of their commitment
+    g: Vec<C::Field>,
+    g_idx: usize,
+    rho: C::ChallengeField,
+    /// a_0 + sum_{j < k} g_j(r_j) at round k
+    g_prefix: C::Field,
+    /// sum_{j >= k} g_j(1) at round k
+    g_suffix: C::Field,
+
+    /// the first variables of rz0 and rz1, and T(0), T(1)
+    rz0_0: C::ChallengeField,
+    rz1_0: C::ChallengeField,
+    t_evals: [C::Field; 2],
+    /// eq(r, 0) over the challenges of the x and y variables so far
+    eq_zero: C::ChallengeField,
+
+    r: Vec<C::ChallengeField>,
+}
+
+impl<'a, C: GKRConfig, H: FiatShamirHash> SumcheckGkrZKHelper<'a, C, H> {
+    /// Commit to a fresh g, send its sum over the hypercube and draw rho
+    pub(crate) fn new(
+        layer: &CircuitLayer<C>,
+        rz0: &[C::ChallengeField],
+        rz1: &[C::ChallengeField],
+        alpha: &C::ChallengeField,
+        beta: &C::ChallengeField,
+        masks: SumcheckZKMasks<'a, C, H>,
+        transcript: &mut Transcript<H>,
+    ) -> Self {
+        let input_var_num = layer.input_var_num;
+        let num_vars = input_var_num * 2 + 1;
+
+        let mut rng = thread_rng();
+        let g = (0..libra_mask_len(num_vars))
+            .map(|_| C::Field::random_unsafe(&mut rng))
+            .collect::<Vec<_>>();
+        let g_idx = masks.zk_com.commit(&g, transcript);
+        let g_suffix = g[1..].iter().fold(C::Field::zero(), |acc, c| acc + c);
+        let g_sum =
+            g[0] * pow_of_2::<C::Field>(num_vars) + g_suffix * pow_of_2::<C::Field>(num_vars - 1);
+        transcript.append_f::<C>(g_sum);
+        let rho = transcript.challenge_f::<C>();
+
+        let t_evals = match masks.output_mask {
+            Some((mask, _)) => {
+                let alpha_z = *alpha * vanishing_eval(rz0);
+                let beta_z = *beta * vanishing_eval(rz1);
+                [C::ChallengeField::zero(), C::ChallengeField::one()].map(|w| {
+                    inner_product::<C>(mask, &mask_weights(rz0[0], w)).scale(&alpha_z)
+                        + inner_product::<C>(mask, &mask_weights(rz1[0], w)).scale(&beta_z)
+                })
+            }
+            None => [C::Field::zero(); 2],
+        };
+
+        Self {
+            masks,
+            input_var_num,
+            num_vars,
+            g_prefix: g[0],
+            g,
+            g_idx,
+            rho,
+            g_suffix,
+            rz0_0: rz0[0],
+            rz1_0: rz1[0],
+            t_evals,
+            eq_zero: C::ChallengeField::one(),
+            r: vec![],
+        }
+    }
+
+    /// V'(r) - V(r) for the inputs of the layer
+    pub(crate) fn input_mask_eval(&self, r: &[C::ChallengeField]) -> C::Field {
+        inner_product::<C>(self.masks.input_mask, &input_mask_weights(r))
+    }
+
+    /// rho times the evaluations at 0, 1, 2, 3 of the round polynomial of g at round k
+    fn libra_evals_at(&mut self, k: usize) -> [C::Field; 4] {
+        let [g1, g2, g3] = [self.g[3 * k + 1], self.g[3 * k + 2], self.g[3 * k + 3]];
+        let g_k = [
+            C::Field::zero(),
+            g1 + g2 + g3,
+            g1.double() + g2.double().double() + g3.double().double().double(),
+            g1.mul_by_3() + g2.mul_by_3().mul_by_3() + g3.mul_by_3().mul_by_3().mul_by_3(),
+        ];
+        let suffix = self.g_suffix - g_k[1];
+        self.g_suffix = suffix;
+
+        // the remaining num_vars - 1 - k variables are summed over the hypercube
+        let remaining = self.num_vars - 1 - k;
+        let prefix_weight = pow_of_2::<C::Field>(remaining);
+        let suffix_sum = if remaining > 0 {
+            suffix * pow_of_2::<C::Field>(remaining - 1)
+        } else {
+            C::Field::zero()
+        };
+        g_k.map(|g| ((self.g_prefix + g) * prefix_weight + suffix_sum).scale(&self.rho))
+    }
+
+    /// The evaluations at 0, 1, 2, 3 of the round polynomial for the variable `var_idx` of
+    /// x or y, from the evaluations at 0, 1, 2 of the unmasked one
+    pub(crate) fn poly_evals_at(
+        &mut self,
+        var_idx: usize,
+        evals: [C::Field; 3],
+        helper: &SumcheckGkrHelper<C>,
+    ) -> [C::Field; 4] {
+        let [p0, p1, p2] = evals;
+        let mut evals = [p0, p1, p2, p0 - p1.mul_by_3() + p2.mul_by_3()];
+
+        // on the last variable of x or y, hg(X) V'(r, X) adds hg(X) c X (1 - X)
+        if var_idx == self.input_var_num - 1 || var_idx == self.input_var_num * 2 - 1 {
+            let phase_start = var_idx + 1 - self.input_var_num;
+            let c = self.input_mask_eval(&self.r[phase_start..]);
+            let [hg0, hg1] = helper.hg_evals_at_last_var();
+            let hg2 = hg1.double() - hg0;
+            let hg3 = hg1.mul_by_3() - hg0.double();
+            evals[2] -= (c * hg2).double();
+            evals[3] -= (c * hg3).mul_by_6();
+        }
+
+        // T(w) eq(x, 0) eq(y, 0) summed over w is linear in the current variable
+        let t_sum = (self.t_evals[0] + self.t_evals[1]).scale(&self.eq_zero);
+        evals[0] += t_sum;
+        evals[2] -= t_sum;
+        evals[3] -= t_sum.double();
+
+        let libra_evals = self.libra_evals_at(var_idx);
+        for (e, l) in evals.iter_mut().zip(libra_evals.iter()) {
+            *e += l;
+        }
+        evals
+    }
+
+    pub(crate) fn receive_challenge(&mut self, var_idx: usize, r: C::ChallengeField) {
+        let [g1, g2, g3] = [
+            self.g[3 * var_idx + 1],
+            self.g[3 * var_idx + 2],
+            self.g[3 * var_idx + 3],
+        ];
+        self.g_prefix += ((g3.scale(&r) + g2).scale(&r) + g1).scale(&r);
+        self.eq_zero *= C::ChallengeField::one() - r;
+        self.r.push(r);
+    }
+
+    /// Run the round of w given mul(rx, ry) V'(rx) V'(ry), and prove the evaluations of the
+    /// output mask and g at the end of the sumcheck
+    pub(crate) fn finalize(mut self, mul_claim: C::Field, transcript: &mut Transcript<H>) {
+        let [t0, t1] = self.t_evals.map(|t| t.scale(&self.eq_zero));
+        let mut evals = [
+            mul_claim + t0,
+            t1,
+            t1.double() - t0 - mul_claim,
+            t1.mul_by_3() - t0.double() - mul_claim.double(),
+        ];
+        let libra_evals = self.libra_evals_at(self.num_vars - 1);
+        for (e, l) in evals.iter_mut().zip(libra_evals.iter()) {
+            *e += l;
+            transcript.append_f::<C>(*e);
+        }
+        let rw = transcript.challenge_f::<C>();
+        self.receive_challenge(self.num_vars - 1, rw);
+
+        if let Some((mask, idx)) = self.masks.output_mask {
+            for z0 in [self.rz0_0, self.rz1_0] {
+                let weights = mask_weights(z0, rw);
+                transcript.append_f::<C>(inner_product::<C>(mask, &weights));
+                self.masks
+                    .zk_com
+                    .prove_inner_product(idx, &weights, transcript);
+            }
+        }
+
+        transcript.append_f::<C>(self.g_prefix);
+        self.masks
+            .zk_com
+            .prove_inner_product(self.g_idx, &libra_mask_weights(&self.r), transcript);
+    }
+}
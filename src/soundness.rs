@@ -0,0 +1,132 @@
+//! Soundness of the GKR proofs, from the shape of the circuit and the size of the challenge
+//! field, to compare with `Config::security_bits`.
+//!
+//! The bound is round by round: every challenge of the verifier follows a message of the
+//! prover, and a cheating prover escapes with at most the error of the round the challenge
+//! belongs to. A sumcheck round of degree d errs with probability d / |F|, the random
+//! combination of the two claims of a layer with 1 / |F|. The random gate coefficients and
+//! the output point are drawn together after the commitment: the outputs are multilinear in
+//! the k coefficients from the circuit field, and their claim is checked at a point of n
+//! variables, so this round errs with k / |F_circuit| + n / |F|. The polynomial commitment
+//! is assumed to be at least as sound.
+//!
+//! A batch of instances adds the instance variables to the output point, and a sumcheck over
+//! them to every layer, whose degree is one more than the one of the gates.
+//!
+//! The soundness of a proof is the one of its worst round. Under Fiat-Shamir, a prover
+//! making 2^t hash queries gains at most t bits over it. Running GKR again in the same
+//! transcript, or grinding once before it, leaves the worst round as it is, so neither is
+//! counted: a higher target needs a larger challenge field.
+
+use arith::BinomialExtensionField;
+
+use crate::{BatchCircuit, Circuit, Config, GKRConfig, GKRScheme};
+
+/// Degree of the sumcheck round polynomials of the scheme
+pub fn sumcheck_degree(gkr_scheme: &GKRScheme, zk: bool) -> usize {
+    match (gkr_scheme, zk) {
+        // the masks of the zero-knowledge mode raise the degree by one
+        (GKRScheme::Vanilla, true) => 3,
+        (GKRScheme::Vanilla, false) => 2,
+        // the pow5 gates and the eq polynomial
+        (GKRScheme::GkrSquare, _) => 7,
+    }
+}
+
+/// Degree of the round polynomials of the sumchecks over the instance variables of a
+/// batch, the gates of a layer times eq over the instance variables
+pub fn instance_sumcheck_degree(gkr_scheme: &GKRScheme) -> usize {
+    match gkr_scheme {
+        // the mul gates and eq
+        GKRScheme::Vanilla => 3,
+        // the pow5 gates and eq
+        GKRScheme::GkrSquare => 6,
+    }
+}
+
+#[inline]
+fn degree_soundness_bits(degree: usize, field_size: usize) -> f64 {
+    // the challenge field has at least 2^(field_size - 1) elements
+    field_size as f64 - 1.0 - (degree as f64).log2()
+}
+
+/// Bits of security of the worst round of the layers, the sumcheck rounds and the
+/// combinations of claims, over a challenge field of `field_size` bits
+pub fn sumcheck_soundness_bits(gkr_scheme: &GKRScheme, zk: bool, field_size: usize) -> f64 {
+    degree_soundness_bits(sumcheck_degree(gkr_scheme, zk), field_size)
+}
+
+/// Bits of security of the first round, the random gate coefficients of the circuit from a
+/// circuit field of `circuit_field_size` bits and the output point from a challenge field
+/// of `field_size` bits
+pub fn output_soundness_bits<C: GKRConfig>(
+    circuit: &Circuit<C>,
+    field_size: usize,
+    circuit_field_size: usize,
+) -> f64 {
+    let output_var_num = circuit.layers.last().unwrap().output_var_num;
+    point_soundness_bits(circuit, output_var_num, field_size, circuit_field_size)
+}
+
+fn point_soundness_bits<C: GKRConfig>(
+    circuit: &Circuit<C>,
+    output_var_num: usize,
+    field_size: usize,
+    circuit_field_size: usize,
+) -> f64 {
+    let num_coefs = circuit
+        .layers
+        .iter()
+        .map(|layer| layer.rand_coef_idx.len())
+        .sum::<usize>();
+    let error = num_coefs as f64 * 2f64.powf(1.0 - circuit_field_size as f64)
+        + output_var_num.max(1) as f64 * 2f64.powf(1.0 - field_size as f64);
+    -error.log2()
+}
+
+/// Round-by-round soundness of a proof
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Soundness {
+    /// Bits of security of the worst round of the layers
+    pub sumcheck_bits: f64,
+    /// Bits of security of the round of the random gate coefficients and the output point
+    pub output_bits: f64,
+}
+
+impl Soundness {
+    pub fn new<C: GKRConfig>(circuit: &Circuit<C>, config: &Config<C>) -> Self {
+        // the challenge field is an extension of the circuit field
+        let circuit_field_size = config.field_size / C::ChallengeField::DEGREE;
+        Self {
+            sumcheck_bits: sumcheck_soundness_bits(
+                &config.gkr_scheme,
+                config.zk,
+                config.field_size,
+            ),
+            output_bits: output_soundness_bits(circuit, config.field_size, circuit_field_size),
+        }
+    }
+
+    /// Round-by-round soundness of the proof of a batch, see `BatchCircuit`. The zero-knowledge
+    /// mode does not support batches.
+    pub fn new_batch<C: GKRConfig>(batch: &BatchCircuit<C>, config: &Config<C>) -> Self {
+        let circuit_field_size = config.field_size / C::ChallengeField::DEGREE;
+        let degree = sumcheck_degree(&config.gkr_scheme, false)
+            .max(instance_sumcheck_degree(&config.gkr_scheme));
+        let output_var_num = batch.circuit.layers.last().unwrap().output_var_num;
+        Self {
+            sumcheck_bits: degree_soundness_bits(degree, config.field_size),
+            output_bits: point_soundness_bits(
+                &batch.circuit,
+                output_var_num + batch.instance_var_num(),
+                config.field_size,
+                circuit_field_size,
+            ),
+        }
+    }
+
+    /// Bits of security achieved by the proof, the ones of its worst round
+    pub fn achieved_bits(&self) -> f64 {
+        self.sumcheck_bits.min(self.output_bits)
+    }
+}
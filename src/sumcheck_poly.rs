@@ -0,0 +1,185 @@
+//! Sumcheck for polynomials built from multilinear polynomials, independent of the layers of
+//! a circuit, for the protocols other than GKR.
+//!
+//! The polynomial is a sum of products of multilinear polynomials over the same variables.
+//! In each round the prover sends the evaluations of the round polynomial at 0, 1, ..., d
+//! where d is the degree of the polynomial, and the variables are bound from the first one,
+//! as in `MultiLinearPoly::eval_multilinear`.
+
+use arith::{Field, MultiLinearPoly, SimdField};
+use ark_std::{end_timer, start_timer};
+
+use crate::{FiatShamirHash, GKRConfig, Proof, Transcript};
+
+/// The polynomial Σ_j coef_j Π_{k in products[j]} polys[k] over `var_num` variables.
+/// Its degree in each variable is the length of the longest product.
+#[derive(Debug, Clone)]
+pub struct SumOfProducts<C: GKRConfig> {
+    pub var_num: usize,
+    pub polys: Vec<MultiLinearPoly<C::Field>>,
+    /// The coefficient and the indices in `polys` of each product
+    pub products: Vec<(C::ChallengeField, Vec<usize>)>,
+}
+
+impl<C: GKRConfig> SumOfProducts<C> {
+    pub fn new(var_num: usize) -> Self {
+        Self {
+            var_num,
+            polys: vec![],
+            products: vec![],
+        }
+    }
+
+    /// Add a multilinear polynomial and return its index
+    pub fn add_poly(&mut self, poly: MultiLinearPoly<C::Field>) -> usize {
+        assert_eq!(poly.var_num, self.var_num);
+        assert_eq!(poly.evals.len(), 1 << self.var_num);
+        self.polys.push(poly);
+        self.polys.len() - 1
+    }
+
+    /// Add the product of the polynomials `poly_ids` with the coefficient `coef`, a
+    /// polynomial may appear several times in the product
+    pub fn add_product(&mut self, coef: C::ChallengeField, poly_ids: &[usize]) {
+        assert!(!poly_ids.is_empty(), "the product is empty");
+        assert!(poly_ids.iter().all(|id| *id < self.polys.len()));
+        self.products.push((coef, poly_ids.to_vec()));
+    }
+
+    pub fn degree(&self) -> usize {
+        self.products
+            .iter()
+            .map(|(_, ids)| ids.len())
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// The value of the polynomial from the values of the multilinear polynomials
+    pub fn combine(&self, poly_evals: &[C::Field]) -> C::Field {
+        assert_eq!(poly_evals.len(), self.polys.len());
+        self.products
+            .iter()
+            .fold(C::Field::zero(), |acc, (coef, ids)| {
+                let prod = ids
+                    .iter()
+                    .fold(C::Field::one(), |prod, id| prod * poly_evals[*id]);
+                acc + prod.scale(coef)
+            })
+    }
+
+    /// The sum of the polynomial over the hypercube
+    pub fn sum(&self) -> C::Field {
+        (0..1 << self.var_num)
+            .map(|i| {
+                let evals = self.polys.iter().map(|p| p.evals[i]).collect::<Vec<_>>();
+                self.combine(&evals)
+            })
+            .fold(C::Field::zero(), |acc, v| acc + v)
+    }
+}
+
+/// Evaluate at x the polynomial whose evaluations at 0, 1, ..., len - 1 are `ps`,
+/// using Lagrange interpolation over the integer points.
+pub(crate) fn eval_at_integers<C: GKRConfig>(ps: &[C::Field], x: C::ChallengeField) -> C::Field {
+    let mut v = C::Field::zero();
+    for (i, p) in ps.iter().enumerate() {
+        let mut numerator = C::ChallengeField::one();
+        let mut denominator = C::CircuitField::one();
+        for j in 0..ps.len() {
+            if j == i {
+                continue;
+            }
+            numerator *= x - C::ChallengeField::from(j as u32);
+            denominator *= if i > j {
+                C::CircuitField::from((i - j) as u32)
+            } else {
+                -C::CircuitField::from((j - i) as u32)
+            };
+        }
+        let weight = C::challenge_mul_circuit_field(&numerator, &denominator.inv().unwrap());
+        v += p.scale(&weight);
+    }
+    v
+}
+
+/// Prove the sum of `poly` over the hypercube. The sum itself is not sent, it is expected
+/// to be known to the verifier. Returns the random point the sum is reduced to and the
+/// values of the multilinear polynomials there, which are not appended to the transcript
+/// either.
+pub fn sumcheck_poly_prove<C: GKRConfig, H: FiatShamirHash>(
+    poly: &SumOfProducts<C>,
+    transcript: &mut Transcript<H>,
+) -> (Vec<C::ChallengeField>, Vec<C::Field>) {
+    let timer = start_timer!(|| format!("sumcheck {} vars", poly.var_num));
+    let degree = poly.degree();
+    let mut bookkeeping = poly
+        .polys
+        .iter()
+        .map(|p| p.evals.clone())
+        .collect::<Vec<_>>();
+
+    let mut r = vec![];
+    let mut evals_at = vec![C::Field::zero(); bookkeeping.len()];
+    let mut steps = vec![C::Field::zero(); bookkeeping.len()];
+    for i_var in 0..poly.var_num {
+        let eval_size = 1 << (poly.var_num - i_var - 1);
+        let mut ps = vec![C::Field::zero(); degree + 1];
+        for i in 0..eval_size {
+            // walk along the line through the two halves of each table
+            for (k, table) in bookkeeping.iter().enumerate() {
+                evals_at[k] = table[i * 2];
+                steps[k] = table[i * 2 + 1] - table[i * 2];
+            }
+            for p in ps.iter_mut() {
+                *p += poly.combine(&evals_at);
+                evals_at
+                    .iter_mut()
+                    .zip(steps.iter())
+                    .for_each(|(e, s)| *e += s);
+            }
+        }
+        ps.iter().for_each(|p| transcript.append_f::<C>(*p));
+
+        let r_i = transcript.challenge_f::<C>();
+        log::trace!("i_var={} ps: {:?} r: {:?}", i_var, ps, r_i);
+        for table in bookkeeping.iter_mut() {
+            for i in 0..eval_size {
+                table[i] = table[i * 2] + (table[i * 2 + 1] - table[i * 2]).scale(&r_i);
+            }
+            table.truncate(eval_size);
+        }
+        r.push(r_i);
+    }
+    end_timer!(timer);
+    (r, bookkeeping.iter().map(|table| table[0]).collect())
+}
+
+/// Verify the sumcheck of a polynomial of `degree` over `var_num` variables summing to
+/// `claimed_sum`. Returns whether the rounds are consistent, the random point and the value
+/// the polynomial must take there, or None if the proof is truncated or malformed. The caller
+/// checks that value, for instance with `SumOfProducts::combine` on the values of the
+/// multilinear polynomials.
+pub fn sumcheck_poly_verify<C: GKRConfig, H: FiatShamirHash>(
+    var_num: usize,
+    degree: usize,
+    claimed_sum: C::Field,
+    proof: &mut Proof,
+    transcript: &mut Transcript<H>,
+) -> Option<(bool, Vec<C::ChallengeField>, C::Field)> {
+    let mut sum = claimed_sum;
+    let mut verified = true;
+    let mut r = vec![];
+    for i_var in 0..var_num {
+        let ps = (0..degree + 1)
+            .map(|_| proof.get_next_and_step::<C::Field>())
+            .collect::<Option<Vec<_>>>()?;
+        ps.iter().for_each(|p| transcript.append_f::<C>(*p));
+
+        let r_i = transcript.challenge_f::<C>();
+        log::trace!("i_var={} ps: {:?} r: {:?}", i_var, ps, r_i);
+        verified &= ps[0] + ps[1] == sum;
+        sum = eval_at_integers::<C>(&ps, r_i);
+        r.push(r_i);
+    }
+    Some((verified, r, sum))
+}
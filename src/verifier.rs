@@ -1,14 +1,19 @@
-use std::{io::Cursor, vec};
+use std::vec;
 
-use arith::{Field, SimdField};
+use arith::{Field, MultiLinearPoly, SimdField};
 use ark_std::{end_timer, start_timer};
 
 #[cfg(feature = "grinding")]
-use crate::grind;
+use crate::verify_grinding;
 
 use crate::{
-    eq_evals_at_primitive, Circuit, CircuitLayer, Config, GKRConfig, Gate, Proof, RawCommitment,
-    Transcript,
+    eq_eval_between, eq_evals_at_primitive, eq_zero_eval, eval_at_integers, input_claim_weights,
+    instance_sumcheck_degree, libra_mask_len, libra_mask_weights, mask_weights,
+    new_batch_statement_transcript, new_pcs_for_gkr, new_statement_transcript,
+    new_zk_commitment_for_gkr, vanishing_eval, Anemoihasher, BatchCircuit, Circuit, CircuitLayer,
+    Config, FiatShamirHash, FiatShamirHashType, GKRConfig, GKRScheme, Gate, GateUni,
+    Keccak256hasher, MiMC5hasher, PCSForGKR, PoseidonM31hasher, Proof, SHA256hasher, Transcript,
+    ZKCommitmentForGKR, ZKSupportError, GATE_TYPE_POW1, GATE_TYPE_POW5, INPUT_MASK_LEN,
 };
 
 #[inline]
@@ -51,63 +56,130 @@ fn eval_sparse_circuit_connect_poly<C: GKRConfig, const INPUT_NUM: usize>(
     v
 }
 
+/// Same as `eval_sparse_circuit_connect_poly`, restricted to the univariate gates of
+/// a given type, with a single output point scaled by `scale` as used by GKR^2.
+fn eval_sparse_circuit_connect_poly_uni<C: GKRConfig>(
+    gates: &[GateUni<C>],
+    gate_type: usize,
+    rz0: &[C::ChallengeField],
+    scale: C::ChallengeField,
+    rx: &[C::ChallengeField],
+) -> C::ChallengeField {
+    let mut eq_evals_at_rz0 = vec![C::ChallengeField::zero(); 1 << rz0.len()];
+    let mut eq_evals_at_rx = vec![C::ChallengeField::zero(); 1 << rx.len()];
+
+    eq_evals_at_primitive(rz0, &scale, &mut eq_evals_at_rz0);
+    eq_evals_at_primitive(rx, &C::ChallengeField::one(), &mut eq_evals_at_rx);
+
+    let mut v = C::ChallengeField::zero();
+    for g in gates.iter().filter(|g| g.gate_type == gate_type) {
+        let prod = eq_evals_at_rz0[g.o_id] * eq_evals_at_rx[g.i_ids[0]];
+        v += C::challenge_mul_circuit_field(&prod, &g.coef);
+    }
+    v
+}
+
+/// The combination alpha v0 + beta v1 of the claims on the outputs of a layer, less the
+/// contribution of its constant gates: the sum the sumcheck of the layer starts from
+fn layer_claim_sum<C: GKRConfig>(
+    layer: &CircuitLayer<C>,
+    rz0: &[C::ChallengeField],
+    rz1: &[C::ChallengeField],
+    claimed_v0: C::Field,
+    claimed_v1: C::Field,
+    alpha: C::ChallengeField,
+    beta: C::ChallengeField,
+) -> C::Field {
+    claimed_v0.scale(&alpha) + claimed_v1.scale(&beta)
+        - C::Field::from(eval_sparse_circuit_connect_poly(
+            &layer.const_,
+            rz0,
+            rz1,
+            alpha,
+            beta,
+            &[],
+        ))
+}
+
+/// The sumcheck of a layer from `sum`, see `layer_claim_sum`. In the zero-knowledge mode,
+/// `zk` holds the hiding commitments and the index of the commitment to the mask of the
+/// outputs of the layer, None for the output layer.
 // todo: FIXME
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::type_complexity)]
-fn sumcheck_verify_gkr_layer<C: GKRConfig>(
+fn sumcheck_verify_gkr_layer<C: GKRConfig, H: FiatShamirHash>(
     layer: &CircuitLayer<C>,
     rz0: &[C::ChallengeField],
     rz1: &[C::ChallengeField],
-    claimed_v0: C::Field,
-    claimed_v1: C::Field,
+    mut sum: C::Field,
     alpha: C::ChallengeField,
     beta: C::ChallengeField,
     proof: &mut Proof,
-    transcript: &mut Transcript,
-) -> (
+    transcript: &mut Transcript<H>,
+    zk: Option<(&mut dyn ZKCommitmentForGKR<C, H>, Option<usize>)>,
+) -> Option<(
     bool,
     Vec<C::ChallengeField>,
     Vec<C::ChallengeField>,
     C::Field,
     C::Field,
-) {
+)> {
     let var_num = layer.input_var_num;
-    let mut sum = claimed_v0.scale(&alpha) + claimed_v1.scale(&beta)
-        - C::Field::from(eval_sparse_circuit_connect_poly(
-            &layer.const_,
-            rz0,
-            rz1,
-            alpha,
-            beta,
-            &[],
-        ));
+
+    // the commitment to the mask g, its sum over the hypercube and rho
+    let zk = zk
+        .map(|(zk_com, output_mask_idx)| {
+            let g_idx =
+                zk_com.read_commitment(libra_mask_len(var_num * 2 + 1), proof, transcript)?;
+            let g_sum: C::Field = proof.get_next_and_step()?;
+            transcript.append_f::<C>(g_sum);
+            let rho = transcript.challenge_f::<C>();
+            sum += g_sum.scale(&rho);
+            Some((zk_com, output_mask_idx, g_idx, rho))
+        })
+        .transpose()?;
 
     let mut rx = vec![];
     let mut ry = vec![];
     let mut vx_claim = C::Field::zero();
     let mut verified = true;
     for i_var in 0..var_num * 2 {
-        let p0 = proof.get_next_and_step();
-        let p1 = proof.get_next_and_step();
-        let p2 = proof.get_next_and_step();
-        transcript.append_f::<C>(p0);
-        transcript.append_f::<C>(p1);
-        transcript.append_f::<C>(p2);
-
-        log::trace!("i_var={} p0 p1 p2: {:?} {:?} {:?}", i_var, p0, p1, p2);
-        let r = transcript.challenge_f::<C>();
+        let r;
+        if zk.is_some() {
+            // the masked round polynomials have degree 3
+            let mut ps = [C::Field::zero(); 4];
+            for p in ps.iter_mut() {
+                *p = proof.get_next_and_step()?;
+                transcript.append_f::<C>(*p);
+            }
+            log::trace!("i_var={} ps: {:?}", i_var, ps);
+            r = transcript.challenge_f::<C>();
+
+            verified &= (ps[0] + ps[1]) == sum;
+            sum = eval_at_integers::<C>(&ps, r);
+        } else {
+            let p0 = proof.get_next_and_step()?;
+            let p1 = proof.get_next_and_step()?;
+            let p2 = proof.get_next_and_step()?;
+            transcript.append_f::<C>(p0);
+            transcript.append_f::<C>(p1);
+            transcript.append_f::<C>(p2);
+
+            log::trace!("i_var={} p0 p1 p2: {:?} {:?} {:?}", i_var, p0, p1, p2);
+            r = transcript.challenge_f::<C>();
+
+            verified &= (p0 + p1) == sum;
+            sum = degree_2_eval(p0, p1, p2, r);
+        }
 
         if i_var < var_num {
             rx.push(r);
         } else {
             ry.push(r);
         }
-        verified &= (p0 + p1) == sum;
-
-        sum = degree_2_eval(p0, p1, p2, r);
 
         if i_var == var_num - 1 {
-            vx_claim = proof.get_next_and_step();
+            vx_claim = proof.get_next_and_step()?;
             sum -= vx_claim.scale(&eval_sparse_circuit_connect_poly(
                 &layer.add,
                 rz0,
@@ -119,35 +191,78 @@ fn sumcheck_verify_gkr_layer<C: GKRConfig>(
             transcript.append_f::<C>(vx_claim);
         }
     }
-    let vy_claim: C::Field = proof.get_next_and_step();
-    verified &= sum
-        == vx_claim
-            * vy_claim.scale(&eval_sparse_circuit_connect_poly(
-                &layer.mul,
-                rz0,
-                rz1,
-                alpha,
-                beta,
-                &[rx.clone(), ry.clone()],
-            ));
+    let vy_claim: C::Field = proof.get_next_and_step()?;
+    let mul_claim = vx_claim
+        * vy_claim.scale(&eval_sparse_circuit_connect_poly(
+            &layer.mul,
+            rz0,
+            rz1,
+            alpha,
+            beta,
+            &[rx.clone(), ry.clone()],
+        ));
     transcript.append_f::<C>(vy_claim);
-    (verified, rx, ry, vx_claim, vy_claim)
+
+    match zk {
+        None => verified &= sum == mul_claim,
+        Some((zk_com, output_mask_idx, g_idx, rho)) => {
+            // the round of w, then the evaluations of the output mask and g
+            let mut ps = [C::Field::zero(); 4];
+            for p in ps.iter_mut() {
+                *p = proof.get_next_and_step()?;
+                transcript.append_f::<C>(*p);
+            }
+            let rw = transcript.challenge_f::<C>();
+            verified &= (ps[0] + ps[1]) == sum;
+            sum = eval_at_integers::<C>(&ps, rw);
+
+            let mut t_eval = C::Field::zero();
+            if let Some(idx) = output_mask_idx {
+                for (rz, coef) in [(rz0, alpha), (rz1, beta)] {
+                    let weights = mask_weights(rz[0], rw);
+                    let mask_eval: C::Field = proof.get_next_and_step()?;
+                    transcript.append_f::<C>(mask_eval);
+                    verified &=
+                        zk_com.verify_inner_product(idx, &weights, mask_eval, proof, transcript);
+                    t_eval += mask_eval.scale(&(coef * vanishing_eval(rz)));
+                }
+            }
+
+            let r = [rx.as_slice(), ry.as_slice(), &[rw]].concat();
+            let g_eval: C::Field = proof.get_next_and_step()?;
+            transcript.append_f::<C>(g_eval);
+            verified &= zk_com.verify_inner_product(
+                g_idx,
+                &libra_mask_weights(&r),
+                g_eval,
+                proof,
+                transcript,
+            );
+
+            verified &= sum
+                == mul_claim.scale(&(C::ChallengeField::one() - rw))
+                    + t_eval.scale(&(eq_zero_eval(&rx) * eq_zero_eval(&ry)))
+                    + g_eval.scale(&rho);
+        }
+    }
+    Some((verified, rx, ry, vx_claim, vy_claim))
 }
 
 // todo: FIXME
 #[allow(clippy::type_complexity)]
-pub fn gkr_verify<C: GKRConfig>(
+pub fn gkr_verify<C: GKRConfig, H: FiatShamirHash>(
     circuit: &Circuit<C>,
     claimed_v: &C::Field,
-    transcript: &mut Transcript,
+    transcript: &mut Transcript<H>,
     proof: &mut Proof,
-) -> (
+    mut zk: Option<&mut dyn ZKCommitmentForGKR<C, H>>,
+) -> Option<(
     bool,
     Vec<C::ChallengeField>,
     Vec<C::ChallengeField>,
     C::Field,
     C::Field,
-) {
+)> {
     let timer = start_timer!(|| "gkr verify");
     let layer_num = circuit.layers.len();
     let mut rz0 = vec![];
@@ -164,7 +279,7 @@ pub fn gkr_verify<C: GKRConfig>(
     let mut verified = true;
     for i in (0..layer_num).rev() {
         let cur_verified;
-        (cur_verified, rz0, rz1, claimed_v0, claimed_v1) = sumcheck_verify_gkr_layer(
+        let sum = layer_claim_sum(
             &circuit.layers[i],
             &rz0,
             &rz1,
@@ -172,9 +287,23 @@ pub fn gkr_verify<C: GKRConfig>(
             claimed_v1,
             alpha,
             beta,
+        );
+        (cur_verified, rz0, rz1, claimed_v0, claimed_v1) = sumcheck_verify_gkr_layer(
+            &circuit.layers[i],
+            &rz0,
+            &rz1,
+            sum,
+            alpha,
+            beta,
             proof,
             transcript,
-        );
+            zk.as_mut().map(|zk_com| {
+                (
+                    &mut **zk_com as &mut dyn ZKCommitmentForGKR<C, H>,
+                    (i + 1 < layer_num).then_some(i + 1),
+                )
+            }),
+        )?;
         verified &= cur_verified;
         alpha = transcript.challenge_f::<C>();
         beta = transcript.challenge_f::<C>();
@@ -188,7 +317,268 @@ pub fn gkr_verify<C: GKRConfig>(
         );
     }
     end_timer!(timer);
-    (verified, rz0, rz1, claimed_v0, claimed_v1)
+    Some((verified, rz0, rz1, claimed_v0, claimed_v1))
+}
+
+/// The sumcheck of a layer of GKR^2 from `sum`, for the claim at rz0 scaled by `scale`.
+/// The sum is the claim less the contribution of the constant gates, see `layer_claim_sum`.
+fn sumcheck_verify_gkr_square_layer<C: GKRConfig, H: FiatShamirHash>(
+    layer: &CircuitLayer<C>,
+    rz0: &[C::ChallengeField],
+    mut sum: C::Field,
+    scale: C::ChallengeField,
+    proof: &mut Proof,
+    transcript: &mut Transcript<H>,
+) -> Option<(bool, Vec<C::ChallengeField>, C::Field)> {
+    const D: usize = 7;
+    let var_num = layer.input_var_num;
+
+    let mut rx = vec![];
+    let mut verified = true;
+    for i_var in 0..var_num {
+        let mut ps = [C::Field::zero(); D];
+        for p in ps.iter_mut() {
+            *p = proof.get_next_and_step()?;
+            transcript.append_f::<C>(*p);
+        }
+
+        log::trace!("i_var={} ps: {:?}", i_var, ps);
+        let r = transcript.challenge_f::<C>();
+        rx.push(r);
+
+        verified &= (ps[0] + ps[1]) == sum;
+        sum = eval_at_integers::<C>(&ps, r);
+    }
+
+    let vx_claim: C::Field = proof.get_next_and_step()?;
+    transcript.append_f::<C>(vx_claim);
+
+    // pow5 gates contribute eq(rz0, o) eq(rx, i) coef * v(x)^5, pow1 gates coef * v(x)
+    let vx_claim_5 = vx_claim.square().square() * vx_claim;
+    verified &= sum
+        == vx_claim_5.scale(&eval_sparse_circuit_connect_poly_uni(
+            &layer.uni,
+            GATE_TYPE_POW5,
+            rz0,
+            scale,
+            &rx,
+        )) + vx_claim.scale(&eval_sparse_circuit_connect_poly_uni(
+            &layer.uni,
+            GATE_TYPE_POW1,
+            rz0,
+            scale,
+            &rx,
+        ));
+    Some((verified, rx, vx_claim))
+}
+
+pub fn gkr_square_verify<C: GKRConfig, H: FiatShamirHash>(
+    circuit: &Circuit<C>,
+    claimed_v: &C::Field,
+    transcript: &mut Transcript<H>,
+    proof: &mut Proof,
+) -> Option<(bool, Vec<C::ChallengeField>, C::Field)> {
+    let timer = start_timer!(|| "gkr^2 verify");
+    let layer_num = circuit.layers.len();
+    let mut rz0 = vec![];
+    for _ in 0..circuit.layers.last().unwrap().output_var_num {
+        rz0.push(transcript.challenge_f::<C>());
+    }
+    let mut claimed_v0 = *claimed_v;
+
+    let mut verified = true;
+    for i in (0..layer_num).rev() {
+        let cur_verified;
+        let sum = layer_claim_sum(
+            &circuit.layers[i],
+            &rz0,
+            &rz0,
+            claimed_v0,
+            C::Field::zero(),
+            C::ChallengeField::one(),
+            C::ChallengeField::zero(),
+        );
+        (cur_verified, rz0, claimed_v0) = sumcheck_verify_gkr_square_layer(
+            &circuit.layers[i],
+            &rz0,
+            sum,
+            C::ChallengeField::one(),
+            proof,
+            transcript,
+        )?;
+        verified &= cur_verified;
+        log::trace!(
+            "Layer {} verified: {}, claimed_v0={:?}",
+            i,
+            cur_verified,
+            claimed_v0
+        );
+    }
+    end_timer!(timer);
+    Some((verified, rz0, claimed_v0))
+}
+
+/// Verify the layers of all the instances of a batch, see `batch_gkr_prove`. Each layer
+/// starts with the sumcheck over the instance variables, whose final claim is checked by
+/// the sumcheck of a single instance scaled by eq(rk, rk'): only the wiring of one instance
+/// is evaluated. The claims on the input layer are returned as (point, claimed value), the
+/// point being the input variables followed by the instance variables.
+// todo: FIXME
+#[allow(clippy::type_complexity)]
+pub fn batch_gkr_verify<C: GKRConfig, H: FiatShamirHash>(
+    circuit: &Circuit<C>,
+    instance_var_num: usize,
+    gkr_scheme: &GKRScheme,
+    claimed_v: &C::Field,
+    transcript: &mut Transcript<H>,
+    proof: &mut Proof,
+) -> Option<(bool, Vec<(Vec<C::ChallengeField>, C::Field)>)> {
+    let timer = start_timer!(|| "batch gkr verify");
+    let layer_num = circuit.layers.len();
+    let mut rz0 = vec![];
+    let mut rz1 = vec![];
+    for _ in 0..circuit.layers.last().unwrap().output_var_num {
+        rz0.push(transcript.challenge_f::<C>());
+        rz1.push(C::ChallengeField::zero());
+    }
+    let mut rk = vec![];
+    for _ in 0..instance_var_num {
+        rk.push(transcript.challenge_f::<C>());
+    }
+    let mut alpha = C::ChallengeField::one();
+    let mut beta = C::ChallengeField::zero();
+    let mut claimed_v0 = *claimed_v;
+    let mut claimed_v1 = C::Field::zero();
+
+    let mut verified = true;
+    for i in (0..layer_num).rev() {
+        let layer = &circuit.layers[i];
+        // the constant gates do not depend on the instance, and eq(rk, k) sums to one
+        let mut sum = match gkr_scheme {
+            GKRScheme::Vanilla => {
+                layer_claim_sum(layer, &rz0, &rz1, claimed_v0, claimed_v1, alpha, beta)
+            }
+            GKRScheme::GkrSquare => layer_claim_sum(
+                layer,
+                &rz0,
+                &rz0,
+                claimed_v0,
+                C::Field::zero(),
+                C::ChallengeField::one(),
+                C::ChallengeField::zero(),
+            ),
+        };
+
+        let mut rk_next = vec![];
+        for _ in 0..instance_var_num {
+            let mut ps = vec![C::Field::zero(); instance_sumcheck_degree(gkr_scheme) + 1];
+            for p in ps.iter_mut() {
+                *p = proof.get_next_and_step()?;
+                transcript.append_f::<C>(*p);
+            }
+            let r = transcript.challenge_f::<C>();
+            log::trace!("instance ps: {:?} r: {:?}", ps, r);
+
+            verified &= (ps[0] + ps[1]) == sum;
+            sum = eval_at_integers::<C>(&ps, r);
+            rk_next.push(r);
+        }
+        let eq_rk = eq_eval_between(&rk, &rk_next);
+
+        let cur_verified;
+        match gkr_scheme {
+            GKRScheme::Vanilla => {
+                (cur_verified, rz0, rz1, claimed_v0, claimed_v1) = sumcheck_verify_gkr_layer(
+                    layer,
+                    &rz0,
+                    &rz1,
+                    sum,
+                    alpha * eq_rk,
+                    beta * eq_rk,
+                    proof,
+                    transcript,
+                    None,
+                )?;
+                alpha = transcript.challenge_f::<C>();
+                beta = transcript.challenge_f::<C>();
+            }
+            GKRScheme::GkrSquare => {
+                (cur_verified, rz0, claimed_v0) =
+                    sumcheck_verify_gkr_square_layer(layer, &rz0, sum, eq_rk, proof, transcript)?;
+            }
+        }
+        verified &= cur_verified;
+        rk = rk_next;
+        log::trace!("Layer {} of the batch verified: {}", i, cur_verified);
+    }
+
+    let claims = match gkr_scheme {
+        GKRScheme::Vanilla => vec![
+            ([rz0, rk.clone()].concat(), claimed_v0),
+            ([rz1, rk].concat(), claimed_v1),
+        ],
+        GKRScheme::GkrSquare => vec![([rz0, rk].concat(), claimed_v0)],
+    };
+    end_timer!(timer);
+    Some((verified, claims))
+}
+
+/// Check the claims on the input layer against the commitment to the private inputs, the
+/// points being the input variables followed by the instance variables of a batch if any.
+/// When the circuit has public inputs, the verifier evaluates them with `eval_public_inputs`
+/// and reads the claim on the private inputs.
+fn verify_input_claims<C: GKRConfig, H: FiatShamirHash>(
+    pcs: &mut dyn PCSForGKR<C, H>,
+    circuit: &Circuit<C>,
+    eval_public_inputs: impl Fn(&[C::ChallengeField]) -> C::Field,
+    claims: &[(Vec<C::ChallengeField>, C::Field)],
+    proof: &mut Proof,
+    transcript: &mut Transcript<H>,
+) -> Option<bool> {
+    let mut verified = true;
+    for (i, (rz, claimed_v)) in claims.iter().enumerate() {
+        let rz_private = circuit.private_input_point(rz);
+        let private_v = if circuit.public_input_offset.is_some() {
+            // claimed_v = eq(rz_public, 0) private_v + the MLE of the public inputs
+            let private_v: C::Field = proof.get_next_and_step()?;
+            transcript.append_f::<C>(private_v);
+            let rz_public = &rz[circuit.log_private_input_size()..circuit.log_input_size()];
+            verified &=
+                *claimed_v == private_v.scale(&eq_zero_eval(rz_public)) + eval_public_inputs(rz);
+            private_v
+        } else {
+            *claimed_v
+        };
+        let v = pcs.verify(&rz_private, private_v, proof, transcript);
+        log::debug!("commitment verification {}: {}", i, v);
+        verified &= v;
+    }
+    Some(verified)
+}
+
+/// The outputs of the circuit the verifier expects, so that the claim on the output layer
+/// is computed by the verifier instead of being taken from the prover.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpectedOutputs<C: GKRConfig> {
+    /// all the outputs are zero, as for circuits made of constraints
+    Zero,
+    /// the public output values, one per output gate
+    Values(Vec<C::Field>),
+}
+
+impl<C: GKRConfig> ExpectedOutputs<C> {
+    /// The MLE of the outputs at rz0, or None if the number of values does not match.
+    pub fn evaluate(&self, rz0: &[C::ChallengeField]) -> Option<C::Field> {
+        match self {
+            ExpectedOutputs::Zero => Some(C::Field::zero()),
+            ExpectedOutputs::Values(values) => {
+                if values.len() != 1 << rz0.len() {
+                    return None;
+                }
+                Some(MultiLinearPoly::<C::Field>::eval_multilinear(values, rz0))
+            }
+        }
+    }
 }
 
 pub struct Verifier<C: GKRConfig> {
@@ -210,54 +600,333 @@ impl<C: GKRConfig> Verifier<C> {
         }
     }
 
+    /// Verify the proof for the output claim `claimed_v` given by the prover.
+    /// The claim itself is not checked against the outputs of the circuit,
+    /// see `verify_with_outputs`.
     pub fn verify(&self, circuit: &Circuit<C>, claimed_v: &C::Field, proof: &Proof) -> bool {
-        let timer = start_timer!(|| "verify");
+        self.verify_internal(circuit, |_| Some(*claimed_v), proof)
+    }
 
-        let poly_size = circuit.layers.first().unwrap().input_vals.evals.len();
-        let mut cursor = Cursor::new(&proof.bytes);
+    /// Verify the proof for the outputs the circuit is expected to produce. The claim on the
+    /// output layer is the MLE of the expected outputs at the output challenge rz0.
+    pub fn verify_with_outputs(
+        &self,
+        circuit: &Circuit<C>,
+        expected_outputs: &ExpectedOutputs<C>,
+        proof: &Proof,
+    ) -> bool {
+        self.verify_internal(circuit, |rz0| expected_outputs.evaluate(rz0), proof)
+    }
 
-        let commitment = RawCommitment::<C>::deserialize_from(&mut cursor, poly_size);
+    /// Verify the proof of all the instances of `batch` for the outputs expected from each
+    /// of them, one per instance. The public inputs of the instances are those set in
+    /// `batch`. The zero-knowledge mode does not support batches.
+    pub fn verify_batch(
+        &self,
+        batch: &BatchCircuit<C>,
+        expected_outputs: &[ExpectedOutputs<C>],
+        proof: &Proof,
+    ) -> bool {
+        match batch.expected_outputs(expected_outputs) {
+            Some(outputs) => self.verify_batch_internal(batch, |rz| outputs.evaluate(rz), proof),
+            None => {
+                log::debug!("the expected outputs do not match the instances");
+                false
+            }
+        }
+    }
 
-        let mut transcript = Transcript::new();
-        transcript.append_u8_slice(&proof.bytes[..commitment.size()]);
+    /// Verify the proof of all the instances of `batch` for the output claim `claimed_v`
+    /// given by the prover, see `verify`
+    pub fn verify_batch_with_claim(
+        &self,
+        batch: &BatchCircuit<C>,
+        claimed_v: &C::Field,
+        proof: &Proof,
+    ) -> bool {
+        self.verify_batch_internal(batch, |_| Some(*claimed_v), proof)
+    }
 
-        // ZZ: shall we use probabilistic grinding so the verifier can avoid this cost?
-        // (and also be recursion friendly)
-        #[cfg(feature = "grinding")]
-        grind::<C>(&mut transcript, &self.config);
+    fn verify_batch_internal(
+        &self,
+        batch: &BatchCircuit<C>,
+        output_claim: impl Fn(&[C::ChallengeField]) -> Option<C::Field>,
+        proof: &Proof,
+    ) -> bool {
+        if self.config.zk {
+            log::debug!("{}", ZKSupportError::UnsupportedBatch);
+            return false;
+        }
+        match self.config.fs_hash {
+            FiatShamirHashType::SHA256 => {
+                self.verify_batch_with_hash::<SHA256hasher>(batch, output_claim, proof)
+            }
+            FiatShamirHashType::Keccak256 => {
+                self.verify_batch_with_hash::<Keccak256hasher>(batch, output_claim, proof)
+            }
+            FiatShamirHashType::Poseidon => {
+                self.verify_batch_with_hash::<PoseidonM31hasher>(batch, output_claim, proof)
+            }
+            FiatShamirHashType::MIMC7 => {
+                self.verify_batch_with_hash::<MiMC5hasher>(batch, output_claim, proof)
+            }
+            FiatShamirHashType::Animoe => {
+                self.verify_batch_with_hash::<Anemoihasher>(batch, output_claim, proof)
+            }
+        }
+    }
 
-        let mut proof = proof.clone(); // FIXME: consider separating pointers to make proof always immutable?
+    fn verify_batch_with_hash<H: FiatShamirHash>(
+        &self,
+        batch: &BatchCircuit<C>,
+        output_claim: impl Fn(&[C::ChallengeField]) -> Option<C::Field>,
+        proof: &Proof,
+    ) -> bool {
+        self.read_and_verify_batch::<H>(batch, output_claim, proof)
+            .unwrap_or_else(|| {
+                log::debug!("the proof is truncated or malformed");
+                false
+            })
+    }
 
-        #[cfg(feature = "grinding")]
-        proof.step(commitment.size() + 32);
-        #[cfg(not(feature = "grinding"))]
-        proof.step(commitment.size());
+    /// None if the proof ends early or holds bytes that are not field elements or points
+    fn read_and_verify_batch<H: FiatShamirHash>(
+        &self,
+        batch: &BatchCircuit<C>,
+        output_claim: impl Fn(&[C::ChallengeField]) -> Option<C::Field>,
+        proof: &Proof,
+    ) -> Option<bool> {
+        let timer = start_timer!(|| "verify batch");
+
+        let mut pcs = new_pcs_for_gkr::<C, H>(&self.config, batch.log_private_input_size());
+
+        let mut proof = proof.clone();
+        let mut transcript =
+            new_batch_statement_transcript::<C, H>(batch, &self.config, pcs.as_ref());
+        if !pcs.read_commitment(&mut proof, &mut transcript) {
+            return None;
+        }
 
-        let (mut verified, rz0, rz1, claimed_v0, claimed_v1) =
-            gkr_verify(circuit, claimed_v, &mut transcript, &mut proof);
+        #[cfg(feature = "grinding")]
+        if self.config.grinding_bits > 0
+            && !verify_grinding::<C, H>(&mut transcript, &mut proof, self.config.grinding_bits)
+        {
+            log::debug!("the grinding nonce does not have enough leading zeros");
+            end_timer!(timer);
+            return Some(false);
+        }
 
+        // the random coefficients shared by the instances, as sampled by the prover
+        let filled_circuit = batch.circuit.has_rand_coef().then(|| {
+            let mut circuit = batch.circuit.clone();
+            circuit.fill_rand_coef(&mut transcript);
+            circuit
+        });
+        let circuit = filled_circuit.as_ref().unwrap_or(&batch.circuit);
+
+        // the output point is made of the output variables and the instance variables
+        let rz = {
+            let mut transcript = transcript.clone();
+            (0..circuit.layers.last().unwrap().output_var_num + batch.instance_var_num())
+                .map(|_| transcript.challenge_f::<C>())
+                .collect::<Vec<_>>()
+        };
+        let claimed_v = match output_claim(&rz) {
+            Some(claimed_v) => claimed_v,
+            None => {
+                log::debug!("the expected outputs do not match the output layer");
+                end_timer!(timer);
+                return Some(false);
+            }
+        };
+
+        let (mut verified, claims) = batch_gkr_verify(
+            circuit,
+            batch.instance_var_num(),
+            &self.config.gkr_scheme,
+            &claimed_v,
+            &mut transcript,
+            &mut proof,
+        )?;
         log::info!("GKR verification: {}", verified);
 
-        match self.config.polynomial_commitment_type {
-            crate::PolynomialCommitmentType::Raw => {
-                // for Raw, no need to load from proof
-                log::trace!("rz0.size() = {}", rz0.len());
-                log::trace!("Poly_vals.size() = {}", commitment.poly_vals.len());
+        verified &= verify_input_claims(
+            pcs.as_mut(),
+            circuit,
+            |rz| batch.eval_public_inputs(rz),
+            &claims,
+            &mut proof,
+            &mut transcript,
+        )?;
 
-                let v1 = commitment.verify(&rz0, claimed_v0);
-                let v2 = commitment.verify(&rz1, claimed_v1);
+        end_timer!(timer);
+        Some(verified)
+    }
 
-                log::debug!("first commitment verification: {}", v1);
-                log::debug!("second commitment verification: {}", v2);
+    fn verify_internal(
+        &self,
+        circuit: &Circuit<C>,
+        output_claim: impl Fn(&[C::ChallengeField]) -> Option<C::Field>,
+        proof: &Proof,
+    ) -> bool {
+        match self.config.fs_hash {
+            FiatShamirHashType::SHA256 => {
+                self.verify_with_hash::<SHA256hasher>(circuit, output_claim, proof)
+            }
+            FiatShamirHashType::Keccak256 => {
+                self.verify_with_hash::<Keccak256hasher>(circuit, output_claim, proof)
+            }
+            FiatShamirHashType::Poseidon => {
+                self.verify_with_hash::<PoseidonM31hasher>(circuit, output_claim, proof)
+            }
+            FiatShamirHashType::MIMC7 => {
+                self.verify_with_hash::<MiMC5hasher>(circuit, output_claim, proof)
+            }
+            FiatShamirHashType::Animoe => {
+                self.verify_with_hash::<Anemoihasher>(circuit, output_claim, proof)
+            }
+        }
+    }
 
-                verified &= v1;
-                verified &= v2;
+    fn verify_with_hash<H: FiatShamirHash>(
+        &self,
+        circuit: &Circuit<C>,
+        output_claim: impl Fn(&[C::ChallengeField]) -> Option<C::Field>,
+        proof: &Proof,
+    ) -> bool {
+        self.read_and_verify::<H>(circuit, output_claim, proof)
+            .unwrap_or_else(|| {
+                log::debug!("the proof is truncated or malformed");
+                false
+            })
+    }
+
+    /// None if the proof ends early or holds bytes that are not field elements or points
+    fn read_and_verify<H: FiatShamirHash>(
+        &self,
+        circuit: &Circuit<C>,
+        output_claim: impl Fn(&[C::ChallengeField]) -> Option<C::Field>,
+        proof: &Proof,
+    ) -> Option<bool> {
+        let timer = start_timer!(|| "verify");
+
+        let mut pcs = new_pcs_for_gkr::<C, H>(&self.config, circuit.log_private_input_size());
+
+        let mut proof = proof.clone(); // FIXME: consider separating pointers to make proof always immutable?
+        let mut transcript = new_statement_transcript::<C, H>(circuit, &self.config, pcs.as_ref());
+        let mut zk_com = None;
+        if self.config.zk {
+            let mut com = match new_zk_commitment_for_gkr::<C, H>(circuit, &self.config) {
+                Ok(com) => com,
+                Err(e) => {
+                    log::debug!("{}", e);
+                    end_timer!(timer);
+                    return Some(false);
+                }
+            };
+            com.read_input_commitment(
+                circuit.log_private_input_size(),
+                INPUT_MASK_LEN,
+                &mut proof,
+                &mut transcript,
+            )?;
+            for _ in 1..circuit.layers.len() {
+                com.read_commitment(INPUT_MASK_LEN, &mut proof, &mut transcript)?;
+            }
+            zk_com = Some(com);
+        } else if !pcs.read_commitment(&mut proof, &mut transcript) {
+            return None;
+        }
+
+        #[cfg(feature = "grinding")]
+        if self.config.grinding_bits > 0
+            && !verify_grinding::<C, H>(&mut transcript, &mut proof, self.config.grinding_bits)
+        {
+            log::debug!("the grinding nonce does not have enough leading zeros");
+            end_timer!(timer);
+            return Some(false);
+        }
+
+        // the random coefficients, as sampled by the prover
+        let filled_circuit = circuit.has_rand_coef().then(|| {
+            let mut circuit = circuit.clone();
+            circuit.fill_rand_coef(&mut transcript);
+            circuit
+        });
+        let circuit = filled_circuit.as_ref().unwrap_or(circuit);
+
+        // the output challenge rz0 is the first one drawn by both GKR schemes
+        let rz0 = {
+            let mut transcript = transcript.clone();
+            (0..circuit.layers.last().unwrap().output_var_num)
+                .map(|_| transcript.challenge_f::<C>())
+                .collect::<Vec<_>>()
+        };
+        let claimed_v = match output_claim(&rz0) {
+            Some(claimed_v) => claimed_v,
+            None => {
+                log::debug!("the expected outputs do not match the output layer");
+                end_timer!(timer);
+                return Some(false);
+            }
+        };
+
+        // claims on the input layer, as pairs of (point, claimed value)
+        let mut verified = true;
+        let mut claims = vec![];
+        match self.config.gkr_scheme {
+            GKRScheme::Vanilla => {
+                let (v, rz0, rz1, claimed_v0, claimed_v1) = gkr_verify(
+                    circuit,
+                    &claimed_v,
+                    &mut transcript,
+                    &mut proof,
+                    zk_com
+                        .as_mut()
+                        .map(|com| com.as_mut() as &mut dyn ZKCommitmentForGKR<C, H>),
+                )?;
+                verified &= v;
+                claims.push((rz0, claimed_v0));
+                claims.push((rz1, claimed_v1));
+            }
+            GKRScheme::GkrSquare => {
+                let (v, rz0, claimed_v0) =
+                    gkr_square_verify(circuit, &claimed_v, &mut transcript, &mut proof)?;
+                verified &= v;
+                claims.push((rz0, claimed_v0));
+            }
+        }
+
+        log::info!("GKR verification: {}", verified);
+
+        match zk_com.as_mut() {
+            Some(com) => {
+                for (i, (rz, claimed_v)) in claims.iter().enumerate() {
+                    let v = com.verify_input_claim(
+                        0,
+                        &input_claim_weights(circuit, rz),
+                        *claimed_v - circuit.eval_public_inputs(rz),
+                        &mut proof,
+                        &mut transcript,
+                    );
+                    log::debug!("commitment verification {}: {}", i, v);
+                    verified &= v;
+                }
+            }
+            None => {
+                verified &= verify_input_claims(
+                    pcs.as_mut(),
+                    circuit,
+                    |rz| circuit.eval_public_inputs(rz),
+                    &claims,
+                    &mut proof,
+                    &mut transcript,
+                )?;
             }
-            _ => todo!(),
         }
 
         end_timer!(timer);
 
-        verified
+        Some(verified)
     }
 }
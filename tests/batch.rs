@@ -0,0 +1,152 @@
+use arith::Field;
+use ark_std::test_rng;
+use expander_rs::{
+    BN254Config, BatchCircuit, Circuit, CircuitLayer, Config, ExpectedOutputs, GKRConfig,
+    GKRScheme, GateUni, M31ExtConfig, Prover, Verifier, ZKSupportError, GATE_TYPE_POW5,
+};
+
+mod common;
+use common::gen_mixing_circuit;
+
+fn random_inputs<C: GKRConfig>(circuit: &Circuit<C>, instance_num: usize) -> Vec<Vec<C::Field>> {
+    let mut rng = test_rng();
+    (0..instance_num)
+        .map(|_| {
+            (0..1 << circuit.log_input_size())
+                .map(|_| C::Field::random_unsafe(&mut rng))
+                .collect()
+        })
+        .collect()
+}
+
+fn instance_outputs<C: GKRConfig>(circuit: &Circuit<C>, inputs: &[C::Field]) -> Vec<C::Field> {
+    let mut circuit = circuit.clone();
+    circuit.layers[0].input_vals.evals = inputs.to_vec();
+    circuit.evaluate();
+    circuit.layers.last().unwrap().output_vals.evals.clone()
+}
+
+fn test_batch_helper<C: GKRConfig>(circuit: &Circuit<C>, config: &Config<C>, instance_num: usize) {
+    let inputs = random_inputs(circuit, instance_num);
+    let mut batch = BatchCircuit::new(circuit, instance_num);
+    batch.set_inputs(&inputs);
+
+    // the batch computes every instance
+    let outputs = inputs
+        .iter()
+        .map(|inputs| instance_outputs(circuit, inputs))
+        .collect::<Vec<_>>();
+    if !circuit.has_rand_coef() {
+        assert_eq!(batch.outputs(), outputs);
+    }
+
+    let mut prover = Prover::new(config);
+    prover.prepare_mem(&batch.circuit);
+    let (claimed_v, proof) = prover.prove_batch(&batch);
+
+    // the verifier only knows the public inputs
+    let mut verifier_batch = BatchCircuit::new(circuit, instance_num);
+    let private_size = 1 << circuit.log_private_input_size();
+    verifier_batch.set_public_inputs(
+        &inputs
+            .iter()
+            .map(|inputs| inputs[private_size..].to_vec())
+            .collect::<Vec<_>>(),
+    );
+
+    let verifier = Verifier::new(config);
+    if circuit.has_rand_coef() {
+        // the outputs depend on the coefficients sampled during the proof, only the claim can
+        // be checked
+        assert!(verifier.verify_batch_with_claim(&verifier_batch, &claimed_v, &proof));
+        assert!(!verifier.verify_batch_with_claim(
+            &verifier_batch,
+            &(claimed_v + C::Field::one()),
+            &proof
+        ));
+        return;
+    }
+    let expected_outputs = batch
+        .outputs()
+        .into_iter()
+        .map(ExpectedOutputs::Values)
+        .collect::<Vec<_>>();
+    assert!(verifier.verify_batch(&verifier_batch, &expected_outputs, &proof));
+
+    // the outputs of every instance are checked
+    let mut wrong_outputs = expected_outputs.clone();
+    if let ExpectedOutputs::Values(values) = &mut wrong_outputs[instance_num - 1] {
+        values[0] += C::Field::one();
+    }
+    assert!(!verifier.verify_batch(&verifier_batch, &wrong_outputs, &proof));
+    assert!(!verifier.verify_batch(&verifier_batch, &expected_outputs[1..], &proof));
+    assert!(!verifier.verify_batch(
+        &verifier_batch,
+        &vec![ExpectedOutputs::Zero; instance_num],
+        &proof
+    ));
+
+    // and so are their public inputs
+    if circuit.public_input_offset.is_some() {
+        let mut public_inputs = inputs
+            .iter()
+            .map(|inputs| inputs[private_size..].to_vec())
+            .collect::<Vec<_>>();
+        public_inputs[instance_num - 1][0] += C::Field::one();
+        let mut other_batch = BatchCircuit::new(circuit, instance_num);
+        other_batch.set_public_inputs(&public_inputs);
+        assert!(!verifier.verify_batch(&other_batch, &expected_outputs, &proof));
+    }
+}
+
+#[test]
+fn test_batch_vanilla() {
+    let config = Config::<M31ExtConfig>::new(GKRScheme::Vanilla);
+    let circuit = gen_mixing_circuit::<M31ExtConfig>(4, 3);
+    test_batch_helper(&circuit, &config, 1);
+    test_batch_helper(&circuit, &config, 8);
+
+    let mut circuit = gen_mixing_circuit::<M31ExtConfig>(4, 3);
+    circuit.set_public_input_offset(8);
+    test_batch_helper(&circuit, &config, 4);
+
+    // the add gates of the second layer come after its 16 mul gates
+    let mut config = Config::<BN254Config>::new(GKRScheme::Vanilla);
+    let mut circuit = gen_mixing_circuit::<BN254Config>(4, 3);
+    circuit.layers[1].rand_coef_idx = (16..32).collect();
+    test_batch_helper(&circuit, &config, 4);
+
+    // the zero-knowledge mode does not support batches
+    config.zk = true;
+    let mut batch = BatchCircuit::new(&circuit, 4);
+    batch.set_inputs(&random_inputs(&circuit, 4));
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&batch.circuit);
+    assert_eq!(
+        prover.try_prove_batch(&batch).unwrap_err(),
+        ZKSupportError::UnsupportedBatch
+    );
+}
+
+#[test]
+fn test_batch_gkr_square() {
+    let config = Config::<M31ExtConfig>::new(GKRScheme::GkrSquare);
+    let mut circuit = Circuit::<M31ExtConfig>::default();
+    for _ in 0..2 {
+        let mut layer = CircuitLayer {
+            input_var_num: 4,
+            output_var_num: 4,
+            ..Default::default()
+        };
+        for i in 0..16 {
+            layer.uni.push(GateUni {
+                i_ids: [i],
+                o_id: i,
+                coef: <M31ExtConfig as GKRConfig>::CircuitField::from(1),
+                gate_type: GATE_TYPE_POW5,
+            });
+        }
+        circuit.layers.push(layer);
+    }
+    test_batch_helper(&circuit, &config, 4);
+}
@@ -0,0 +1,111 @@
+//! Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use expander_rs::{
+    BN254Config, Circuit, CircuitLayer, ExpectedOutputs, GKRConfig, GateAdd, GateConst, GateMul,
+    GateUni, MultilinearKZG, PolynomialCommitment, GATE_TYPE_POW1, GATE_TYPE_POW5,
+};
+
+/// The parameters of a trusted setup for the tests, whose trapdoor is thrown away, saved in
+/// a temporary file that is deleted when the guard is dropped.
+pub struct KZGSRSFile {
+    pub path: String,
+}
+
+impl KZGSRSFile {
+    pub fn new(name: &str, num_vars: usize) -> Self {
+        // the parameters are cached by path, every file gets its own
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let params = <MultilinearKZG as PolynomialCommitment<BN254Config>>::setup(
+            num_vars,
+            rand::thread_rng(),
+        );
+        let path = std::env::temp_dir().join(format!(
+            "expander_{}_{}_{}.srs",
+            name,
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let path = path.to_str().unwrap().to_string();
+        params.save(&path);
+        Self { path }
+    }
+}
+
+impl Drop for KZGSRSFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// The outputs of the evaluated circuit, for `Verifier::verify_with_outputs`
+pub fn circuit_outputs<C: GKRConfig>(circuit: &Circuit<C>) -> ExpectedOutputs<C> {
+    ExpectedOutputs::Values(circuit.layers.last().unwrap().output_vals.evals.clone())
+}
+
+// a layer mixing the inputs with add and mul gates, so that every input reaches the outputs
+pub fn gen_mixing_circuit<C: GKRConfig>(var_num: usize, layer_num: usize) -> Circuit<C> {
+    let mut circuit = Circuit::default();
+    let n = 1 << var_num;
+    for _ in 0..layer_num {
+        let mut layer = CircuitLayer::<C> {
+            input_var_num: var_num,
+            output_var_num: var_num,
+            ..Default::default()
+        };
+        for i in 0..n {
+            layer.mul.push(GateMul {
+                i_ids: [i, (i + n / 2) % n],
+                o_id: i,
+                coef: C::CircuitField::from(2),
+                gate_type: 0,
+            });
+            layer.add.push(GateAdd {
+                i_ids: [(i + 1) % n],
+                o_id: i,
+                coef: C::CircuitField::from(1),
+                gate_type: 1,
+            });
+        }
+        circuit.layers.push(layer);
+    }
+    circuit
+}
+
+// layers of x^5 + 3x mixing the inputs in the same way, as in the poseidon rounds, with a
+// round constant on every output, for GKR^2
+pub fn gen_pow5_circuit<C: GKRConfig>(var_num: usize, layer_num: usize) -> Circuit<C> {
+    let mut circuit = Circuit::default();
+    let n = 1 << var_num;
+    for _ in 0..layer_num {
+        let mut layer = CircuitLayer::<C> {
+            input_var_num: var_num,
+            output_var_num: var_num,
+            ..Default::default()
+        };
+        for i in 0..n {
+            layer.uni.push(GateUni {
+                i_ids: [i],
+                o_id: i,
+                coef: C::CircuitField::from(1),
+                gate_type: GATE_TYPE_POW5,
+            });
+            layer.uni.push(GateUni {
+                i_ids: [(i + n / 2) % n],
+                o_id: i,
+                coef: C::CircuitField::from(3),
+                gate_type: GATE_TYPE_POW1,
+            });
+            layer.const_.push(GateConst {
+                i_ids: [],
+                o_id: i,
+                coef: C::CircuitField::from(i as u32 + 7),
+                gate_type: 0,
+            });
+        }
+        circuit.layers.push(layer);
+    }
+    circuit
+}
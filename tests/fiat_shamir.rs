@@ -0,0 +1,367 @@
+use arith::{Field, FieldSerde, M31};
+use expander_rs::{
+    Anemoihasher, BN254Config, Config, FiatShamirHash, FiatShamirHashType, GKRConfig, GKRScheme,
+    GateAdd, Keccak256hasher, M31ExtConfig, MiMC5hasher, PoseidonM31Params, PoseidonM31hasher,
+    Prover, SHA256hasher, Transcript, Verifier,
+};
+
+mod common;
+use common::{circuit_outputs, gen_mixing_circuit};
+
+const FS_HASHES: [FiatShamirHashType; 5] = [
+    FiatShamirHashType::SHA256,
+    FiatShamirHashType::Keccak256,
+    FiatShamirHashType::Poseidon,
+    FiatShamirHashType::MIMC7,
+    FiatShamirHashType::Animoe,
+];
+
+// keccak256 of the empty string, as returned by the EVM
+const KECCAK_EMPTY: [u8; 32] = [
+    197, 210, 70, 1, 134, 247, 35, 60, 146, 126, 125, 178, 220, 199, 3, 192, 229, 0, 182, 83, 202,
+    130, 39, 59, 123, 250, 216, 4, 93, 133, 164, 112,
+];
+
+#[test]
+fn check_keccak256() {
+    let mut out = [0u8; 32];
+    Keccak256hasher.hash(&mut out, &[], 0);
+    assert_eq!(out, KECCAK_EMPTY);
+}
+
+fn challenges_after<H: FiatShamirHash>(messages: &[&[u8]]) -> Vec<u8> {
+    let mut transcript = Transcript::<H>::new();
+    let mut challenges = vec![];
+    for m in messages {
+        transcript.append_u8_slice(m);
+        challenges.push(format!("{:?}", transcript.challenge_f::<BN254Config>()));
+    }
+    challenges.concat().into_bytes()
+}
+
+fn check_transcript_helper<H: FiatShamirHash>() {
+    // the challenges depend on all the messages so far, not only the last ones
+    assert_ne!(
+        challenges_after::<H>(&[&[1], &[3]]),
+        challenges_after::<H>(&[&[2], &[3]])
+    );
+    assert_eq!(
+        challenges_after::<H>(&[&[1], &[3]]),
+        challenges_after::<H>(&[&[1], &[3]])
+    );
+}
+
+#[test]
+fn check_transcript_chaining() {
+    check_transcript_helper::<SHA256hasher>();
+    check_transcript_helper::<Keccak256hasher>();
+    check_transcript_helper::<PoseidonM31hasher>();
+    check_transcript_helper::<MiMC5hasher>();
+    check_transcript_helper::<Anemoihasher>();
+    assert_ne!(
+        challenges_after::<SHA256hasher>(&[&[1]]),
+        challenges_after::<Keccak256hasher>(&[&[1]])
+    );
+    assert_ne!(
+        challenges_after::<SHA256hasher>(&[&[1]]),
+        challenges_after::<PoseidonM31hasher>(&[&[1]])
+    );
+    assert_ne!(
+        challenges_after::<MiMC5hasher>(&[&[1]]),
+        challenges_after::<Anemoihasher>(&[&[1]])
+    );
+}
+
+#[test]
+fn check_poseidon_m31_native() {
+    type F = <M31ExtConfig as GKRConfig>::Field;
+    type ChallengeF = <M31ExtConfig as GKRConfig>::ChallengeField;
+
+    let f = F::from(ChallengeF::from(7));
+    let mut transcript = Transcript::<PoseidonM31hasher>::new();
+    transcript.append_f::<M31ExtConfig>(f);
+    let challenge = transcript.challenge_f::<M31ExtConfig>();
+
+    // the field element is absorbed as its M31 words after the zero initial digest, and the
+    // challenge is made of the first words of the new digest
+    let mut words = vec![M31::zero(); 8];
+    let mut bytes = vec![];
+    f.serialize_into(&mut bytes);
+    words.extend(bytes.chunks(4).map(M31::deserialize_from));
+    let digest = transcript.hasher.params.hash(&words);
+    assert_eq!(challenge.v, digest[..3]);
+}
+
+// The permutation vector is the one of the width 16 test of the Mersenne31 Poseidon2 of
+// Plonky3, `test_poseidon2_width_16_random`. The sponge digests come from a Python port of that
+// permutation written apart from this crate, which reproduces the Plonky3 vector; they were not
+// produced by Plonky3.
+#[test]
+fn check_poseidon_m31_known_answers() {
+    let params = PoseidonM31Params::default();
+    assert_eq!(params.external_round_constants[0][0], M31::from(670752198));
+    assert_eq!(params.internal_round_constants[0], M31::from(129024239));
+
+    let mut state = [
+        894848333, 1437655012, 1200606629, 1690012884, 71131202, 1749206695, 1717947831, 120589055,
+        19776022, 42382981, 1831865506, 724844064, 171220207, 1299207443, 227047920, 1783754913,
+    ]
+    .map(M31::from);
+    params.permute(&mut state);
+    let expected = [
+        1124552602, 2127602268, 1834113265, 1207687593, 1891161485, 245915620, 981277919,
+        627265710, 1534924153, 1580826924, 887997842, 1526280482, 547791593, 1028672510,
+        1803086471, 323071277,
+    ]
+    .map(M31::from);
+    assert_eq!(state, expected);
+
+    let digests: [(Vec<u32>, [u32; 8]); 3] = [
+        (
+            vec![],
+            [
+                1187828704, 629957687, 1051427610, 143297874, 1267748112, 1994222365, 1352989975,
+                1453813432,
+            ],
+        ),
+        (
+            vec![1, 2, 3],
+            [
+                153300150, 1526222908, 1371549552, 1388668565, 1227555886, 1693881857, 1857423866,
+                831548450,
+            ],
+        ),
+        (
+            (0..10).collect(),
+            [
+                1130299022, 104747609, 883021956, 1168758370, 1751556971, 487537705, 1150800372,
+                1810046864,
+            ],
+        ),
+    ];
+    digests.iter().for_each(|(input, digest)| {
+        let input = input.iter().map(|w| M31::from(*w)).collect::<Vec<_>>();
+        assert_eq!(params.hash(&input), digest.map(M31::from));
+    });
+}
+
+#[test]
+fn check_bn254_hashes_native() {
+    type F = <BN254Config as GKRConfig>::Field;
+
+    // the field element is absorbed as it is after the zero initial digest, and the challenge
+    // is the new digest
+    let f = F::from(7u32);
+    let mut transcript = Transcript::<MiMC5hasher>::new();
+    transcript.append_f::<BN254Config>(f);
+    let challenge = transcript.challenge_f::<BN254Config>();
+    assert_eq!(challenge, transcript.hasher.hash_frs(&[F::from(0u32), f]));
+
+    let mut transcript = Transcript::<Anemoihasher>::new();
+    transcript.append_f::<BN254Config>(f);
+    let challenge = transcript.challenge_f::<BN254Config>();
+    assert_eq!(challenge, transcript.hasher.hash_frs(&[F::from(0u32), f]));
+}
+
+// The digests of a Python port of the MiMC of gnark-crypto v0.10.0 and later,
+// `ecc/bn254/fr/mimc`, and of the Anemoi reference sponge, written apart from this crate. They
+// were not produced by running gnark, which was not available.
+#[test]
+fn check_bn254_hashes_known_answers() {
+    type F = <BN254Config as GKRConfig>::Field;
+
+    let inputs = [
+        vec![F::from(0u32)],
+        vec![F::from(1u32), F::from(2u32)],
+        vec![-F::one(), F::from(0u32), F::from(3u32)],
+    ];
+
+    let mimc = MiMC5hasher::default();
+    assert_eq!(mimc.constants.len(), 110);
+    assert_eq!(
+        format!("{:?}", mimc.constants[0]),
+        "0x00808370c37267481fb91b077899955706f209e5e0762dac2c79ba1e7a91b018"
+    );
+    assert_eq!(
+        format!("{:?}", mimc.constants[109]),
+        "0x2075888a58fb95ac51d3db00013c2b4cccb4ece51ac65594e7d31d81ae3a2262"
+    );
+    let digests = [
+        "0x2c7298fd87d3039ffea208538f6b297b60b373a63792b4cd0654fdc88fd0d6ee",
+        "0x07f751d627280b8f73ebe288d68acd77dc2fd6962debda017df192e355065814",
+        "0x08ddcfc18943640f0b831ecdd152ab903e183c4ce1991772efe3d542457791ea",
+    ];
+    inputs
+        .iter()
+        .zip(digests.iter())
+        .for_each(|(input, digest)| {
+            assert_eq!(format!("{:?}", mimc.hash_frs(input)), *digest);
+        });
+
+    let anemoi = Anemoihasher::default();
+    assert_eq!(anemoi.c[0], F::from(37u32));
+    assert_eq!(
+        format!("{:?}", anemoi.d[0]),
+        "0x135b52945a13d9aa49b9b57c33cd568ba9ae5ce9ca4a2d06e7f3fbd4c666668c"
+    );
+    let digests = [
+        "0x20ae5df4c413393705fdc14deceaa875ab0ea3bcfe74f2a9b4a45b25c5eba3b1",
+        "0x1eea45044a6d1447ab15107c8f1fe203df5280ef4e5284bfa512beab276ef705",
+        "0x114c33b2a4725d365bfc84b79eda867fb056b8260200e0e0317d95a72bb4caa9",
+    ];
+    inputs
+        .iter()
+        .zip(digests.iter())
+        .for_each(|(input, digest)| {
+            assert_eq!(format!("{:?}", anemoi.hash_frs(input)), *digest);
+        });
+}
+
+fn test_fs_hash_helper<C: GKRConfig>(config: &Config<C>) {
+    let mut circuit = gen_mixing_circuit::<C>(4, 3);
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+
+    let mut prover = Prover::new(config);
+    prover.prepare_mem(&circuit);
+    let (_, proof) = prover.prove(&circuit);
+    let outputs = circuit_outputs(&circuit);
+
+    // the proof only verifies with the hash it was made with
+    for fs_hash in FS_HASHES {
+        let mut verifier_config = config.clone();
+        verifier_config.fs_hash = fs_hash.clone();
+        let verifier = Verifier::new(&verifier_config);
+        assert_eq!(
+            verifier.verify_with_outputs(&circuit, &outputs, &proof),
+            fs_hash == config.fs_hash
+        );
+    }
+}
+
+#[test]
+fn test_keccak_transcript() {
+    for fs_hash in FS_HASHES {
+        let mut config = Config::<M31ExtConfig>::new(GKRScheme::Vanilla);
+        config.fs_hash = fs_hash.clone();
+        test_fs_hash_helper::<M31ExtConfig>(&config);
+
+        let mut config = Config::<BN254Config>::new(GKRScheme::Vanilla);
+        config.fs_hash = fs_hash;
+        test_fs_hash_helper::<BN254Config>(&config);
+    }
+}
+
+#[test]
+fn test_statement_binding() {
+    let config = Config::<M31ExtConfig>::new(GKRScheme::Vanilla);
+    let mut circuit = gen_mixing_circuit::<M31ExtConfig>(4, 3);
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+
+    // the digest only depends on the description of the circuit
+    let mut other_inputs = circuit.clone();
+    other_inputs.layers[0].input_vals.evals[0] += <M31ExtConfig as GKRConfig>::Field::one();
+    other_inputs.evaluate();
+    assert_eq!(circuit.digest(), other_inputs.digest());
+
+    // a gate with a zero coefficient does not change the outputs, only the description
+    let mut padded = circuit.clone();
+    padded.layers[1].add.push(GateAdd {
+        i_ids: [0],
+        o_id: 0,
+        coef: <M31ExtConfig as GKRConfig>::CircuitField::zero(),
+        gate_type: 1,
+    });
+    padded.evaluate();
+    assert_ne!(circuit.digest(), padded.digest());
+
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&circuit);
+    let (_, proof) = prover.prove(&circuit);
+    let outputs = circuit_outputs(&circuit);
+
+    let verifier = Verifier::new(&config);
+    assert!(verifier.verify_with_outputs(&circuit, &outputs, &proof));
+    assert!(!verifier.verify_with_outputs(&padded, &outputs, &proof));
+
+    let mut other_config = config.clone();
+    other_config.security_bits += 1;
+    assert_ne!(config.fingerprint(), other_config.fingerprint());
+    let verifier = Verifier::new(&other_config);
+    assert!(!verifier.verify_with_outputs(&circuit, &outputs, &proof));
+}
+
+#[test]
+fn test_config_fingerprint() {
+    // every combination of the codes gives its own fingerprint
+    let mut fingerprints = vec![];
+    for fs_hash in FS_HASHES {
+        for scheme in [GKRScheme::Vanilla, GKRScheme::GkrSquare] {
+            for zk in [false, true] {
+                let mut config = Config::<M31ExtConfig>::new(scheme.clone());
+                config.fs_hash = fs_hash.clone();
+                config.zk = zk;
+                fingerprints.push(config.fingerprint());
+            }
+        }
+    }
+    fingerprints.push(Config::<BN254Config>::new(GKRScheme::Vanilla).fingerprint());
+    let num_fingerprints = fingerprints.len();
+    fingerprints.sort();
+    fingerprints.dedup();
+    assert_eq!(fingerprints.len(), num_fingerprints);
+}
+
+#[test]
+fn test_rand_coef_from_transcript() {
+    let config = Config::<BN254Config>::new(GKRScheme::Vanilla);
+    let mut circuit = gen_mixing_circuit::<BN254Config>(4, 3);
+    // the add gates of the second layer come after its 16 mul gates
+    circuit.layers[1].rand_coef_idx = (16..32).collect();
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+    assert!(circuit.has_rand_coef());
+
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&circuit);
+    let (claimed_v, proof) = prover.prove(&circuit);
+
+    let verifier = Verifier::new(&config);
+    assert!(verifier.verify(&circuit, &claimed_v, &proof));
+
+    // the proof is not valid for the circuit with the placeholder coefficients
+    let mut fixed_circuit = circuit.clone();
+    fixed_circuit.layers[1].rand_coef_idx.clear();
+    assert!(!verifier.verify(&fixed_circuit, &claimed_v, &proof));
+
+    let mut filled_circuit = circuit.clone();
+    filled_circuit.fill_rand_coef(&mut Transcript::<SHA256hasher>::new());
+    assert_ne!(
+        filled_circuit.layers[1].add[0].coef,
+        filled_circuit.layers[1].add[1].coef
+    );
+}
+
+#[cfg(feature = "grinding")]
+#[test]
+fn test_grinding_nonce() {
+    let mut config = Config::<M31ExtConfig>::new(GKRScheme::Vanilla);
+    config.grinding_bits = 12;
+    let mut circuit = gen_mixing_circuit::<M31ExtConfig>(4, 2);
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&circuit);
+    let (_, mut proof) = prover.prove(&circuit);
+    let outputs = circuit_outputs(&circuit);
+
+    let verifier = Verifier::new(&config);
+    assert!(verifier.verify_with_outputs(&circuit, &outputs, &proof));
+
+    // the nonce follows the raw commitment of the inputs
+    let nonce_idx = (1 << 4) * <<M31ExtConfig as GKRConfig>::Field as Field>::SIZE;
+    proof.bytes[nonce_idx] ^= 1;
+    assert!(!verifier.verify_with_outputs(&circuit, &outputs, &proof));
+}
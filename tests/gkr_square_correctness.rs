@@ -0,0 +1,133 @@
+use arith::Field;
+use expander_rs::{
+    BN254Config, Circuit, Config, ExpectedOutputs, GKRConfig, GKRScheme, M31ExtConfig,
+    PolynomialCommitmentType, Prover, Verifier,
+};
+
+use rand::Rng;
+
+mod common;
+use common::{circuit_outputs, gen_pow5_circuit, KZGSRSFile};
+
+#[test]
+fn test_gkr_square_correctness() {
+    test_gkr_square_correctness_helper::<M31ExtConfig>(&Config::<M31ExtConfig>::new(
+        GKRScheme::GkrSquare,
+    ));
+    test_gkr_square_correctness_helper::<BN254Config>(&Config::<BN254Config>::new(
+        GKRScheme::GkrSquare,
+    ));
+}
+
+#[test]
+fn test_gkr_square_correctness_kzg() {
+    let mut config = Config::<BN254Config>::new(GKRScheme::GkrSquare);
+    config.polynomial_commitment_type = PolynomialCommitmentType::KZG;
+    let srs = KZGSRSFile::new("gkr_square", 6);
+    config.pcs_srs_path = Some(srs.path.clone());
+    test_gkr_square_correctness_helper::<BN254Config>(&config);
+}
+
+#[test]
+fn test_gkr_square_kzg_mismatched_srs() {
+    let mut config = Config::<BN254Config>::new(GKRScheme::GkrSquare);
+    config.polynomial_commitment_type = PolynomialCommitmentType::KZG;
+    let prover_srs = KZGSRSFile::new("gkr_square_prover", 4);
+    config.pcs_srs_path = Some(prover_srs.path.clone());
+    let mut circuit = gen_pow5_circuit::<BN254Config>(4, 3);
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&circuit);
+    let (claimed_v, proof) = prover.prove(&circuit);
+    assert!(Verifier::new(&config).verify(&circuit, &claimed_v, &proof));
+
+    // the same proof checked against the parameters of another ceremony
+    let verifier_srs = KZGSRSFile::new("gkr_square_verifier", 4);
+    config.pcs_srs_path = Some(verifier_srs.path.clone());
+    assert!(!Verifier::new(&config).verify(&circuit, &claimed_v, &proof));
+}
+
+#[test]
+fn test_gkr_square_correctness_hyrax() {
+    let mut config = Config::<BN254Config>::new(GKRScheme::GkrSquare);
+    config.polynomial_commitment_type = PolynomialCommitmentType::Hyrax;
+    test_gkr_square_correctness_helper::<BN254Config>(&config);
+}
+
+#[test]
+fn test_gkr_square_correctness_orion() {
+    let mut config = Config::<M31ExtConfig>::new(GKRScheme::GkrSquare);
+    config.polynomial_commitment_type = PolynomialCommitmentType::Orion;
+    test_gkr_square_correctness_helper::<M31ExtConfig>(&config);
+}
+
+#[test]
+fn test_gkr_square_correctness_fri() {
+    let mut config = Config::<M31ExtConfig>::new(GKRScheme::GkrSquare);
+    config.polynomial_commitment_type = PolynomialCommitmentType::FRI;
+    test_gkr_square_correctness_helper::<M31ExtConfig>(&config);
+}
+
+fn test_gkr_square_correctness_helper<C: GKRConfig>(config: &Config<C>) {
+    let mut circuit = gen_pow5_circuit::<C>(4, 3);
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+    println!("Circuit evaluated.");
+
+    let mut prover = Prover::new(config);
+    prover.prepare_mem(&circuit);
+    let (claimed_v, proof) = prover.prove(&circuit);
+    println!("Proof generated. Size: {} bytes", proof.bytes.len());
+
+    let verifier = Verifier::new(config);
+    let outputs = circuit_outputs(&circuit);
+    assert!(verifier.verify_with_outputs(&circuit, &outputs, &proof));
+    println!("Correct proof verified.");
+
+    if config.polynomial_commitment_type != PolynomialCommitmentType::Raw {
+        // the witness is not needed by the verifier
+        let mut public_circuit = circuit.clone();
+        public_circuit.layers[0]
+            .input_vals
+            .evals
+            .iter_mut()
+            .for_each(|v| *v = C::Field::zero());
+        assert!(verifier.verify_with_outputs(&public_circuit, &outputs, &proof));
+        println!("Correct proof verified without the witness.");
+    }
+
+    let mut bad_proof = proof.clone();
+    let rng = &mut rand::thread_rng();
+    let random_idx = rng.gen_range(0..bad_proof.bytes.len());
+    let random_change = rng.gen_range(1..256) as u8;
+    bad_proof.bytes[random_idx] ^= random_change;
+    assert!(!verifier.verify_with_outputs(&circuit, &outputs, &bad_proof));
+    println!("Bad proof rejected.");
+
+    // bytes that are not field elements, and a truncated proof, are rejected without panicking
+    let mut bad_proof = proof.clone();
+    bad_proof.bytes[..32].fill(0xff);
+    assert!(!verifier.verify_with_outputs(&circuit, &outputs, &bad_proof));
+    let mut short_proof = proof.clone();
+    short_proof.bytes.truncate(random_idx);
+    assert!(!verifier.verify_with_outputs(&circuit, &outputs, &short_proof));
+    println!("Malformed proofs rejected.");
+
+    // a wrong claimed output must be rejected as well
+    let bad_claimed_v = claimed_v + C::Field::one();
+    assert!(!verifier.verify(&circuit, &bad_claimed_v, &proof));
+    println!("Bad claim rejected.");
+
+    // the claim is computed from the expected outputs, which must be the right ones
+    let mut bad_outputs = circuit.layers.last().unwrap().output_vals.evals.clone();
+    bad_outputs[0] += C::Field::one();
+    assert!(!verifier.verify_with_outputs(&circuit, &ExpectedOutputs::Values(bad_outputs), &proof));
+    assert!(!verifier.verify_with_outputs(
+        &circuit,
+        &ExpectedOutputs::Values(vec![C::Field::zero()]),
+        &proof
+    ));
+    println!("Bad outputs rejected.");
+}
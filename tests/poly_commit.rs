@@ -0,0 +1,224 @@
+use arith::{Field, MultiLinearPoly, SimdM31Ext3};
+use expander_rs::{
+    transparent_setup_rng, BN254Config, BasefoldPCS, GKRConfig, HyraxPCS, M31ExtConfig,
+    MultilinearKZG, MultilinearKZGParams, OrionCode, OrionPCS, PCSInstance, PolynomialCommitment,
+    RawPCS, SHA256hasher, Transcript, MERKLE_HASH_SIZE, ORION_CODE_DISTANCE, ORION_CODE_R,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn test_pcs_helper<C: GKRConfig, P: PolynomialCommitment<C>>(num_vars: usize) {
+    let mut rng = StdRng::seed_from_u64(1234);
+    let params = P::setup(num_vars, &mut rng);
+
+    let poly_vals = (0..1 << num_vars)
+        .map(|_| C::Field::random_unsafe(&mut rng))
+        .collect::<Vec<_>>();
+    let x = (0..num_vars)
+        .map(|_| C::ChallengeField::random_unsafe(&mut rng))
+        .collect::<Vec<_>>();
+    let y = MultiLinearPoly::<C::Field>::eval_multilinear(&poly_vals, &x);
+
+    let (commitment, aux) = P::commit(&params, &poly_vals);
+    let mut buffer = vec![];
+    P::serialize_commitment(&commitment, &mut buffer);
+    assert_eq!(buffer.len(), P::commitment_size(&params));
+    assert!(P::deserialize_commitment(&params, &buffer[..buffer.len() - 1]).is_none());
+    let commitment = P::deserialize_commitment(&params, buffer.as_slice()).unwrap();
+
+    let mut transcript = Transcript::<SHA256hasher>::new();
+    let opening = P::open(&params, &poly_vals, &aux, &x, &mut transcript);
+    let mut buffer = vec![];
+    P::serialize_opening(&opening, &mut buffer);
+    assert_eq!(buffer.len(), P::opening_size(&params));
+    if !buffer.is_empty() {
+        assert!(P::deserialize_opening(&params, &buffer[..buffer.len() - 1]).is_none());
+    }
+    let opening = P::deserialize_opening(&params, buffer.as_slice()).unwrap();
+
+    let mut transcript = Transcript::<SHA256hasher>::new();
+    assert!(P::verify(
+        &params,
+        &commitment,
+        &x,
+        y,
+        &opening,
+        &mut transcript
+    ));
+
+    let mut transcript = Transcript::<SHA256hasher>::new();
+    assert!(!P::verify(
+        &params,
+        &commitment,
+        &x,
+        y + C::Field::one(),
+        &opening,
+        &mut transcript
+    ));
+}
+
+#[test]
+fn test_raw_pcs() {
+    test_pcs_helper::<M31ExtConfig, RawPCS>(8);
+    test_pcs_helper::<BN254Config, RawPCS>(8);
+}
+
+#[test]
+fn test_kzg_pcs() {
+    test_pcs_helper::<BN254Config, MultilinearKZG>(1);
+    test_pcs_helper::<BN254Config, MultilinearKZG>(8);
+}
+
+#[test]
+fn test_kzg_mismatched_params() {
+    let num_vars = 4;
+    let mut rng = StdRng::seed_from_u64(1234);
+    let params = <MultilinearKZG as PolynomialCommitment<BN254Config>>::setup(num_vars, &mut rng);
+    let other_params =
+        <MultilinearKZG as PolynomialCommitment<BN254Config>>::setup(num_vars, &mut rng);
+
+    let poly_vals = (0..1 << num_vars)
+        .map(|_| <BN254Config as GKRConfig>::Field::random_unsafe(&mut rng))
+        .collect::<Vec<_>>();
+    let x = (0..num_vars)
+        .map(|_| <BN254Config as GKRConfig>::ChallengeField::random_unsafe(&mut rng))
+        .collect::<Vec<_>>();
+    let y = MultiLinearPoly::eval_multilinear(&poly_vals, &x);
+
+    let (commitment, aux) = MultilinearKZG::commit(&params, &poly_vals);
+    let opening = MultilinearKZG::open(
+        &params,
+        &poly_vals,
+        &aux,
+        &x,
+        &mut Transcript::<SHA256hasher>::new(),
+    );
+    assert!(MultilinearKZG::verify(
+        &params,
+        &commitment,
+        &x,
+        y,
+        &opening,
+        &mut Transcript::<SHA256hasher>::new()
+    ));
+    assert!(!MultilinearKZG::verify(
+        &other_params,
+        &commitment,
+        &x,
+        y,
+        &opening,
+        &mut Transcript::<SHA256hasher>::new()
+    ));
+
+    // the statement binds the parameters, and only those of a trusted setup
+    assert_ne!(
+        MultilinearKZG::params_digest(&params),
+        MultilinearKZG::params_digest(&other_params)
+    );
+    let raw_params = <RawPCS as PolynomialCommitment<BN254Config>>::setup(num_vars, &mut rng);
+    assert!(<RawPCS as PolynomialCommitment<BN254Config>>::params_digest(&raw_params).is_none());
+}
+
+#[test]
+fn test_kzg_params_round_trip() {
+    let mut rng = StdRng::seed_from_u64(1234);
+    let params = <MultilinearKZG as PolynomialCommitment<BN254Config>>::setup(5, &mut rng);
+    let mut buffer = vec![];
+    params.serialize_into(&mut buffer);
+    assert_eq!(
+        MultilinearKZGParams::deserialize_from(buffer.as_slice()),
+        params
+    );
+    assert_ne!(params.digest(), params.trim(4).digest());
+
+    // a smaller circuit uses the last variables of the ceremony
+    let trimmed = params.trim(3);
+    assert_eq!(trimmed.num_vars, 3);
+    assert_eq!(trimmed.lagrange_bases, params.lagrange_bases[2..].to_vec());
+    assert_eq!(trimmed.tau_h, params.tau_h[2..].to_vec());
+}
+
+#[test]
+#[should_panic(expected = "trusted setup")]
+fn test_kzg_seeded_setup_refused() {
+    PCSInstance::<BN254Config, MultilinearKZG>::new(4);
+}
+
+#[test]
+fn test_orion_pcs() {
+    test_pcs_helper::<M31ExtConfig, OrionPCS>(1);
+    test_pcs_helper::<M31ExtConfig, OrionPCS>(8);
+    test_pcs_helper::<M31ExtConfig, OrionPCS>(16);
+}
+
+#[test]
+fn test_orion_code() {
+    // the code is systematic with rate 1 / r, on messages of any length
+    for msg_len in [7, 64, 1000, 1 << 14] {
+        let code = OrionCode::new(msg_len, StdRng::seed_from_u64(0));
+        assert_eq!(
+            code.codeword_len(),
+            (ORION_CODE_R * msg_len as f64).ceil() as usize
+        );
+        let msg = (0..msg_len)
+            .map(|i| SimdM31Ext3::from(i as u32 + 1))
+            .collect::<Vec<_>>();
+        let codeword = code.encode(&msg);
+        assert_eq!(codeword.len(), code.codeword_len());
+        assert_eq!(&codeword[..msg_len], msg.as_slice());
+    }
+}
+
+#[test]
+fn test_orion_security_bits() {
+    // the number of queries follows the target of the configuration
+    let setup = |security_bits| {
+        <OrionPCS as PolynomialCommitment<M31ExtConfig>>::setup_with_security_bits(
+            10,
+            security_bits,
+            StdRng::seed_from_u64(0),
+        )
+    };
+    assert!(setup(128).query_num > setup(100).query_num);
+    assert_eq!(
+        setup(100).query_num,
+        (100.0 / -(1.0 - ORION_CODE_DISTANCE / 3.0).log2()).ceil() as usize
+    );
+}
+
+#[test]
+fn test_orion_opening_size() {
+    // the queries of the proximity test dominate the opening, so the assumed distance
+    // must not drive their number up: for 2^20 packed evaluations, the opening holds at
+    // most 2^18 field elements and 2^16 hashes
+    let params =
+        <OrionPCS as PolynomialCommitment<M31ExtConfig>>::setup(20, StdRng::seed_from_u64(1234));
+    assert!(params.query_num <= 3000);
+    assert!(
+        <OrionPCS as PolynomialCommitment<M31ExtConfig>>::opening_size(&params)
+            <= (1 << 18) * SimdM31Ext3::SIZE + (1 << 16) * MERKLE_HASH_SIZE
+    );
+}
+
+#[test]
+fn test_basefold_pcs() {
+    test_pcs_helper::<M31ExtConfig, BasefoldPCS>(0);
+    test_pcs_helper::<M31ExtConfig, BasefoldPCS>(1);
+    test_pcs_helper::<M31ExtConfig, BasefoldPCS>(10);
+}
+
+#[test]
+fn test_hyrax_pcs() {
+    test_pcs_helper::<BN254Config, HyraxPCS>(1);
+    test_pcs_helper::<BN254Config, HyraxPCS>(7);
+    test_pcs_helper::<BN254Config, HyraxPCS>(8);
+}
+
+#[test]
+fn test_hyrax_transparent_setup() {
+    // the generators only depend on the domain
+    let setup = |domain: &[u8]| {
+        <HyraxPCS as PolynomialCommitment<BN254Config>>::setup(6, transparent_setup_rng(domain))
+    };
+    assert_eq!(setup(b"domain"), setup(b"domain"));
+    assert_ne!(setup(b"domain"), setup(b"other domain"));
+}
@@ -0,0 +1,227 @@
+use arith::{Field, FieldSerde};
+use expander_rs::{
+    BN254Config, Circuit, Config, FiatShamirHashType, GKRConfig, GKRScheme, M31ExtConfig,
+    PolynomialCommitmentType, Proof, ProofFormatError, ProofHeader, Prover, StructuredProof,
+    Verifier, PROOF_MAGIC, PROOF_VERSION,
+};
+
+mod common;
+use common::{circuit_outputs, gen_mixing_circuit, gen_pow5_circuit};
+
+fn check_structured_proof<C: GKRConfig>(circuit: &mut Circuit<C>, config: &Config<C>) {
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+
+    let mut prover = Prover::new(config);
+    prover.prepare_mem(circuit);
+    let (claimed_v, proof) = prover.prove(circuit);
+
+    let header = ProofHeader::new(config);
+    let structured =
+        StructuredProof::parse_with_header(header.clone(), &proof, circuit, config).unwrap();
+    let derived = StructuredProof::parse(&proof, circuit, config).unwrap();
+    assert_eq!(derived.header, header);
+    assert_eq!(derived.to_proof().bytes, proof.bytes);
+    assert_eq!(structured.to_proof().bytes, proof.bytes);
+    // the nonce is only there when the header asks for grinding
+    assert_eq!(
+        structured.grinding_nonce.is_some(),
+        header.grinding_bits > 0
+    );
+
+    // the encoding is parsed without the circuit
+    let mut encoding = vec![];
+    structured.serialize_into(&mut encoding);
+    let decoded = StructuredProof::<C>::deserialize_from(&encoding[..]).unwrap();
+    let mut decoded_encoding = vec![];
+    decoded.serialize_into(&mut decoded_encoding);
+    assert_eq!(decoded_encoding, encoding);
+    assert_eq!(decoded.header, header);
+    assert_eq!(decoded.to_proof().bytes, proof.bytes);
+    let mut written = vec![];
+    proof
+        .serialize_with_header(circuit, config, &mut written)
+        .unwrap();
+    assert_eq!(written, encoding);
+
+    assert_eq!(structured.layers.len(), circuit.layers.len());
+    for (layer, layer_proof) in circuit.layers.iter().rev().zip(structured.layers.iter()) {
+        let rounds_num = match config.gkr_scheme {
+            GKRScheme::Vanilla => 2 * layer.input_var_num,
+            GKRScheme::GkrSquare => layer.input_var_num,
+        };
+        assert_eq!(layer_proof.rounds.len(), rounds_num);
+        assert_eq!(layer_proof.zk.is_some(), config.zk);
+    }
+
+    // the first sumcheck round adds up to the claim on the outputs, without the mask g
+    let first_round = &structured.layers[0].rounds[0];
+    if config.gkr_scheme == GKRScheme::Vanilla
+        && !config.zk
+        && circuit.layers.last().unwrap().const_.is_empty()
+    {
+        assert_eq!(first_round[0] + first_round[1], claimed_v);
+    }
+
+    // the layout follows the header, which must match the circuit and the configuration
+    let mut other_header = header.clone();
+    other_header.security_bits += 1;
+    assert!(matches!(
+        StructuredProof::parse_with_header(other_header, &proof, circuit, config),
+        Err(ProofFormatError::ConfigMismatch("security level"))
+    ));
+
+    // a message edited in the typed view reaches the verifier
+    let verifier = Verifier::new(config);
+    assert!(verifier.verify(circuit, &claimed_v, &structured.to_proof()));
+    let mut tampered = structured.clone();
+    tampered.layers[0].vx_claim += C::Field::from(1u32);
+    assert!(!verifier.verify(circuit, &claimed_v, &tampered.to_proof()));
+
+    // all the bytes are accounted for
+    let mut longer = proof.clone();
+    longer.bytes.push(0);
+    assert!(matches!(
+        StructuredProof::parse(&longer, circuit, config),
+        Err(ProofFormatError::TrailingBytes(1))
+    ));
+    let mut shorter = proof.clone();
+    shorter.bytes.pop();
+    assert!(matches!(
+        StructuredProof::parse(&shorter, circuit, config),
+        Err(ProofFormatError::Truncated(_))
+    ));
+}
+
+#[test]
+fn test_structured_proof() {
+    let mut config = Config::<M31ExtConfig>::new(GKRScheme::Vanilla);
+    check_structured_proof(&mut gen_mixing_circuit(4, 3), &config);
+
+    // a target the field cannot reach leaves the proof as it is
+    config.security_bits = 200;
+    check_structured_proof(&mut gen_mixing_circuit(4, 3), &config);
+
+    let config = Config::<BN254Config>::new(GKRScheme::Vanilla);
+    check_structured_proof(&mut gen_mixing_circuit(4, 3), &config);
+
+    let mut config = Config::<BN254Config>::new(GKRScheme::Vanilla);
+    config.zk = true;
+    config.polynomial_commitment_type = PolynomialCommitmentType::Hyrax;
+    check_structured_proof(&mut gen_mixing_circuit(4, 3), &config);
+
+    let config = Config::<M31ExtConfig>::new(GKRScheme::GkrSquare);
+    check_structured_proof(&mut gen_pow5_circuit(4, 2), &config);
+}
+
+#[test]
+fn test_proof_encoding() {
+    let config = Config::<M31ExtConfig>::new(GKRScheme::Vanilla);
+    let mut circuit = gen_mixing_circuit::<M31ExtConfig>(4, 2);
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&circuit);
+    let (_, proof) = prover.prove(&circuit);
+
+    let mut bytes = vec![];
+    proof
+        .serialize_with_header(&circuit, &config, &mut bytes)
+        .unwrap();
+    assert_eq!(bytes[..4], PROOF_MAGIC);
+    assert_eq!(bytes[4..6], PROOF_VERSION.to_le_bytes());
+    let header = ProofHeader::deserialize_from(&bytes[..]).unwrap();
+    assert_eq!(header, ProofHeader::new(&config));
+    assert_eq!(header.field_size, config.field_size);
+    assert_eq!(header.security_bits, config.security_bits);
+
+    let decoded = Proof::deserialize_with_header(&circuit, &config, &bytes[..]).unwrap();
+    assert_eq!(decoded.bytes, proof.bytes);
+    let verifier = Verifier::new(&config);
+    assert!(verifier.verify_with_outputs(&circuit, &circuit_outputs(&circuit), &decoded));
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] ^= 1;
+    assert_eq!(
+        Proof::deserialize_with_header(&circuit, &config, &bad_magic[..]).unwrap_err(),
+        ProofFormatError::BadMagic
+    );
+
+    let mut bad_version = bytes.clone();
+    bad_version[4] += 1;
+    assert_eq!(
+        Proof::deserialize_with_header(&circuit, &config, &bad_version[..]).unwrap_err(),
+        ProofFormatError::UnsupportedVersion(PROOF_VERSION + 1)
+    );
+
+    let mut bad_tag = bytes.clone();
+    bad_tag[9] = 0xff;
+    assert_eq!(
+        Proof::deserialize_with_header(&circuit, &config, &bad_tag[..]).unwrap_err(),
+        ProofFormatError::UnknownTag("hash", 0xff)
+    );
+
+    let mut other_config = config.clone();
+    other_config.fs_hash = FiatShamirHashType::Keccak256;
+    assert_eq!(
+        Proof::deserialize_with_header(&circuit, &other_config, &bytes[..]).unwrap_err(),
+        ProofFormatError::ConfigMismatch("hash")
+    );
+
+    let mut other_config = config.clone();
+    other_config.security_bits = 200;
+    assert_eq!(
+        Proof::deserialize_with_header(&circuit, &other_config, &bytes[..]).unwrap_err(),
+        ProofFormatError::ConfigMismatch("security level")
+    );
+    let mut other_grinding = bytes.clone();
+    other_grinding[ProofHeader::SIZE - 2] += 1;
+    assert_eq!(
+        Proof::deserialize_with_header(&circuit, &config, &other_grinding[..]).unwrap_err(),
+        ProofFormatError::ConfigMismatch("grinding bits")
+    );
+
+    assert!(matches!(
+        Proof::deserialize_with_header(&circuit, &config, &bytes[..bytes.len() - 1]),
+        Err(ProofFormatError::Truncated(_))
+    ));
+}
+
+#[test]
+fn test_canonical_encoding() {
+    // the openings of hyrax start with field elements
+    let mut config = Config::<BN254Config>::new(GKRScheme::Vanilla);
+    config.polynomial_commitment_type = PolynomialCommitmentType::Hyrax;
+    let mut circuit = gen_mixing_circuit::<BN254Config>(4, 2);
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&circuit);
+    let (_, proof) = prover.prove(&circuit);
+    let mut structured = StructuredProof::parse(&proof, &circuit, &config).unwrap();
+
+    // the first element of the opening read as the claim on the private inputs, which the
+    // circuit does not have, gives the same proof bytes in other sections
+    let opening = &mut structured.openings[0];
+    assert_eq!(opening.private_v, None);
+    type F = <BN254Config as GKRConfig>::Field;
+    opening.private_v = Some(F::try_deserialize_from(&opening.opening[..]).unwrap());
+    opening.opening.drain(..F::SIZE);
+    assert_eq!(structured.to_proof().bytes, proof.bytes);
+
+    let mut bytes = vec![];
+    structured.serialize_into(&mut bytes);
+    assert!(StructuredProof::<BN254Config>::deserialize_from(&bytes[..]).is_ok());
+    assert_eq!(
+        Proof::deserialize_with_header(&circuit, &config, &bytes[..]).unwrap_err(),
+        ProofFormatError::CircuitMismatch
+    );
+
+    // the encoding is for a single field
+    assert!(matches!(
+        StructuredProof::<M31ExtConfig>::deserialize_from(&bytes[..]),
+        Err(ProofFormatError::ConfigMismatch("field"))
+    ));
+}
@@ -0,0 +1,72 @@
+use arith::Field;
+use expander_rs::{
+    BN254Config, Config, GKRConfig, GKRScheme, M31ExtConfig, PolynomialCommitmentType, Prover,
+    Verifier,
+};
+
+mod common;
+use common::{circuit_outputs, gen_mixing_circuit, gen_pow5_circuit, KZGSRSFile};
+
+fn test_public_input_helper<C: GKRConfig>(config: &Config<C>) {
+    let mut circuit = match config.gkr_scheme {
+        GKRScheme::Vanilla => gen_mixing_circuit::<C>(4, 3),
+        GKRScheme::GkrSquare => gen_pow5_circuit::<C>(4, 3),
+    };
+    circuit.set_public_input_offset(4);
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+    assert_eq!(circuit.log_private_input_size(), 2);
+    assert_eq!(circuit.private_input_vals().len(), 4);
+    assert_eq!(circuit.public_input_vals().len(), 12);
+
+    let mut prover = Prover::new(config);
+    prover.prepare_mem(&circuit);
+    let (_, proof) = prover.prove(&circuit);
+
+    let verifier = Verifier::new(config);
+    let outputs = circuit_outputs(&circuit);
+    assert!(verifier.verify_with_outputs(&circuit, &outputs, &proof));
+    println!("Correct proof verified.");
+
+    // the verifier only needs the public inputs
+    let mut public_circuit = circuit.clone();
+    public_circuit.layers[0].input_vals.evals[..4]
+        .iter_mut()
+        .for_each(|v| *v = C::Field::zero());
+    assert!(verifier.verify_with_outputs(&public_circuit, &outputs, &proof));
+    println!("Correct proof verified with the public inputs only.");
+
+    // but they must be the ones the proof was made for
+    let mut wrong_circuit = public_circuit.clone();
+    wrong_circuit.layers[0].input_vals.evals[9] += C::Field::one();
+    assert!(!verifier.verify_with_outputs(&wrong_circuit, &outputs, &proof));
+    println!("Wrong public inputs rejected.");
+}
+
+#[test]
+fn test_public_input_raw() {
+    for scheme in [GKRScheme::Vanilla, GKRScheme::GkrSquare] {
+        test_public_input_helper::<M31ExtConfig>(&Config::<M31ExtConfig>::new(scheme.clone()));
+        test_public_input_helper::<BN254Config>(&Config::<BN254Config>::new(scheme));
+    }
+}
+
+#[test]
+fn test_public_input_kzg() {
+    for scheme in [GKRScheme::Vanilla, GKRScheme::GkrSquare] {
+        let mut config = Config::<BN254Config>::new(scheme);
+        config.polynomial_commitment_type = PolynomialCommitmentType::KZG;
+        let srs = KZGSRSFile::new("public_input", 6);
+        config.pcs_srs_path = Some(srs.path.clone());
+        test_public_input_helper::<BN254Config>(&config);
+    }
+}
+
+#[test]
+fn test_public_input_orion() {
+    for scheme in [GKRScheme::Vanilla, GKRScheme::GkrSquare] {
+        let mut config = Config::<M31ExtConfig>::new(scheme);
+        config.polynomial_commitment_type = PolynomialCommitmentType::Orion;
+        test_public_input_helper::<M31ExtConfig>(&config);
+    }
+}
@@ -0,0 +1,134 @@
+use arith::Field;
+use expander_rs::{
+    sumcheck_soundness_bits, BN254Config, Circuit, CircuitLayer, Config, ExpectedOutputs,
+    GKRConfig, GKRScheme, GateAdd, M31ExtConfig, Prover, Soundness, Verifier,
+};
+
+mod common;
+use common::{circuit_outputs, gen_mixing_circuit};
+
+#[test]
+fn test_sumcheck_soundness_bits() {
+    // rounds of degree 2 over a challenge field of at least 2^92 elements
+    let bits = sumcheck_soundness_bits(&GKRScheme::Vanilla, false, 93);
+    assert!((bits - 91.0).abs() < 1e-9);
+    // the masks raise the degree to 3
+    let bits = sumcheck_soundness_bits(&GKRScheme::Vanilla, true, 93);
+    assert!((bits - (92.0 - 3f64.log2())).abs() < 1e-9);
+    // rounds of degree 7
+    let bits = sumcheck_soundness_bits(&GKRScheme::GkrSquare, false, 93);
+    assert!((bits - (92.0 - 7f64.log2())).abs() < 1e-9);
+}
+
+#[test]
+fn test_soundness_of_worst_round() {
+    // the output point of 4 variables is the worst round, whatever the depth
+    let mut config = Config::<M31ExtConfig>::new(GKRScheme::Vanilla);
+    for depth in [1, 3, 8] {
+        let circuit = gen_mixing_circuit::<M31ExtConfig>(4, depth);
+        let soundness = Soundness::new(&circuit, &config);
+        assert!((soundness.output_bits - 90.0).abs() < 1e-9);
+        assert!((soundness.achieved_bits() - 90.0).abs() < 1e-9);
+    }
+
+    // the degree 7 of GKR^2 is worse than the output point
+    config.gkr_scheme = GKRScheme::GkrSquare;
+    let circuit = gen_mixing_circuit::<M31ExtConfig>(4, 3);
+    let soundness = Soundness::new(&circuit, &config);
+    assert!((soundness.achieved_bits() - (92.0 - 7f64.log2())).abs() < 1e-9);
+
+    // the bn254 scalar field is large enough for the targeted level
+    let circuit = gen_mixing_circuit::<BN254Config>(4, 3);
+    let config = Config::<BN254Config>::new(GKRScheme::Vanilla);
+    assert!(Soundness::new(&circuit, &config).achieved_bits() >= config.security_bits as f64);
+}
+
+#[test]
+fn test_proof_below_target() {
+    // the extension of M31 cannot reach 200 bits, the proof reports what it achieves
+    let mut config = Config::<M31ExtConfig>::new(GKRScheme::Vanilla);
+    config.security_bits = 200;
+    let mut circuit = gen_mixing_circuit::<M31ExtConfig>(4, 3);
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&circuit);
+    let (claimed_v, proof) = prover.prove(&circuit);
+    let soundness = proof.soundness.unwrap();
+    assert_eq!(soundness, Soundness::new(&circuit, &config));
+    assert!(soundness.achieved_bits() < 200.0);
+
+    let verifier = Verifier::new(&config);
+    assert!(verifier.verify(&circuit, &claimed_v, &proof));
+    assert!(verifier.verify_with_outputs(&circuit, &circuit_outputs(&circuit), &proof));
+}
+
+// the constraints that the inputs are all equal, combined with the coefficients of the add
+// gates of the second layer: the outputs vanish for any coefficients when they hold
+fn gen_equality_circuit<C: GKRConfig>(var_num: usize) -> Circuit<C> {
+    let n = 1 << var_num;
+    let mut differences = CircuitLayer::<C> {
+        input_var_num: var_num,
+        output_var_num: var_num,
+        ..Default::default()
+    };
+    let mut combination = differences.clone();
+    for i in 0..n {
+        differences.add.push(GateAdd {
+            i_ids: [i],
+            o_id: i,
+            coef: C::CircuitField::one(),
+            gate_type: 1,
+        });
+        differences.add.push(GateAdd {
+            i_ids: [(i + 1) % n],
+            o_id: i,
+            coef: -C::CircuitField::one(),
+            gate_type: 1,
+        });
+        combination.add.push(GateAdd {
+            i_ids: [i],
+            o_id: i,
+            coef: C::CircuitField::one(),
+            gate_type: 1,
+        });
+    }
+    let mut circuit = Circuit::default();
+    circuit.layers.push(differences);
+    circuit.layers.push(combination);
+    circuit
+}
+
+#[test]
+fn test_rand_coef_soundness() {
+    let config = Config::<M31ExtConfig>::new(GKRScheme::Vanilla);
+    let mut circuit = gen_equality_circuit::<M31ExtConfig>(4);
+    circuit.layers[1].rand_coef_idx = (0..16).collect();
+
+    // 16 coefficients from the 31 bits of M31 leave 26 bits, far below GKR over the
+    // extension: the challenge field does not help with them
+    let soundness = Soundness::new(&circuit, &config);
+    assert!((soundness.output_bits - 26.0).abs() < 1e-6);
+    assert_eq!(soundness.achieved_bits(), soundness.output_bits);
+    assert!(soundness.sumcheck_bits > 90.0);
+
+    // equal inputs satisfy the constraints whatever the coefficients
+    circuit.layers[0].input_vals.evals = vec![<M31ExtConfig as GKRConfig>::Field::one(); 16];
+    circuit.evaluate();
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&circuit);
+    let (claimed_v, proof) = prover.prove(&circuit);
+    assert_eq!(proof.soundness.unwrap(), soundness);
+    assert_eq!(claimed_v, <M31ExtConfig as GKRConfig>::Field::zero());
+
+    let verifier = Verifier::new(&config);
+    assert!(verifier.verify_with_outputs(&circuit, &ExpectedOutputs::Zero, &proof));
+    assert!(verifier.verify(&circuit, &claimed_v, &proof));
+
+    // other inputs do not
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+    let (_, proof) = prover.prove(&circuit);
+    assert!(!verifier.verify_with_outputs(&circuit, &ExpectedOutputs::Zero, &proof));
+}
@@ -0,0 +1,90 @@
+use arith::{Field, MultiLinearPoly};
+use ark_std::test_rng;
+use expander_rs::{
+    sumcheck_poly_prove, sumcheck_poly_verify, BN254Config, GKRConfig, M31ExtConfig, SHA256hasher,
+    SumOfProducts, Transcript,
+};
+
+fn random_poly<C: GKRConfig>(var_num: usize) -> MultiLinearPoly<C::Field> {
+    let mut rng = test_rng();
+    MultiLinearPoly {
+        var_num,
+        evals: (0..1 << var_num)
+            .map(|_| C::Field::random_unsafe(&mut rng))
+            .collect(),
+    }
+}
+
+fn test_sumcheck_poly_helper<C: GKRConfig>() {
+    let var_num = 5;
+    let mut rng = test_rng();
+
+    // a * b * c + 3 * b^2 + c
+    let mut poly = SumOfProducts::<C>::new(var_num);
+    let a = poly.add_poly(random_poly::<C>(var_num));
+    let b = poly.add_poly(random_poly::<C>(var_num));
+    let c = poly.add_poly(random_poly::<C>(var_num));
+    poly.add_product(C::ChallengeField::random_unsafe(&mut rng), &[a, b, c]);
+    poly.add_product(C::ChallengeField::from(3), &[b, b]);
+    poly.add_product(C::ChallengeField::one(), &[c]);
+    assert_eq!(poly.degree(), 3);
+    let sum = poly.sum();
+
+    let mut transcript = Transcript::<SHA256hasher>::new();
+    let (r, poly_evals) = sumcheck_poly_prove(&poly, &mut transcript);
+    for (p, v) in poly.polys.iter().zip(poly_evals.iter()) {
+        assert_eq!(MultiLinearPoly::eval_multilinear(&p.evals, &r), *v);
+    }
+    let mut proof = transcript.proof;
+
+    let mut transcript = Transcript::<SHA256hasher>::new();
+    let (verified, r_verifier, claim) = sumcheck_poly_verify::<C, _>(
+        var_num,
+        poly.degree(),
+        sum,
+        &mut proof.clone(),
+        &mut transcript,
+    )
+    .unwrap();
+    assert!(verified);
+    assert_eq!(r_verifier, r);
+    assert_eq!(claim, poly.combine(&poly_evals));
+
+    // another sum is rejected
+    let mut transcript = Transcript::<SHA256hasher>::new();
+    let (verified, _, _) = sumcheck_poly_verify::<C, _>(
+        var_num,
+        poly.degree(),
+        sum + C::Field::one(),
+        &mut proof.clone(),
+        &mut transcript,
+    )
+    .unwrap();
+    assert!(!verified);
+
+    // a truncated proof is malformed
+    let mut short_proof = proof.clone();
+    short_proof.bytes.pop();
+    let mut transcript = Transcript::<SHA256hasher>::new();
+    assert!(sumcheck_poly_verify::<C, _>(
+        var_num,
+        poly.degree(),
+        sum,
+        &mut short_proof,
+        &mut transcript
+    )
+    .is_none());
+
+    // and a proof with an edited round is rejected
+    proof.bytes[0] ^= 1;
+    let mut transcript = Transcript::<SHA256hasher>::new();
+    let result =
+        sumcheck_poly_verify::<C, _>(var_num, poly.degree(), sum, &mut proof, &mut transcript);
+    assert!(!matches!(result, Some((true, _, _))));
+}
+
+#[test]
+fn test_sumcheck_poly() {
+    test_sumcheck_poly_helper::<M31ExtConfig>();
+    test_sumcheck_poly_helper::<BN254Config>();
+}
@@ -0,0 +1,117 @@
+use arith::Field;
+use expander_rs::{
+    BN254Config, Config, ExpectedOutputs, GKRConfig, GKRScheme, M31ExtConfig,
+    PolynomialCommitmentType, Prover, Verifier, ZKSupportError,
+};
+
+mod common;
+use common::gen_mixing_circuit;
+
+fn test_zk_helper(public_input_offset: Option<usize>) {
+    let mut config = Config::<BN254Config>::new(GKRScheme::Vanilla);
+    config.zk = true;
+    config.polynomial_commitment_type = PolynomialCommitmentType::Hyrax;
+
+    let mut circuit = gen_mixing_circuit::<BN254Config>(4, 3);
+    if let Some(offset) = public_input_offset {
+        circuit.set_public_input_offset(offset);
+    }
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+    let outputs = ExpectedOutputs::Values(circuit.layers.last().unwrap().output_vals.evals.clone());
+
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&circuit);
+    let (claimed_v, proof) = prover.prove(&circuit);
+    let (claimed_v_2, proof_2) = prover.prove(&circuit);
+    // the masks are sampled for each proof
+    assert_ne!(proof.bytes, proof_2.bytes);
+
+    let verifier = Verifier::new(&config);
+    assert!(verifier.verify(&circuit, &claimed_v, &proof));
+    assert!(verifier.verify(&circuit, &claimed_v_2, &proof_2));
+    assert!(verifier.verify_with_outputs(&circuit, &outputs, &proof));
+    println!("Correct proof verified.");
+
+    assert!(!verifier.verify(
+        &circuit,
+        &(claimed_v + <<BN254Config as GKRConfig>::Field as Field>::one()),
+        &proof
+    ));
+    println!("Bad claim rejected.");
+
+    // the proof does not open the inputs, the verifier only needs the public ones
+    let mut public_circuit = circuit.clone();
+    let private_len = 1 << public_circuit.log_private_input_size();
+    public_circuit.layers[0].input_vals.evals[..private_len]
+        .iter_mut()
+        .for_each(|v| *v = <<BN254Config as GKRConfig>::Field as Field>::zero());
+    assert!(verifier.verify(&public_circuit, &claimed_v, &proof));
+
+    if public_input_offset.is_some() {
+        let mut wrong_circuit = public_circuit.clone();
+        wrong_circuit.layers[0].input_vals.evals[private_len + 1] +=
+            <<BN254Config as GKRConfig>::Field as Field>::one();
+        assert!(!verifier.verify(&wrong_circuit, &claimed_v, &proof));
+        println!("Wrong public inputs rejected.");
+    }
+}
+
+#[test]
+fn test_zk_gkr() {
+    test_zk_helper(None);
+}
+
+#[test]
+fn test_zk_gkr_with_public_inputs() {
+    test_zk_helper(Some(4));
+}
+
+#[test]
+fn test_zk_gkr_unsupported_config() {
+    let mut config = Config::<BN254Config>::new(GKRScheme::Vanilla);
+    let mut circuit = gen_mixing_circuit::<BN254Config>(4, 2);
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&circuit);
+    let (claimed_v, proof) = prover.prove(&circuit);
+
+    // the inputs are committed as for Hyrax, the other commitments are rejected
+    config.zk = true;
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&circuit);
+    assert_eq!(
+        prover.try_prove(&circuit).unwrap_err(),
+        ZKSupportError::UnsupportedPolynomialCommitment
+    );
+    assert!(!Verifier::new(&config).verify(&circuit, &claimed_v, &proof));
+
+    config.polynomial_commitment_type = PolynomialCommitmentType::Hyrax;
+    config.gkr_scheme = GKRScheme::GkrSquare;
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&circuit);
+    assert_eq!(
+        prover.try_prove(&circuit).unwrap_err(),
+        ZKSupportError::UnsupportedScheme
+    );
+    assert!(!Verifier::new(&config).verify(&circuit, &claimed_v, &proof));
+}
+
+#[test]
+fn test_zk_gkr_unsupported_field() {
+    let mut config = Config::<M31ExtConfig>::new(GKRScheme::Vanilla);
+    config.zk = true;
+    config.polynomial_commitment_type = PolynomialCommitmentType::Hyrax;
+
+    let mut circuit = gen_mixing_circuit::<M31ExtConfig>(4, 2);
+    circuit.set_random_bool_input_for_test();
+    circuit.evaluate();
+
+    let mut prover = Prover::new(&config);
+    prover.prepare_mem(&circuit);
+    assert_eq!(
+        prover.try_prove(&circuit).unwrap_err(),
+        ZKSupportError::UnsupportedCommitment
+    );
+}